@@ -1,4 +1,7 @@
-use cb_common::{config::load_pbs_config, utils::initialize_tracing_log};
+use cb_common::{
+    config::{load_pbs_config, PBS_MODULE_NAME},
+    utils::initialize_tracing_log,
+};
 use cb_pbs::{DefaultBuilderApi, PbsService, PbsState};
 use eyre::Result;
 
@@ -11,7 +14,7 @@ async fn main() -> Result<()> {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 
-    initialize_tracing_log();
+    let _guard = initialize_tracing_log(PBS_MODULE_NAME)?;
 
     // TODO: handle errors
     let pbs_config = load_pbs_config().expect("failed to load pbs config");
@@ -1,4 +1,7 @@
-use cb_common::{config::StartSignerConfig, utils::initialize_tracing_log};
+use cb_common::{
+    config::{StartSignerConfig, SIGNER_MODULE_NAME},
+    utils::initialize_tracing_log,
+};
 use cb_signer::service::SigningService;
 use eyre::Result;
 
@@ -11,8 +14,9 @@ async fn main() -> Result<()> {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 
-    initialize_tracing_log();
+    let _guard = initialize_tracing_log(SIGNER_MODULE_NAME)?;
 
     let config = StartSignerConfig::load_from_env()?;
+    SigningService::init_metrics()?;
     SigningService::run(config).await
 }
@@ -1,9 +1,14 @@
 pub mod prelude {
     pub use cb_common::{
         commit,
-        commit::request::SignRequest,
+        commit::request::{
+            GenerateProxyRequest, SignEcdsaMessageRequest, SignEcdsaRequest, SignMessageRequest,
+            SignRequest, SignValidatorRegistrationRequest,
+        },
+        commit::schema::{register_schema, MessageSchema, SchemaMessageRoot},
         config::{load_builder_module_config, load_commit_module_config, StartCommitModuleConfig},
         pbs::{BuilderEvent, BuilderEventClient, OnBuilderApiEvent},
+        signature::{verify_ecdsa_module_message, verify_signed_module_message},
         utils::{initialize_tracing_log, utcnow_ms, utcnow_ns, utcnow_sec, utcnow_us},
     };
     pub use cb_metrics::provider::MetricsProvider;
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+use cb_common::signer::{read_audit_head, read_audit_key, read_audit_log};
+use eyre::Result;
+
+/// Entries of the audit log to print, all filters are optional
+pub struct AuditFilter {
+    pub module_id: Option<String>,
+    pub pubkey: Option<BlsPublicKey>,
+    pub address: Option<Address>,
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+}
+
+pub fn handle_audit_verify(path: String, key_path: String, filter: AuditFilter) -> Result<()> {
+    println!("Verifying audit log: {}", path);
+
+    let key = read_audit_key(Path::new(&key_path))?;
+    // read before the entries, which are checked to include it
+    let head = read_audit_head(Path::new(&path), &key)?;
+    let entries = read_audit_log(Path::new(&path), &key)?;
+
+    let mut matching = 0;
+    for entry in &entries {
+        let record = &entry.record;
+
+        if filter.module_id.as_ref().is_some_and(|id| *id != record.module_id)
+            || filter.pubkey.is_some_and(|pubkey| record.pubkey != Some(pubkey))
+            || filter.address.is_some_and(|address| record.address != Some(address))
+            || filter.from_ms.is_some_and(|from| entry.timestamp_ms < from)
+            || filter.to_ms.is_some_and(|to| entry.timestamp_ms > to)
+        {
+            continue;
+        }
+
+        println!("{}", serde_json::to_string(entry)?);
+        matching += 1;
+    }
+
+    println!("Audit log is valid: {} entries, {} matching", entries.len(), matching);
+    match head {
+        Some(head) => println!("Head: entry {}, hash {}", head.index, head.hash),
+        None => println!("Head: none, the log is empty"),
+    }
+
+    Ok(())
+}
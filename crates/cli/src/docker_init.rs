@@ -1,26 +1,39 @@
-use std::{path::Path, vec};
+use std::{fs::Permissions, net::IpAddr, os::unix::fs::PermissionsExt, path::Path, vec};
 
 use cb_common::{
+    commit::client::UNIX_SOCKET_SCHEME,
     config::{
-        CommitBoostConfig, ModuleKind, BUILDER_SERVER_ENV, CB_CONFIG_ENV, CB_CONFIG_NAME, JWTS_ENV,
-        METRICS_SERVER_ENV, MODULE_ID_ENV, MODULE_JWT_ENV, SIGNER_DIR_KEYS, SIGNER_DIR_KEYS_ENV,
-        SIGNER_DIR_SECRETS, SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS, SIGNER_KEYS_ENV,
-        SIGNER_SERVER_ENV,
+        load_jwts_file, CommitBoostConfig, ModuleKind, TlsConfig, AUDIT_DIR, AUDIT_DIR_ENV,
+        AUDIT_KEY, AUDIT_KEY_ENV, BUILDER_SERVER_ENV, CB_CONFIG_ENV, CB_CONFIG_NAME, DIRK_CA_CERT,
+        DIRK_CA_CERT_ENV, DIRK_CERT, DIRK_CERT_ENV, DIRK_KEY, DIRK_KEY_ENV, JWTS_FILE,
+        JWTS_FILE_ENV, KEYMANAGER_DIR_KEYS, KEYMANAGER_DIR_KEYS_ENV, KEYMANAGER_DIR_SECRETS,
+        KEYMANAGER_DIR_SECRETS_ENV, KEYMANAGER_TOKEN_ENV, LISTEN_HOST_ENV, LOGS_DIR_DEFAULT,
+        LOGS_DIR_ENV, METRICS_SERVER_ENV, MODULE_CERT, MODULE_CERT_ENV, MODULE_ID_ENV,
+        MODULE_JWT_ENV, MODULE_KEY, MODULE_KEY_ENV, PEER_CA_CERT, PEER_CA_CERT_ENV, PEER_CERT,
+        PEER_CERT_ENV, PEER_KEY, PEER_KEY_ENV, PKCS11_DIR_KEYS, PKCS11_DIR_KEYS_ENV,
+        PKCS11_PIN_ENV, PROXY_DIR_KEYS, PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS,
+        PROXY_DIR_SECRETS_ENV, SIGNER_CA_CERT, SIGNER_CA_CERT_ENV, SIGNER_DIR_KEYS,
+        SIGNER_DIR_KEYS_ENV, SIGNER_DIR_SECRETS, SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS,
+        SIGNER_KEYS_ENV, SIGNER_SERVER_ENV, SIGNER_SOCKET_DIR, SIGNER_SOCKET_DIR_ENV,
+        SIGNER_SOCKET_FILE, THRESHOLD_SECRETS_ENV, TLS_CERT, TLS_CERT_ENV, TLS_CLIENT_CA_CERT,
+        TLS_CLIENT_CA_CERT_ENV, TLS_KEY, TLS_KEY_ENV,
     },
     loader::SignerLoader,
-    utils::random_jwt,
+    signer::Pkcs11Keys,
+    utils::{random_jwt, write_private_file},
 };
 use docker_compose_types::{
     Compose, ComposeVolume, DependsOnOptions, Environment, Labels, LoggingParameters, MapOrEmpty,
     NetworkSettings, Networks, Ports, Service, Services, SingleValue, TopLevelVolumes, Volumes,
 };
-use eyre::Result;
+use eyre::{bail, Result};
 use indexmap::IndexMap;
 use serde::Serialize;
 
 pub(super) const CB_CONFIG_FILE: &str = "cb-config.toml";
 pub(super) const CB_COMPOSE_FILE: &str = "cb.docker-compose.yml";
 pub(super) const CB_ENV_FILE: &str = ".cb.env";
+pub(super) const CB_JWTS_FILE: &str = ".cb.jwts.json";
 pub(super) const CB_TARGETS_FILE: &str = "targets.json"; // needs to match prometheus.yml
 pub(super) const PROMETHEUS_DATA_VOLUME: &str = "prometheus-data";
 
@@ -42,6 +55,18 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
     // config volume to pass to all services
     let config_volume = Volumes::Simple(format!("./{}:{}:ro", config_path, CB_CONFIG_NAME));
 
+    // log directory to pass to all services, each service writes to its own file
+    let logs_volume =
+        cb_config.logs.file.as_ref().map(|file| {
+            Volumes::Simple(format!("{}:{}", file.dir_path.display(), LOGS_DIR_DEFAULT))
+        });
+    let logs_env = logs_volume.as_ref().map(|_| get_env_val(LOGS_DIR_ENV, LOGS_DIR_DEFAULT));
+
+    // secrets of the modules already in the file are kept, so that running init
+    // again doesn't invalidate running modules or undo a rotation in progress
+    let jwts_path = Path::new(&output_dir).join(CB_JWTS_FILE);
+    let existing_jwts =
+        if jwts_path.exists() { load_jwts_file(&jwts_path)? } else { Default::default() };
     let mut jwts = IndexMap::new();
     // envs to write in .env file
     let mut envs = IndexMap::from([(CB_CONFIG_ENV.into(), CB_CONFIG_NAME.into())]);
@@ -52,7 +77,25 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
 
     // address for signer API communication
     let signer_port = 20000;
-    let signer_server = format!("cb_signer:{signer_port}");
+    let signer_socket = cb_config.signer.as_ref().and_then(|signer| signer.socket.clone());
+    // commit modules connect over TLS if the signer is served over TLS, and not on a socket
+    let signer_tls = match &signer_socket {
+        Some(_) => None,
+        None => cb_config.signer.as_ref().and_then(|signer| signer.tls.clone()),
+    };
+    // with a socket, commit modules mount its directory instead of joining the signer network
+    let (signer_server, signer_socket_volume) = match &signer_socket {
+        Some(socket) => (
+            format!("{UNIX_SOCKET_SCHEME}{SIGNER_SOCKET_DIR}/{SIGNER_SOCKET_FILE}"),
+            Some(Volumes::Simple(format!(
+                "{}:{}:ro",
+                socket.dir_path.display(),
+                SIGNER_SOCKET_DIR
+            ))),
+        ),
+        None if signer_tls.is_some() => (format!("https://cb_signer:{signer_port}"), None),
+        None => (format!("cb_signer:{signer_port}"), None),
+    };
 
     let builder_events_port = 30000;
     let mut builder_events_modules = Vec::new();
@@ -63,10 +106,14 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
         labels: PrometheusLabelsConfig { job: "pbs".to_owned() },
     });
 
+    // services listen on all the interfaces of their container, the configured host is the one
+    // ports are published on
     let mut pbs_envs = IndexMap::from([
         get_env_same(CB_CONFIG_ENV),
         get_env_val(METRICS_SERVER_ENV, &metrics_port.to_string()),
+        get_env_val(LISTEN_HOST_ENV, "0.0.0.0"),
     ]);
+    pbs_envs.extend(logs_env.clone());
 
     let mut needs_signer_module = cb_config.pbs.with_signer;
 
@@ -86,30 +133,79 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
                 ModuleKind::Commit => {
                     needs_signer_module = true;
 
-                    let jwt = random_jwt();
+                    // the first secret is the newest one, while rotating
+                    let secrets = existing_jwts
+                        .get(&module.id)
+                        .filter(|secrets| !secrets.is_empty())
+                        .cloned()
+                        .unwrap_or_else(|| vec![random_jwt()]);
+                    let jwt = secrets[0].clone();
                     let jwt_name = format!("CB_JWT_{}", module.id.to_uppercase());
 
                     // module ids are assumed unique, so envs dont override each other
-                    let module_envs = IndexMap::from([
+                    let mut module_envs = IndexMap::from([
                         get_env_val(MODULE_ID_ENV, &module.id),
                         get_env_same(CB_CONFIG_ENV),
                         get_env_interp(MODULE_JWT_ENV, &jwt_name),
                         get_env_val(METRICS_SERVER_ENV, &metrics_port.to_string()),
                         get_env_val(SIGNER_SERVER_ENV, &signer_server),
                     ]);
+                    module_envs.extend(logs_env.clone());
+
+                    let mut module_volumes = vec![config_volume.clone()];
+                    module_volumes.extend(logs_volume.clone());
+                    module_volumes.extend(signer_socket_volume.clone());
+
+                    if let Some(tls) = &signer_tls {
+                        if let Some(ca_cert_path) = &tls.ca_cert_path {
+                            module_volumes.push(Volumes::Simple(format!(
+                                "{}:{}:ro",
+                                ca_cert_path.display(),
+                                SIGNER_CA_CERT
+                            )));
+                            let (k, v) = get_env_val(SIGNER_CA_CERT_ENV, SIGNER_CA_CERT);
+                            module_envs.insert(k, v);
+                        }
+
+                        match &module.tls {
+                            Some(module_tls) => {
+                                for (path, mounted, env) in [
+                                    (&module_tls.cert_path, MODULE_CERT, MODULE_CERT_ENV),
+                                    (&module_tls.key_path, MODULE_KEY, MODULE_KEY_ENV),
+                                ] {
+                                    module_volumes.push(Volumes::Simple(format!(
+                                        "{}:{}:ro",
+                                        path.display(),
+                                        mounted
+                                    )));
+                                    let (k, v) = get_env_val(env, mounted);
+                                    module_envs.insert(k, v);
+                                }
+                            }
+                            None if tls.client_ca_cert_path.is_some() => {
+                                bail!(
+                                    "module {} has no client certificate for the signer",
+                                    module.id
+                                )
+                            }
+                            None => {}
+                        }
+                    }
+
+                    let mut module_networks = vec![METRICS_NETWORK.to_owned()];
+                    if signer_socket.is_none() {
+                        module_networks.push(SIGNER_NETWORK.to_owned());
+                    }
 
                     envs.insert(jwt_name.clone(), jwt.clone());
-                    jwts.insert(module.id.clone(), jwt);
+                    jwts.insert(module.id.clone(), secrets);
 
                     Service {
                         container_name: Some(module_cid.clone()),
                         image: Some(module.docker_image),
                         // TODO: allow service to open ports here
-                        networks: Networks::Simple(vec![
-                            METRICS_NETWORK.to_owned(),
-                            SIGNER_NETWORK.to_owned(),
-                        ]),
-                        volumes: vec![config_volume.clone()],
+                        networks: Networks::Simple(module_networks),
+                        volumes: module_volumes,
                         environment: Environment::KvPair(module_envs),
                         depends_on: DependsOnOptions::Simple(vec!["cb_signer".to_owned()]),
                         ..Service::default()
@@ -118,12 +214,16 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
                 // an event module just needs a port to listen on
                 ModuleKind::Events => {
                     // module ids are assumed unique, so envs dont override each other
-                    let module_envs = IndexMap::from([
+                    let mut module_envs = IndexMap::from([
                         get_env_val(MODULE_ID_ENV, &module.id),
                         get_env_same(CB_CONFIG_ENV),
                         get_env_val(METRICS_SERVER_ENV, &metrics_port.to_string()),
                         get_env_val(BUILDER_SERVER_ENV, &builder_events_port.to_string()),
                     ]);
+                    module_envs.extend(logs_env.clone());
+
+                    let mut module_volumes = vec![config_volume.clone()];
+                    module_volumes.extend(logs_volume.clone());
 
                     builder_events_modules.push(format!("{module_cid}:{builder_events_port}"));
 
@@ -131,7 +231,7 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
                         container_name: Some(module_cid.clone()),
                         image: Some(module.docker_image),
                         networks: Networks::Simple(vec![METRICS_NETWORK.to_owned()]),
-                        volumes: vec![config_volume.clone()],
+                        volumes: module_volumes,
                         environment: Environment::KvPair(module_envs),
                         depends_on: DependsOnOptions::Simple(vec!["cb_pbs".to_owned()]),
                         ..Service::default()
@@ -149,15 +249,24 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
         pbs_envs.insert(k, v);
     }
 
+    let mut pbs_volumes = vec![config_volume.clone()];
+    pbs_volumes.extend(logs_volume.clone());
+
+    if let Some(tls) = cb_config.pbs.pbs_config.tls {
+        let (tls_volumes, tls_envs) = tls_mounts(tls);
+        pbs_volumes.extend(tls_volumes);
+        pbs_envs.extend(tls_envs);
+    }
+
     let pbs_service = Service {
         container_name: Some("cb_pbs".to_owned()),
         image: Some(cb_config.pbs.docker_image),
-        ports: Ports::Short(vec![format!(
-            "{}:{}",
-            cb_config.pbs.pbs_config.port, cb_config.pbs.pbs_config.port
+        ports: Ports::Short(vec![publish_port(
+            cb_config.pbs.pbs_config.host,
+            cb_config.pbs.pbs_config.port,
         )]),
         networks: Networks::Simple(vec![METRICS_NETWORK.to_owned()]),
-        volumes: vec![config_volume.clone()],
+        volumes: pbs_volumes,
         environment: Environment::KvPair(pbs_envs),
         ..Service::default()
     };
@@ -171,6 +280,7 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
     if let Some(signer_config) = cb_config.signer {
         if needs_signer_module {
             let mut volumes = vec![config_volume.clone()];
+            volumes.extend(logs_volume.clone());
 
             targets.push(PrometheusTargetConfig {
                 targets: vec![format!("cb_signer:{metrics_port}")],
@@ -179,10 +289,19 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
 
             let mut signer_envs = IndexMap::from([
                 get_env_same(CB_CONFIG_ENV),
-                get_env_same(JWTS_ENV),
                 get_env_val(METRICS_SERVER_ENV, &metrics_port.to_string()),
                 get_env_val(SIGNER_SERVER_ENV, &signer_port.to_string()),
+                get_env_val(LISTEN_HOST_ENV, "0.0.0.0"),
             ]);
+            signer_envs.extend(logs_env.clone());
+
+            // module secrets, reloaded by the signer on SIGHUP. The file is only
+            // written if there are modules
+            if !jwts.is_empty() {
+                volumes.push(Volumes::Simple(format!("./{}:{}:ro", CB_JWTS_FILE, JWTS_FILE)));
+                let (k, v) = get_env_val(JWTS_FILE_ENV, JWTS_FILE);
+                signer_envs.insert(k, v);
+            }
 
             // TODO: generalize this, different loaders may not need volumes but eg ports
             match signer_config.loader {
@@ -191,7 +310,7 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
                     let (k, v) = get_env_val(SIGNER_KEYS_ENV, SIGNER_KEYS);
                     signer_envs.insert(k, v);
                 }
-                SignerLoader::ValidatorsDir { keys_path, secrets_path } => {
+                SignerLoader::ValidatorsDir { keys_path, secrets_path, .. } => {
                     volumes.push(Volumes::Simple(format!("{}:{}:ro", keys_path, SIGNER_DIR_KEYS)));
                     let (k, v) = get_env_val(SIGNER_DIR_KEYS_ENV, SIGNER_DIR_KEYS);
                     signer_envs.insert(k, v);
@@ -203,21 +322,160 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
                     let (k, v) = get_env_val(SIGNER_DIR_SECRETS_ENV, SIGNER_DIR_SECRETS);
                     signer_envs.insert(k, v);
                 }
+                // the PKCS#11 module and its configuration must be part of the signer image
+                SignerLoader::Pkcs11 { keys, .. } => {
+                    let Pkcs11Keys::Wrapped { keys_path, .. } = keys;
+                    volumes.push(Volumes::Simple(format!(
+                        "{}:{}:ro",
+                        keys_path.display(),
+                        PKCS11_DIR_KEYS
+                    )));
+                    let (k, v) = get_env_val(PKCS11_DIR_KEYS_ENV, PKCS11_DIR_KEYS);
+                    signer_envs.insert(k, v);
+
+                    let (k, v) = get_env_same(PKCS11_PIN_ENV);
+                    signer_envs.insert(k, v);
+                }
+                SignerLoader::Dirk { tls, .. } => {
+                    if let Some(tls) = tls {
+                        for (path, mounted, env) in [
+                            (tls.cert_path, DIRK_CERT, DIRK_CERT_ENV),
+                            (tls.key_path, DIRK_KEY, DIRK_KEY_ENV),
+                            (tls.ca_cert_path, DIRK_CA_CERT, DIRK_CA_CERT_ENV),
+                        ] {
+                            volumes.push(Volumes::Simple(format!(
+                                "{}:{}:ro",
+                                path.display(),
+                                mounted
+                            )));
+                            let (k, v) = get_env_val(env, mounted);
+                            signer_envs.insert(k, v);
+                        }
+                    }
+                }
+                // keys stay in Web3Signer, which is reached via the url in the config
+                SignerLoader::Remote { .. } => {}
             };
 
-            // write jwts to env
-            let jwts_json = serde_json::to_string(&jwts).unwrap().clone();
-            envs.insert(JWTS_ENV.into(), format!("{jwts_json:?}"));
+            if let Some(store) = signer_config.store {
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    store.keys_path.display(),
+                    PROXY_DIR_KEYS
+                )));
+                let (k, v) = get_env_val(PROXY_DIR_KEYS_ENV, PROXY_DIR_KEYS);
+                signer_envs.insert(k, v);
+
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    store.secrets_path.display(),
+                    PROXY_DIR_SECRETS
+                )));
+                let (k, v) = get_env_val(PROXY_DIR_SECRETS_ENV, PROXY_DIR_SECRETS);
+                signer_envs.insert(k, v);
+            }
+
+            if let Some(audit) = signer_config.audit {
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    audit.dir_path.display(),
+                    AUDIT_DIR
+                )));
+                let (k, v) = get_env_val(AUDIT_DIR_ENV, AUDIT_DIR);
+                signer_envs.insert(k, v);
+
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:ro",
+                    audit.key_path.display(),
+                    AUDIT_KEY
+                )));
+                let (k, v) = get_env_val(AUDIT_KEY_ENV, AUDIT_KEY);
+                signer_envs.insert(k, v);
+            }
+
+            if let Some(socket) = signer_config.socket {
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    socket.dir_path.display(),
+                    SIGNER_SOCKET_DIR
+                )));
+                let (k, v) = get_env_val(SIGNER_SOCKET_DIR_ENV, SIGNER_SOCKET_DIR);
+                signer_envs.insert(k, v);
+            }
+
+            if let Some(tls) = signer_config.tls {
+                let (tls_volumes, tls_envs) = tls_mounts(tls);
+                volumes.extend(tls_volumes);
+                signer_envs.extend(tls_envs);
+            }
+
+            // admin api, only published on localhost
+            let mut signer_ports = vec![];
+            if let Some(keymanager) = signer_config.keymanager {
+                signer_ports.push(format!("127.0.0.1:{}:{}", keymanager.port, keymanager.port));
+
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    keymanager.keys_path.display(),
+                    KEYMANAGER_DIR_KEYS
+                )));
+                let (k, v) = get_env_val(KEYMANAGER_DIR_KEYS_ENV, KEYMANAGER_DIR_KEYS);
+                signer_envs.insert(k, v);
+
+                volumes.push(Volumes::Simple(format!(
+                    "{}:{}:rw",
+                    keymanager.secrets_path.display(),
+                    KEYMANAGER_DIR_SECRETS
+                )));
+                let (k, v) = get_env_val(KEYMANAGER_DIR_SECRETS_ENV, KEYMANAGER_DIR_SECRETS);
+                signer_envs.insert(k, v);
+
+                let (k, v) = get_env_same(KEYMANAGER_TOKEN_ENV);
+                signer_envs.insert(k, v);
+                envs.insert(KEYMANAGER_TOKEN_ENV.into(), random_jwt());
+            }
+
+            // the other nodes of the cluster request partial signatures on the peer port, which
+            // is the only one published. The secrets are shared with the other nodes, so they
+            // are not generated here
+            if let Some(threshold) = &signer_config.threshold {
+                signer_ports.push(publish_port(signer_config.host, threshold.port));
+
+                let (k, v) = get_env_same(THRESHOLD_SECRETS_ENV);
+                signer_envs.insert(k, v);
+
+                if let Some(peer_tls) = &threshold.tls {
+                    let mounts = [
+                        (&peer_tls.ca_cert_path, PEER_CA_CERT, PEER_CA_CERT_ENV),
+                        (&peer_tls.cert_path, PEER_CERT, PEER_CERT_ENV),
+                        (&peer_tls.key_path, PEER_KEY, PEER_KEY_ENV),
+                    ];
+                    for (path, mounted, env) in mounts {
+                        volumes.push(Volumes::Simple(format!("{}:{}:ro", path.display(), mounted)));
+                        let (k, v) = get_env_val(env, mounted);
+                        signer_envs.insert(k, v);
+                    }
+                }
+            }
+
+            // mlockall needs the capability, or a memlock limit larger than the signer memory
+            let cap_add = if signer_config.hardening.lock_memory {
+                vec!["IPC_LOCK".to_owned()]
+            } else {
+                vec![]
+            };
 
             let signer_service = Service {
                 container_name: Some("cb_signer".to_owned()),
                 image: Some(signer_config.docker_image),
+                ports: Ports::Short(signer_ports),
                 networks: Networks::Simple(vec![
                     METRICS_NETWORK.to_owned(),
                     SIGNER_NETWORK.to_owned(),
                 ]),
                 volumes,
                 environment: Environment::KvPair(signer_envs),
+                cap_add,
                 ..Service::default()
             };
 
@@ -320,6 +578,16 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
     std::fs::write(&env_path, envs_str)?;
     println!("Env file written to: {:?}", env_path);
 
+    // write module secrets to file, mounted in the signer. Secrets of modules
+    // removed from the config are dropped
+    if !jwts.is_empty() {
+        let jwts_str = serde_json::to_string_pretty(&jwts)?;
+        write_private_file(&jwts_path, jwts_str)?;
+        // an existing file keeps its mode when truncated
+        std::fs::set_permissions(&jwts_path, Permissions::from_mode(0o600))?;
+        println!("JWT secrets file written to: {:?}", jwts_path);
+    }
+
     // write prometheus targets to file
     let targets_str = serde_json::to_string_pretty(&targets)?;
     let targets_path = Path::new(&output_dir).join(CB_TARGETS_FILE);
@@ -332,6 +600,35 @@ pub fn handle_docker_init(config_path: String, output_dir: String) -> Result<()>
     Ok(())
 }
 
+/// Mounts the certificates of a TLS listener, in the PBS or signer container
+fn tls_mounts(tls: TlsConfig) -> (Vec<Volumes>, Vec<(String, Option<SingleValue>)>) {
+    let mut volumes = vec![];
+    let mut envs = vec![];
+
+    let mounts = [
+        (Some(tls.cert_path), TLS_CERT, TLS_CERT_ENV),
+        (Some(tls.key_path), TLS_KEY, TLS_KEY_ENV),
+        (tls.client_ca_cert_path, TLS_CLIENT_CA_CERT, TLS_CLIENT_CA_CERT_ENV),
+    ];
+    for (path, mounted, env) in mounts {
+        if let Some(path) = path {
+            volumes.push(Volumes::Simple(format!("{}:{}:ro", path.display(), mounted)));
+            envs.push(get_env_val(env, mounted));
+        }
+    }
+
+    (volumes, envs)
+}
+
+/// Publishes `port` on `host`, or on all the interfaces if it's unspecified
+fn publish_port(host: IpAddr, port: u16) -> String {
+    match host {
+        IpAddr::V4(host) if host.is_unspecified() => format!("{port}:{port}"),
+        IpAddr::V4(host) => format!("{host}:{port}:{port}"),
+        IpAddr::V6(host) => format!("[{host}]:{port}:{port}"),
+    }
+}
+
 // FOO=${FOO}
 fn get_env_same(k: &str) -> (String, Option<SingleValue>) {
     get_env_interp(k, k)
@@ -1,9 +1,14 @@
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+use audit::AuditFilter;
 use cb_common::utils::print_logo;
 use clap::{Parser, Subcommand};
 use docker_init::{CB_COMPOSE_FILE, CB_CONFIG_FILE, CB_ENV_FILE};
 
+mod audit;
 mod docker_cmd;
 mod docker_init;
+mod revoke;
+mod wrap_keys;
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -61,6 +66,70 @@ pub enum Command {
         )]
         compose_path: String,
     },
+
+    /// Check that the signer audit log wasn't tampered with, and print its
+    /// entries
+    Audit {
+        /// Path to the audit log file
+        #[arg(long("path"))]
+        path: String,
+
+        /// Path to the HMAC key of the audit log
+        #[arg(long("key"))]
+        key_path: String,
+
+        /// Only print the requests of this module
+        #[arg(long("module"))]
+        module_id: Option<String>,
+
+        /// Only print the requests for this consensus or BLS proxy pubkey
+        #[arg(long("pubkey"))]
+        pubkey: Option<BlsPublicKey>,
+
+        /// Only print the requests for this ECDSA proxy address
+        #[arg(long("address"))]
+        address: Option<Address>,
+
+        /// Only print the requests received from this timestamp, in ms
+        #[arg(long("from"))]
+        from_ms: Option<u64>,
+
+        /// Only print the requests received until this timestamp, in ms
+        #[arg(long("to"))]
+        to_ms: Option<u64>,
+    },
+
+    /// Revoke a stored proxy key without the signer running, and print the
+    /// revocation signed by its consensus key
+    Revoke {
+        /// Path to config file
+        #[arg(long("config"), default_value = CB_CONFIG_FILE)]
+        config_path: String,
+
+        /// Id of the module which generated the proxy
+        #[arg(long("module"))]
+        module_id: String,
+
+        /// Pubkey of the BLS proxy to revoke
+        #[arg(long("pubkey"), conflicts_with = "address")]
+        pubkey: Option<BlsPublicKey>,
+
+        /// Address of the ECDSA proxy to revoke
+        #[arg(long("address"))]
+        address: Option<Address>,
+    },
+
+    /// Encrypt plain text keys with the wrapping key of the PKCS#11 token in
+    /// the signer config. The token PIN is read from `CB_PKCS11_PIN`
+    WrapKeys {
+        /// Path to config file
+        #[arg(long("config"), default_value = CB_CONFIG_FILE)]
+        config_path: String,
+
+        /// Path to the plain text keys, in the format of the file loader
+        #[arg(long("keys"))]
+        keys_path: String,
+    },
 }
 
 impl Args {
@@ -81,6 +150,19 @@ impl Args {
             }
 
             Command::Logs { compose_path } => docker_cmd::handle_docker_logs(compose_path),
+
+            Command::Audit { path, key_path, module_id, pubkey, address, from_ms, to_ms } => {
+                let filter = AuditFilter { module_id, pubkey, address, from_ms, to_ms };
+                audit::handle_audit_verify(path, key_path, filter)
+            }
+
+            Command::Revoke { config_path, module_id, pubkey, address } => {
+                revoke::handle_revoke(config_path, module_id, pubkey, address).await
+            }
+
+            Command::WrapKeys { config_path, keys_path } => {
+                wrap_keys::handle_wrap_keys(config_path, keys_path)
+            }
         }
     }
 }
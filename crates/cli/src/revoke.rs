@@ -0,0 +1,58 @@
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+use cb_common::{
+    config::CommitBoostConfig,
+    loader::{load_validators_dir, ValidatorKeysFormat},
+};
+use cb_signer::manager::SigningManager;
+use eyre::{bail, OptionExt, Result};
+
+/// Revokes a stored proxy without going through the Signer API, e.g. if the
+/// signer is down. The revocation is saved next to the proxy, so the signer
+/// stops using it once restarted, and printed so it can be forwarded. Shares of
+/// distributed keys are not combined here, so their proxies can only be
+/// revoked through the Signer API
+pub async fn handle_revoke(
+    config_path: String,
+    module_id: String,
+    pubkey: Option<BlsPublicKey>,
+    address: Option<Address>,
+) -> Result<()> {
+    let config = CommitBoostConfig::from_file(&config_path)?;
+    let signer_config = config.signer.ok_or_eyre("signer is not configured")?;
+    let store =
+        signer_config.store.ok_or_eyre("proxy store is not configured, proxies are not saved")?;
+
+    let mut manager = SigningManager::new(config.chain, Some(store.clone()));
+    for signer in signer_config.loader.load_keys().await? {
+        manager.add_consensus_signer(signer);
+    }
+    if let Some(keymanager) = &signer_config.keymanager {
+        let imported = load_validators_dir(
+            &keymanager.keys_path,
+            &keymanager.secrets_path,
+            ValidatorKeysFormat::Lighthouse,
+        )?;
+        for signer in imported {
+            manager.add_consensus_signer(signer);
+        }
+    }
+    manager.load_proxies()?;
+
+    let revocation = match (pubkey, address) {
+        (Some(pubkey), None) => {
+            let revocation = manager.revoke_proxy(&module_id, &pubkey, None).await?;
+            store.store_bls_revocation(&module_id, &revocation)?;
+            serde_json::to_string(&revocation)?
+        }
+        (None, Some(address)) => {
+            let revocation = manager.revoke_ecdsa_proxy(&module_id, &address, None).await?;
+            store.store_ecdsa_revocation(&module_id, &revocation)?;
+            serde_json::to_string(&revocation)?
+        }
+        _ => bail!("set either the pubkey or the address of the proxy"),
+    };
+
+    println!("{revocation}");
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::{fs, path::Path};
+
+use cb_common::{
+    config::{load_env_var, CommitBoostConfig, PKCS11_PIN_ENV},
+    loader::{FileKey, SignerLoader},
+    signer::{Pkcs11Keys, Pkcs11Token, Signer},
+};
+use eyre::{bail, OptionExt, Result, WrapErr};
+use zeroize::Zeroizing;
+
+/// Encrypts plain text keys, in the format of the `File` loader, with the
+/// wrapping key of the PKCS#11 token in the signer config. The encrypted keys
+/// are saved in the configured `keys_path`, and the plain text file can then be
+/// deleted
+pub fn handle_wrap_keys(config_path: String, keys_path: String) -> Result<()> {
+    let config = CommitBoostConfig::from_file(&config_path)?;
+    let signer_config = config.signer.ok_or_eyre("signer is not configured")?;
+    let SignerLoader::Pkcs11 { module_path, token_label, keys } = signer_config.loader else {
+        bail!("signer loader is not a PKCS#11 token");
+    };
+    let Pkcs11Keys::Wrapped { wrapping_key_label, keys_path: output_path } = keys;
+
+    let pin = Zeroizing::new(load_env_var(PKCS11_PIN_ENV)?);
+    let token = Pkcs11Token::open(&module_path, &token_label, &pin)?;
+
+    let file = Zeroizing::new(
+        fs::read_to_string(&keys_path)
+            .wrap_err_with(|| format!("unable to read keys file {keys_path}"))?,
+    );
+    let keys: Vec<FileKey> = serde_json::from_str(&file)?;
+
+    fs::create_dir_all(&output_path)?;
+    for key in keys.iter() {
+        let signer = Signer::new_from_bytes(&key.secret_key)?;
+        let wrapped = token.wrap_key(&wrapping_key_label, &signer)?;
+
+        let path = Path::new(&output_path).join(format!("{}.json", wrapped.pubkey));
+        fs::write(&path, serde_json::to_string_pretty(&wrapped)?)?;
+        println!("Wrapped {} in {}", wrapped.pubkey, path.display());
+    }
+
+    Ok(())
+}
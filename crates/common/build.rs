@@ -0,0 +1,10 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // use a vendored protoc so that no system install is needed
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::configure()
+        .build_server(true)
+        .compile_protos(&["proto/dirk/lister.proto", "proto/dirk/signer.proto"], &["proto/dirk"])?;
+
+    Ok(())
+}
@@ -1,90 +1,760 @@
-use std::sync::Arc;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
-use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
-use eyre::WrapErr;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
-use serde::{Deserialize, Serialize};
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::beacon::{BlsPublicKey, BlsSignature},
+};
+use eyre::{bail, ensure, WrapErr};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, Request};
+use hyper_util::client::legacy::Client as HyperClient;
+use hyperlocal::{UnixClientExt, UnixConnector};
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE},
+    Certificate, Identity, Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::debug;
+use tree_hash::TreeHash;
 
 use super::{
-    constants::{GET_PUBKEYS_PATH, REQUEST_SIGNATURE_PATH},
+    constants::{
+        GENERATE_PROXY_KEY_PATH, GET_DELEGATION_PATH, GET_PUBKEYS_PATH,
+        REQUEST_ECDSA_MESSAGE_SIGNATURE_PATH, REQUEST_ECDSA_SIGNATURE_PATH,
+        REQUEST_MESSAGE_SIGNATURE_PATH, REQUEST_SIGNATURES_PATH, REQUEST_SIGNATURE_PATH,
+        REQUEST_VALIDATOR_REGISTRATION_PATH, REVOKE_PROXY_KEY_PATH, VERIFY_DELEGATION_PATH,
+        VERIFY_ECDSA_SIGNATURE_PATH, VERIFY_SIGNATURE_PATH,
+    },
     error::SignerClientError,
-    request::SignRequest,
+    request::{
+        EncryptionScheme, GenerateProxyRequest, GetDelegationRequest, ProxyId, ProxyInfo, ProxyKey,
+        ProxyStatus, RevokeProxyRequest, SignEcdsaMessageRequest, SignEcdsaRequest,
+        SignMessageRequest, SignRequest, SignValidatorRegistrationRequest, SignedProxyDelegation,
+        SignedProxyDelegationBls, SignedProxyDelegationEcdsa, SignedRevokedProxy,
+        SignedRevokedProxyBls, SignedRevokedProxyEcdsa, VerifyDelegationRequestBls,
+        VerifyDelegationRequestEcdsa, VerifyEcdsaSignatureRequest, VerifySignatureRequest,
+    },
+    schema::SchemaMessage,
 };
-use crate::DEFAULT_REQUEST_TIMEOUT;
+use crate::{
+    config::{MODULE_CERT_ENV, MODULE_KEY_ENV, SIGNER_CA_CERT_ENV},
+    jwt::create_jwt,
+    signature::{compute_module_signing_root, compute_signing_root, verify_signature},
+    signer::EcdsaSignature,
+    types::Chain,
+    utils::utcnow_sec,
+    DEFAULT_REQUEST_TIMEOUT,
+};
+
+/// JWTs are refreshed when they expire in less than this
+const JWT_REFRESH_MARGIN_SECONDS: u64 = 30;
+/// Prefix of the signer addresses which are Unix sockets, e.g.
+/// `unix:///run/commit-boost/signer.sock`
+pub const UNIX_SOCKET_SCHEME: &str = "unix://";
+/// Default time the pubkeys are cached for, see
+/// [`SignerClient::cached_pubkeys`]
+const DEFAULT_PUBKEYS_TTL: Duration = Duration::from_secs(12);
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GetPubkeysResponse {
     pub consensus: Vec<BlsPublicKey>,
+    /// BLS proxy pubkeys created by the requesting module, which can still
+    /// sign
     pub proxy: Vec<BlsPublicKey>,
+    /// Addresses of the ECDSA proxy keys created by the requesting module,
+    /// which can still sign
+    #[serde(default)]
+    pub proxy_ecdsa: Vec<Address>,
+    /// All the proxies created by the requesting module, including the revoked
+    /// and expired ones
+    #[serde(default)]
+    pub proxy_status: Vec<ProxyInfo>,
+}
+
+/// Outcome of one of the requests in a batch, see
+/// [`SignerClient::request_signatures`]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchSignatureResult {
+    Signature(BlsSignature),
+    Error { code: u16, message: String },
+}
+
+/// Outcome of a verification, see [`SignerClient::verify_signature`] and
+/// [`SignerClient::verify_delegation`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VerificationResponse {
+    pub valid: bool,
+    /// Why the verification failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl VerificationResponse {
+    pub fn valid() -> Self {
+        Self { valid: true, reason: None }
+    }
+
+    pub fn invalid(reason: impl ToString) -> Self {
+        Self { valid: false, reason: Some(reason.to_string()) }
+    }
+}
+
+/// Delegation of a proxy loaded in the signer, see
+/// [`SignerClient::get_delegation`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelegationResponse<T: ProxyKey> {
+    pub delegation: SignedProxyDelegation<T>,
+    pub status: ProxyStatus,
+    /// Set if the proxy was revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<SignedRevokedProxy<T>>,
+}
+
+pub type DelegationResponseBls = DelegationResponse<BlsPublicKey>;
+pub type DelegationResponseEcdsa = DelegationResponse<Address>;
+
+/// How requests failing with a transient error are retried: connection
+/// errors, timeouts, and 502, 503 and 504 responses. POST requests, e.g.
+/// signature or proxy requests, may have been handled by the signer even if
+/// they failed, so they are only retried if they failed to connect. The
+/// backoff doubles after each attempt, up to `max_backoff`
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    /// Retries after the first attempt, 0 to disable retries
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryConfig {
+    pub fn disabled() -> Self {
+        Self { max_retries: 0, ..Self::default() }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Certificates to connect to a signer served over https, as PEM files
+#[derive(Debug, Clone, Default)]
+pub struct SignerClientTls {
+    /// CA of the signer certificate, trusted instead of the system roots
+    pub ca_cert_path: Option<PathBuf>,
+    /// Client certificate and key, needed if the signer requires mTLS
+    pub cert_path: Option<PathBuf>,
+    pub key_path: Option<PathBuf>,
+}
+
+impl SignerClientTls {
+    /// Loads the certificates mounted by `commit-boost init`, if any
+    pub fn from_env() -> Option<Self> {
+        let env_path = |env| std::env::var(env).ok().map(PathBuf::from);
+        let tls = Self {
+            ca_cert_path: env_path(SIGNER_CA_CERT_ENV),
+            cert_path: env_path(MODULE_CERT_ENV),
+            key_path: env_path(MODULE_KEY_ENV),
+        };
+
+        (tls.ca_cert_path.is_some() || tls.cert_path.is_some()).then_some(tls)
+    }
+}
+
+/// How the client reaches the Signer Module
+#[derive(Clone)]
+enum Transport {
+    Http(reqwest::Client),
+    Unix { socket_path: Arc<PathBuf>, client: HyperClient<UnixConnector, Full<Bytes>> },
 }
 
 /// Client used by commit modules to request signatures via the Signer API
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct SignerClient {
     /// Url endpoint of the Signer Module
     url: Arc<String>,
-    client: reqwest::Client,
+    transport: Transport,
+    module_id: Arc<String>,
+    /// Secret used to sign the JWTs sent to the Signer Module
+    jwt_secret: Arc<String>,
+    /// Current JWT and its expiry, refreshed before it expires
+    jwt: Arc<RwLock<(HeaderValue, u64)>>,
+    /// If set, returned signatures are verified against the signing root
+    /// expected for the request
+    chain: Option<Chain>,
+    retry: RetryConfig,
+    /// Last pubkeys returned by the signer, and when they were fetched
+    pubkeys: Arc<RwLock<Option<(GetPubkeysResponse, Instant)>>>,
+    pubkeys_ttl: Duration,
+}
+
+impl std::fmt::Debug for SignerClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignerClient")
+            .field("url", &self.url)
+            .field("module_id", &self.module_id)
+            .field("chain", &self.chain)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
 }
 
 impl SignerClient {
-    /// Create a new SignerClient
-    pub fn new(signer_server_address: String, jwt: &str) -> eyre::Result<Self> {
-        let url = format!("http://{}", signer_server_address);
-        let mut headers = HeaderMap::new();
+    /// Create a new SignerClient, authenticating as `module_id` with JWTs
+    /// signed with `jwt_secret`. The signer is reached over HTTP at
+    /// `signer_server_address`, over HTTPS if it starts with `https://`, or
+    /// over a Unix socket if it starts with `unix://`
+    pub fn new(
+        signer_server_address: String,
+        module_id: &str,
+        jwt_secret: &str,
+    ) -> eyre::Result<Self> {
+        let (url, transport) = match signer_server_address.strip_prefix(UNIX_SOCKET_SCHEME) {
+            Some(socket_path) => {
+                let transport = Transport::Unix {
+                    socket_path: Arc::new(socket_path.into()),
+                    client: HyperClient::unix(),
+                };
+                (signer_server_address, transport)
+            }
+            None => {
+                let client = reqwest::Client::builder().timeout(DEFAULT_REQUEST_TIMEOUT).build()?;
+                let url = if signer_server_address.contains("://") {
+                    signer_server_address
+                } else {
+                    format!("http://{}", signer_server_address)
+                };
+                (url, Transport::Http(client))
+            }
+        };
+        let jwt = auth_header(module_id, jwt_secret)?;
 
-        let mut auth_value =
-            HeaderValue::from_str(&format!("Bearer {}", jwt)).wrap_err("invalid jwt")?;
-        auth_value.set_sensitive(true);
-        headers.insert(AUTHORIZATION, auth_value);
-        let client = reqwest::Client::builder()
-            .timeout(DEFAULT_REQUEST_TIMEOUT)
-            .default_headers(headers)
-            .build()?;
+        Ok(Self {
+            url: url.into(),
+            transport,
+            module_id: module_id.to_owned().into(),
+            jwt_secret: jwt_secret.to_owned().into(),
+            jwt: Arc::new(RwLock::new(jwt)),
+            chain: None,
+            retry: RetryConfig::default(),
+            pubkeys: Arc::new(RwLock::new(None)),
+            pubkeys_ttl: DEFAULT_PUBKEYS_TTL,
+        })
+    }
 
-        Ok(Self { url: url.into(), client })
+    /// Verify the returned signatures, with the domains of `chain`. Requests
+    /// whose signature doesn't match the requested pubkey or address fail with
+    /// [`SignerClientError::InvalidSignature`]
+    pub fn with_chain(self, chain: Chain) -> Self {
+        Self { chain: Some(chain), ..self }
+    }
+
+    /// Connect with the given certificates, the signer address must start
+    /// with `https://`
+    pub fn with_tls(self, tls: &SignerClientTls) -> eyre::Result<Self> {
+        ensure!(self.url.starts_with("https://"), "signer address {} is not https", self.url);
+
+        let mut builder =
+            reqwest::Client::builder().timeout(DEFAULT_REQUEST_TIMEOUT).use_rustls_tls();
+        if let Some(path) = &tls.ca_cert_path {
+            let ca_cert = fs::read(path).wrap_err("failed reading signer CA certificate")?;
+            builder = builder
+                .tls_built_in_root_certs(false)
+                .add_root_certificate(Certificate::from_pem(&ca_cert)?);
+        }
+        match (&tls.cert_path, &tls.key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut pem = fs::read(cert_path).wrap_err("failed reading client certificate")?;
+                pem.push(b'\n');
+                pem.extend(fs::read(key_path).wrap_err("failed reading client key")?);
+                builder = builder.identity(Identity::from_pem(&pem)?);
+            }
+            (None, None) => {}
+            _ => bail!("client certificate and key must be set together"),
+        }
+
+        Ok(Self { transport: Transport::Http(builder.build()?), ..self })
+    }
+
+    pub fn with_retries(self, retry: RetryConfig) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// How long [`SignerClient::cached_pubkeys`] returns the same pubkeys for
+    pub fn with_pubkeys_ttl(self, pubkeys_ttl: Duration) -> Self {
+        Self { pubkeys_ttl, ..self }
+    }
+
+    pub fn module_id(&self) -> &str {
+        &self.module_id
+    }
+
+    /// Returns the current JWT, creating a new one if it's about to expire
+    fn jwt(&self) -> Result<HeaderValue, SignerClientError> {
+        let (jwt, exp) = self.jwt.read().expect("poisoned jwt").clone();
+        if utcnow_sec() + JWT_REFRESH_MARGIN_SECONDS < exp {
+            return Ok(jwt);
+        }
+
+        let (jwt, exp) = auth_header(&self.module_id, &self.jwt_secret)?;
+        *self.jwt.write().expect("poisoned jwt") = (jwt.clone(), exp);
+
+        Ok(jwt)
     }
 
     /// Request a list of validator pubkeys for which signatures can be
-    /// requested. TODO: add more docs on how proxy keys work
+    /// requested. Proxy pubkeys are only the ones generated by this module, see
+    /// [`SignerClient::generate_proxy_key`]. The response is cached, see
+    /// [`SignerClient::cached_pubkeys`]
     pub async fn get_pubkeys(&self) -> Result<GetPubkeysResponse, SignerClientError> {
-        let url = format!("{}{}", self.url, GET_PUBKEYS_PATH);
-        let res = self.client.get(&url).send().await?;
+        let pubkeys: GetPubkeysResponse = self.get(GET_PUBKEYS_PATH).await?;
+        *self.pubkeys.write().expect("poisoned pubkeys") = Some((pubkeys.clone(), Instant::now()));
 
-        let status = res.status();
-        let response_bytes = res.bytes().await?;
+        Ok(pubkeys)
+    }
 
-        if !status.is_success() {
-            return Err(SignerClientError::FailedRequest {
-                status: status.as_u16(),
-                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
-            });
+    /// Same as [`SignerClient::get_pubkeys`], but returns the last response if
+    /// it was fetched less than the TTL ago, see
+    /// [`SignerClient::with_pubkeys_ttl`]. The cache is cleared when this
+    /// client generates or revokes a proxy
+    pub async fn cached_pubkeys(&self) -> Result<GetPubkeysResponse, SignerClientError> {
+        if let Some((pubkeys, fetched_at)) = &*self.pubkeys.read().expect("poisoned pubkeys") {
+            if fetched_at.elapsed() < self.pubkeys_ttl {
+                return Ok(pubkeys.clone());
+            }
         }
 
-        let parsed_response: GetPubkeysResponse = serde_json::from_slice(&response_bytes)?;
+        self.get_pubkeys().await
+    }
 
-        Ok(parsed_response)
+    fn clear_pubkeys(&self) {
+        *self.pubkeys.write().expect("poisoned pubkeys") = None;
     }
 
-    /// Send a signature request
+    /// Send a signature request. If the client has a chain, the signature is
+    /// verified before being returned, see [`SignerClient::with_chain`]
     pub async fn request_signature(
         &self,
         request: &SignRequest,
     ) -> Result<BlsSignature, SignerClientError> {
-        let url = format!("{}{}", self.url, REQUEST_SIGNATURE_PATH);
-        let res = self.client.post(&url).json(&request).send().await?;
+        let signature = self.post(REQUEST_SIGNATURE_PATH, &request).await?;
+        self.check_signature(request, &signature)?;
 
-        let status = res.status();
-        let response_bytes = res.bytes().await?;
+        Ok(signature)
+    }
 
-        if !status.is_success() {
-            return Err(SignerClientError::FailedRequest {
-                status: status.as_u16(),
-                error_msg: String::from_utf8_lossy(&response_bytes).into_owned(),
-            });
-        }
+    /// Request a signature of `msg` with a consensus key, with the module
+    /// domain
+    pub async fn request_consensus_signature(
+        &self,
+        pubkey: BlsPublicKey,
+        msg: &impl TreeHash,
+    ) -> Result<BlsSignature, SignerClientError> {
+        self.request_signature(&SignRequest::builder(self.module_id(), pubkey).with_msg(msg)).await
+    }
+
+    /// Request a signature of `msg` with a BLS proxy key generated by this
+    /// module, with the module domain
+    pub async fn request_proxy_signature(
+        &self,
+        proxy: BlsPublicKey,
+        msg: &impl TreeHash,
+    ) -> Result<BlsSignature, SignerClientError> {
+        let request = SignRequest::builder(self.module_id(), proxy).is_proxy().with_msg(msg);
+        self.request_signature(&request).await
+    }
+
+    /// Request a signature of `msg` with an ECDSA proxy key generated by this
+    /// module, with the module domain
+    pub async fn request_ecdsa_proxy_signature(
+        &self,
+        address: Address,
+        msg: &impl TreeHash,
+    ) -> Result<EcdsaSignature, SignerClientError> {
+        let request = SignEcdsaRequest::builder(self.module_id(), address).with_msg(msg);
+        self.request_ecdsa_signature(&request).await
+    }
+
+    /// Send a batch of signature requests, for consensus or proxy keys, in a
+    /// single round trip. Requests are signed in order, and each one fails or
+    /// succeeds independently of the others. Batches can have at most
+    /// [`MAX_BATCH_SIGNATURES`](super::constants::MAX_BATCH_SIGNATURES)
+    /// requests
+    pub async fn request_signatures(
+        &self,
+        requests: &[SignRequest],
+    ) -> Result<Vec<Result<BlsSignature, SignerClientError>>, SignerClientError> {
+        let results: Vec<BatchSignatureResult> =
+            self.post(REQUEST_SIGNATURES_PATH, &requests).await?;
+
+        Ok(results
+            .into_iter()
+            .zip(requests)
+            .map(|(res, request)| match res {
+                BatchSignatureResult::Signature(signature) => {
+                    self.check_signature(request, &signature).map(|_| signature)
+                }
+                BatchSignatureResult::Error { code, message } => {
+                    Err(SignerClientError::FailedRequest { status: code, error_msg: message })
+                }
+            })
+            .collect())
+    }
+
+    /// Send a signature request for an ECDSA proxy key, see
+    /// [`SignerClient::generate_ecdsa_proxy_key`]
+    pub async fn request_ecdsa_signature(
+        &self,
+        request: &SignEcdsaRequest,
+    ) -> Result<EcdsaSignature, SignerClientError> {
+        let signature = self.post(REQUEST_ECDSA_SIGNATURE_PATH, &request).await?;
+        self.check_ecdsa_signature(request, &signature)?;
+
+        Ok(signature)
+    }
+
+    /// Send a signature request for a structured message. The signer decodes
+    /// the message and computes its root itself, so that the module policy
+    /// can check the message fields. If the client has a chain, the signature
+    /// is verified before being returned
+    pub async fn request_message_signature(
+        &self,
+        request: &SignMessageRequest,
+    ) -> Result<BlsSignature, SignerClientError> {
+        let object_root = decode_root(&request.message)?;
+        let signature = self.post(REQUEST_MESSAGE_SIGNATURE_PATH, &request).await?;
+        self.check_signature(&request.to_raw_request(object_root), &signature)?;
+
+        Ok(signature)
+    }
+
+    /// Send a signature request for a structured message with an ECDSA proxy
+    /// key, see [`SignerClient::request_message_signature`]
+    pub async fn request_ecdsa_message_signature(
+        &self,
+        request: &SignEcdsaMessageRequest,
+    ) -> Result<EcdsaSignature, SignerClientError> {
+        let object_root = decode_root(&request.message)?;
+        let signature = self.post(REQUEST_ECDSA_MESSAGE_SIGNATURE_PATH, &request).await?;
+        self.check_ecdsa_signature(&request.to_raw_request(object_root), &signature)?;
+
+        Ok(signature)
+    }
 
-        let signature: BlsSignature = serde_json::from_slice(&response_bytes)?;
+    /// Send a signature request for a validator registration, with the builder
+    /// domain. This is the only request Web3Signer keys can sign. If the client
+    /// has a chain, the signature is verified before being returned
+    pub async fn request_validator_registration(
+        &self,
+        request: &SignValidatorRegistrationRequest,
+    ) -> Result<BlsSignature, SignerClientError> {
+        let signature = self.post(REQUEST_VALIDATOR_REGISTRATION_PATH, &request).await?;
+        self.check_signature(&request.to_raw_request(), &signature)?;
 
         Ok(signature)
     }
+
+    /// Generate a new BLS proxy key for the given consensus pubkey. The
+    /// returned delegation is signed by the consensus key, and the proxy can
+    /// then be used to sign with `SignRequest::is_proxy`. Proxies are only
+    /// available to the module that generated them
+    pub async fn generate_proxy_key(
+        &self,
+        request: &GenerateProxyRequest,
+    ) -> Result<SignedProxyDelegationBls, SignerClientError> {
+        self.generate_proxy(request, EncryptionScheme::Bls).await
+    }
+
+    /// Generate a new ECDSA proxy key for the given consensus pubkey. The
+    /// consensus key delegates to the address of the new key, which can then
+    /// be used with [`SignerClient::request_ecdsa_signature`]
+    pub async fn generate_ecdsa_proxy_key(
+        &self,
+        request: &GenerateProxyRequest,
+    ) -> Result<SignedProxyDelegationEcdsa, SignerClientError> {
+        self.generate_proxy(request, EncryptionScheme::Ecdsa).await
+    }
+
+    async fn generate_proxy<T: DeserializeOwned>(
+        &self,
+        request: &GenerateProxyRequest,
+        scheme: EncryptionScheme,
+    ) -> Result<T, SignerClientError> {
+        let request = GenerateProxyRequest { scheme, ..request.clone() };
+        let res = self.post(GENERATE_PROXY_KEY_PATH, &request).await;
+        self.clear_pubkeys();
+
+        res
+    }
+
+    /// Revoke a BLS proxy key generated by this module. The proxy can't sign
+    /// anymore, and the returned revocation is signed by the consensus key, so
+    /// that it can be forwarded to the protocols the delegation was sent to.
+    /// Revoking a proxy again returns the same revocation
+    pub async fn revoke_proxy_key(
+        &self,
+        proxy: BlsPublicKey,
+    ) -> Result<SignedRevokedProxyBls, SignerClientError> {
+        self.revoke_proxy(&RevokeProxyRequest::new(self.module_id.as_str(), proxy)).await
+    }
+
+    /// Revoke an ECDSA proxy key generated by this module, see
+    /// [`SignerClient::revoke_proxy_key`]
+    pub async fn revoke_ecdsa_proxy_key(
+        &self,
+        proxy: Address,
+    ) -> Result<SignedRevokedProxyEcdsa, SignerClientError> {
+        self.revoke_proxy(&RevokeProxyRequest::new_ecdsa(self.module_id.as_str(), proxy)).await
+    }
+
+    async fn revoke_proxy<T: DeserializeOwned>(
+        &self,
+        request: &RevokeProxyRequest,
+    ) -> Result<T, SignerClientError> {
+        let res = self.post(REVOKE_PROXY_KEY_PATH, &request).await;
+        self.clear_pubkeys();
+
+        res
+    }
+
+    /// Verify a BLS signature made by any key, e.g. received from another
+    /// sidecar. An invalid signature is not an error, but a response with the
+    /// reason
+    pub async fn verify_signature(
+        &self,
+        request: &VerifySignatureRequest,
+    ) -> Result<VerificationResponse, SignerClientError> {
+        self.post(VERIFY_SIGNATURE_PATH, request).await
+    }
+
+    /// Verify an ECDSA signature made by any address, see
+    /// [`SignerClient::verify_signature`]
+    pub async fn verify_ecdsa_signature(
+        &self,
+        request: &VerifyEcdsaSignatureRequest,
+    ) -> Result<VerificationResponse, SignerClientError> {
+        self.post(VERIFY_ECDSA_SIGNATURE_PATH, request).await
+    }
+
+    /// Get the delegation of a BLS proxy loaded in the signer, with its status.
+    /// Unlike signing, this works for the proxies of all modules
+    pub async fn get_delegation(
+        &self,
+        proxy: BlsPublicKey,
+    ) -> Result<DelegationResponseBls, SignerClientError> {
+        let request = GetDelegationRequest { proxy: ProxyId::Bls(proxy) };
+        self.post(GET_DELEGATION_PATH, &request).await
+    }
+
+    /// Get the delegation of an ECDSA proxy loaded in the signer, see
+    /// [`SignerClient::get_delegation`]
+    pub async fn get_ecdsa_delegation(
+        &self,
+        proxy: Address,
+    ) -> Result<DelegationResponseEcdsa, SignerClientError> {
+        let request = GetDelegationRequest { proxy: ProxyId::Ecdsa(proxy) };
+        self.post(GET_DELEGATION_PATH, &request).await
+    }
+
+    /// Verify a delegation to a BLS proxy, and optionally a message signed by
+    /// the proxy. Delegations of proxies revoked in this signer are invalid
+    pub async fn verify_delegation(
+        &self,
+        request: &VerifyDelegationRequestBls,
+    ) -> Result<VerificationResponse, SignerClientError> {
+        self.post(VERIFY_DELEGATION_PATH, request).await
+    }
+
+    /// Verify a delegation to an ECDSA proxy, see
+    /// [`SignerClient::verify_delegation`]
+    pub async fn verify_ecdsa_delegation(
+        &self,
+        request: &VerifyDelegationRequestEcdsa,
+    ) -> Result<VerificationResponse, SignerClientError> {
+        self.post(VERIFY_DELEGATION_PATH, request).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, SignerClientError> {
+        self.send(Method::GET, path, None).await
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, SignerClientError> {
+        self.send(Method::POST, path, Some(serde_json::to_vec(body)?)).await
+    }
+
+    /// Sends the request with the current JWT, and parses the response.
+    /// Requests failing with a transient error are sent again, or only those
+    /// that failed to connect for POST requests, see [`RetryConfig`]
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+    ) -> Result<T, SignerClientError> {
+        let mut backoff = self.retry.initial_backoff;
+        let mut attempt = 0;
+
+        loop {
+            let res = match self.send_once(&method, path, body.clone()).await {
+                Ok((status, response_bytes)) => parse_response(status, &response_bytes),
+                Err(err) => Err(err),
+            };
+
+            let retry = |err: &SignerClientError| {
+                if method == Method::GET {
+                    err.is_transient()
+                } else {
+                    err.is_connect()
+                }
+            };
+
+            match res {
+                Err(err) if retry(&err) && attempt < self.retry.max_retries => {
+                    attempt += 1;
+                    debug!(%err, attempt, ?backoff, "Retrying signer request");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.retry.max_backoff);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Sends the request once, returns the status and body of the response
+    async fn send_once(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+    ) -> Result<(StatusCode, Bytes), SignerClientError> {
+        let jwt = self.jwt()?;
+
+        match &self.transport {
+            Transport::Http(client) => {
+                let url = format!("{}{}", self.url, path);
+                let mut request = client.request(method.clone(), url).header(AUTHORIZATION, jwt);
+                if let Some(body) = body {
+                    request = request.header(CONTENT_TYPE, "application/json").body(body);
+                }
+
+                let res = request.send().await?;
+                Ok((res.status(), res.bytes().await?))
+            }
+
+            Transport::Unix { socket_path, client } => {
+                let mut request = Request::builder()
+                    .method(method.clone())
+                    .uri(hyperlocal::Uri::new(socket_path.as_path(), path))
+                    .header(AUTHORIZATION, jwt);
+                if body.is_some() {
+                    request = request.header(CONTENT_TYPE, "application/json");
+                }
+                let request = request.body(Full::from(body.unwrap_or_default()))?;
+
+                let send = async {
+                    let res = client.request(request).await?;
+                    let status = res.status();
+                    let response_bytes = res.into_body().collect().await?.to_bytes();
+                    Ok::<_, SignerClientError>((status, response_bytes))
+                };
+
+                tokio::time::timeout(DEFAULT_REQUEST_TIMEOUT, send)
+                    .await
+                    .map_err(|_| SignerClientError::Timeout)?
+            }
+        }
+    }
+
+    /// Signing root of a request, if signatures are verified
+    fn signing_root(&self, builder_domain: bool, object_root: [u8; 32]) -> Option<[u8; 32]> {
+        let chain = self.chain?;
+        Some(if builder_domain {
+            compute_signing_root(object_root, chain.builder_domain())
+        } else {
+            compute_module_signing_root(chain, &self.module_id, object_root)
+        })
+    }
+
+    fn check_signature(
+        &self,
+        request: &SignRequest,
+        signature: &BlsSignature,
+    ) -> Result<(), SignerClientError> {
+        let Some(signing_root) = self.signing_root(request.builder_domain, request.object_root)
+        else {
+            return Ok(());
+        };
+
+        verify_signature(&request.pubkey, &signing_root, signature).map_err(|err| {
+            SignerClientError::InvalidSignature(format!("{err} for {}", request.pubkey))
+        })
+    }
+
+    fn check_ecdsa_signature(
+        &self,
+        request: &SignEcdsaRequest,
+        signature: &EcdsaSignature,
+    ) -> Result<(), SignerClientError> {
+        let Some(signing_root) = self.signing_root(request.builder_domain, request.object_root)
+        else {
+            return Ok(());
+        };
+
+        let recovered = signature
+            .recover_address_from_prehash(&B256::from(signing_root))
+            .map_err(|err| SignerClientError::InvalidSignature(err.to_string()))?;
+        if recovered != request.address {
+            return Err(SignerClientError::InvalidSignature(format!(
+                "signature from {recovered}, expected {}",
+                request.address
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Root of the message, as computed by the signer
+fn decode_root(message: &SchemaMessage) -> Result<[u8; 32], SignerClientError> {
+    let decoded =
+        message.decode().map_err(|err| SignerClientError::InvalidMessage(err.to_string()))?;
+    Ok(decoded.object_root)
+}
+
+/// Creates a new JWT for the module, as an authorization header
+fn auth_header(module_id: &str, jwt_secret: &str) -> Result<(HeaderValue, u64), SignerClientError> {
+    let (jwt, exp) = create_jwt(module_id, jwt_secret)?;
+    let mut header = HeaderValue::from_str(&format!("Bearer {}", jwt))?;
+    header.set_sensitive(true);
+
+    Ok((header, exp))
+}
+
+fn parse_response<T: DeserializeOwned>(
+    status: StatusCode,
+    response_bytes: &[u8],
+) -> Result<T, SignerClientError> {
+    if !status.is_success() {
+        return Err(SignerClientError::FailedRequest {
+            status: status.as_u16(),
+            error_msg: String::from_utf8_lossy(response_bytes).into_owned(),
+        });
+    }
+
+    Ok(serde_json::from_slice(response_bytes)?)
 }
@@ -1,2 +1,20 @@
 pub const GET_PUBKEYS_PATH: &str = "/signer/v1/get_pubkeys";
 pub const REQUEST_SIGNATURE_PATH: &str = "/signer/v1/request_signature";
+pub const REQUEST_SIGNATURES_PATH: &str = "/signer/v1/request_signatures";
+pub const REQUEST_ECDSA_SIGNATURE_PATH: &str = "/signer/v1/request_ecdsa_signature";
+pub const REQUEST_MESSAGE_SIGNATURE_PATH: &str = "/signer/v1/request_message_signature";
+pub const REQUEST_ECDSA_MESSAGE_SIGNATURE_PATH: &str = "/signer/v1/request_ecdsa_message_signature";
+pub const REQUEST_VALIDATOR_REGISTRATION_PATH: &str = "/signer/v1/request_validator_registration";
+pub const GENERATE_PROXY_KEY_PATH: &str = "/signer/v1/generate_proxy_key";
+pub const REVOKE_PROXY_KEY_PATH: &str = "/signer/v1/revoke_proxy_key";
+pub const GET_DELEGATION_PATH: &str = "/signer/v1/get_delegation";
+pub const VERIFY_SIGNATURE_PATH: &str = "/signer/v1/verify_signature";
+pub const VERIFY_ECDSA_SIGNATURE_PATH: &str = "/signer/v1/verify_ecdsa_signature";
+pub const VERIFY_DELEGATION_PATH: &str = "/signer/v1/verify_delegation";
+/// Called by the other nodes of a distributed validator cluster
+pub const PARTIAL_SIGNATURE_PATH: &str = "/signer/v1/partial_signature";
+pub const HEALTH_PATH: &str = "/health";
+pub const READY_PATH: &str = "/ready";
+
+/// Max number of requests in a batch, see `SignerClient::request_signatures`
+pub const MAX_BATCH_SIGNATURES: usize = 512;
@@ -3,12 +3,58 @@ pub enum SignerClientError {
     #[error("reqwest error: {0}")]
     ReqwestError(#[from] reqwest::Error),
 
+    #[error("unix socket error: {0}")]
+    UnixSocketError(#[from] hyper_util::client::legacy::Error),
+
+    #[error("hyper error: {0}")]
+    HyperError(#[from] hyper::Error),
+
+    #[error("http error: {0}")]
+    HttpError(#[from] hyper::http::Error),
+
+    #[error("request timed out")]
+    Timeout,
+
     #[error("invalid header value: {0}")]
     InvalidHeader(#[from] reqwest::header::InvalidHeaderValue),
 
+    #[error("jwt error: {0}")]
+    JwtError(#[from] jsonwebtoken::errors::Error),
+
     #[error("failed request: status {status} msg {error_msg}")]
     FailedRequest { status: u16, error_msg: String },
 
     #[error("serde decode error: {0}")]
     SerdeDecodeError(#[from] serde_json::Error),
+
+    /// The structured message of the request doesn't decode with its schema
+    #[error("invalid message: {0}")]
+    InvalidMessage(String),
+
+    /// The signature returned by the signer doesn't match the request
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+impl SignerClientError {
+    /// Whether the request may succeed if sent again: connection errors,
+    /// timeouts, and bad gateway or unavailable responses
+    pub fn is_transient(&self) -> bool {
+        match self {
+            SignerClientError::ReqwestError(err) => err.is_connect() || err.is_timeout(),
+            SignerClientError::UnixSocketError(err) => err.is_connect(),
+            SignerClientError::Timeout => true,
+            SignerClientError::FailedRequest { status, .. } => matches!(status, 502..=504),
+            _ => false,
+        }
+    }
+
+    /// Whether the request failed to connect, so it never reached the signer
+    pub fn is_connect(&self) -> bool {
+        match self {
+            SignerClientError::ReqwestError(err) => err.is_connect(),
+            SignerClientError::UnixSocketError(err) => err.is_connect(),
+            _ => false,
+        }
+    }
 }
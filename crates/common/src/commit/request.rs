@@ -1,27 +1,164 @@
-use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
+use alloy::{
+    primitives::Address,
+    rpc::types::beacon::{relay::ValidatorRegistrationMessage, BlsPublicKey, BlsSignature},
+};
 use serde::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
-use tree_hash::TreeHash;
-use tree_hash_derive::TreeHash;
+use tree_hash::{Hash256, MerkleHasher, PackedEncoding, TreeHash, TreeHashType};
 
-use crate::{error::BlstErrorWrapper, signature::verify_signed_builder_message, types::Chain};
+use super::schema::{MessageSchema, SchemaMessage};
+use crate::{
+    error::BlstErrorWrapper,
+    signature::{
+        compute_module_signing_root, compute_signing_root, compute_validator_registration_root,
+        verify_signed_builder_message,
+    },
+    signer::EcdsaSignature,
+    types::Chain,
+};
+
+/// First field of the revocation container, so that its root is always
+/// different from the one of a delegation
+const REVOKED_PROXY_TAG: [u8; 32] = *b"commit-boost-revoked-proxy\0\0\0\0\0\0";
+
+/// Key a consensus key can delegate to, i.e. a BLS pubkey or the address of a
+/// secp256k1 key
+pub trait ProxyKey: TreeHash + Encode + Decode {}
+
+impl<T: TreeHash + Encode + Decode> ProxyKey for T {}
 
 // TODO: might need to adapt the SignedProxyDelegation so that it goes through
 // web3 signer
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Encode, Decode, TreeHash)]
-pub struct ProxyDelegation {
+/// Delegation from a consensus key to a proxy key
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Encode, Decode)]
+pub struct ProxyDelegation<T: ProxyKey> {
     pub delegator: BlsPublicKey,
-    pub proxy: BlsPublicKey,
+    pub proxy: T,
+    /// First epoch in which the delegation is no longer valid, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_epoch: Option<u64>,
+}
+
+impl<T: ProxyKey> ProxyDelegation<T> {
+    pub fn new(delegator: BlsPublicKey, proxy: T) -> Self {
+        Self { delegator, proxy, expiry_epoch: None }
+    }
+
+    pub fn is_expired(&self, epoch: u64) -> bool {
+        self.expiry_epoch.is_some_and(|expiry_epoch| epoch >= expiry_epoch)
+    }
+}
+
+/// Hashed as a container of `delegator`, `proxy` and `expiry_epoch`, with `0`
+/// for delegations without expiry, so that the container always has the same
+/// shape. The signer never delegates with an expiry epoch of `0`, which is
+/// always in the past
+impl<T: ProxyKey> TreeHash for ProxyDelegation<T> {
+    fn tree_hash_type() -> TreeHashType {
+        TreeHashType::Container
+    }
+
+    fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+        unreachable!("Struct should never be packed.")
+    }
+
+    fn tree_hash_packing_factor() -> usize {
+        unreachable!("Struct should never be packed.")
+    }
+
+    fn tree_hash_root(&self) -> Hash256 {
+        container_root(&[
+            self.delegator.tree_hash_root().0,
+            self.proxy.tree_hash_root().0,
+            self.expiry_epoch.unwrap_or(0).tree_hash_root().0,
+        ])
+    }
 }
 
+/// Revocation of a proxy key by the consensus key which delegated to it.
+/// Signed like delegations, with the builder domain
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct SignedProxyDelegation {
-    pub message: ProxyDelegation,
+pub struct RevokedProxy<T: TreeHash> {
+    pub delegator: BlsPublicKey,
+    pub proxy: T,
+    /// Epoch from which the proxy is revoked
+    pub revoked_epoch: u64,
+}
+
+pub type RevokedProxyBls = RevokedProxy<BlsPublicKey>;
+pub type RevokedProxyEcdsa = RevokedProxy<Address>;
+
+/// Hashed as a container of a fixed tag, `delegator`, `proxy` and
+/// `revoked_epoch`
+impl<T: TreeHash> TreeHash for RevokedProxy<T> {
+    fn tree_hash_type() -> TreeHashType {
+        TreeHashType::Container
+    }
+
+    fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+        unreachable!("Struct should never be packed.")
+    }
+
+    fn tree_hash_packing_factor() -> usize {
+        unreachable!("Struct should never be packed.")
+    }
+
+    fn tree_hash_root(&self) -> Hash256 {
+        container_root(&[
+            REVOKED_PROXY_TAG,
+            self.delegator.tree_hash_root().0,
+            self.proxy.tree_hash_root().0,
+            self.revoked_epoch.tree_hash_root().0,
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SignedRevokedProxy<T: TreeHash> {
+    pub message: RevokedProxy<T>,
     /// Signature of message with the delegator keypair
     pub signature: BlsSignature,
 }
 
-impl SignedProxyDelegation {
+pub type SignedRevokedProxyBls = SignedRevokedProxy<BlsPublicKey>;
+pub type SignedRevokedProxyEcdsa = SignedRevokedProxy<Address>;
+
+impl<T: TreeHash> SignedRevokedProxy<T> {
+    pub fn validate(&self, chain: Chain) -> Result<(), BlstErrorWrapper> {
+        verify_signed_builder_message(
+            chain,
+            &self.message.delegator,
+            &self.message,
+            &self.signature,
+        )
+    }
+}
+
+/// Root of a container with the given field roots, as with the `TreeHash`
+/// derive
+fn container_root(fields: &[[u8; 32]]) -> Hash256 {
+    let mut hasher = MerkleHasher::with_leaves(fields.len());
+    for field in fields {
+        hasher.write(field).expect("number of leaves is fixed");
+    }
+    hasher.finish().expect("all leaves are written")
+}
+
+pub type ProxyDelegationBls = ProxyDelegation<BlsPublicKey>;
+pub type ProxyDelegationEcdsa = ProxyDelegation<Address>;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SignedProxyDelegation<T: ProxyKey> {
+    pub message: ProxyDelegation<T>,
+    /// Signature of message with the delegator keypair
+    pub signature: BlsSignature,
+}
+
+pub type SignedProxyDelegationBls = SignedProxyDelegation<BlsPublicKey>;
+pub type SignedProxyDelegationEcdsa = SignedProxyDelegation<Address>;
+
+impl<T: ProxyKey> SignedProxyDelegation<T> {
     pub fn validate(&self, chain: Chain) -> Result<(), BlstErrorWrapper> {
         verify_signed_builder_message(
             chain,
@@ -38,6 +175,10 @@ pub struct SignRequest {
     pub pubkey: BlsPublicKey,
     pub is_proxy: bool,
     pub object_root: [u8; 32],
+    /// Sign with the builder domain instead of the module domain, only allowed
+    /// for privileged modules
+    #[serde(default)]
+    pub builder_domain: bool,
 }
 
 impl SignRequest {
@@ -47,7 +188,7 @@ impl SignRequest {
         is_proxy: bool,
         object_root: [u8; 32],
     ) -> SignRequest {
-        Self { id: id.into(), pubkey, is_proxy, object_root }
+        Self { id: id.into(), pubkey, is_proxy, object_root, builder_domain: false }
     }
 
     pub fn builder(id: impl Into<String>, pubkey: BlsPublicKey) -> Self {
@@ -58,6 +199,44 @@ impl SignRequest {
         Self { is_proxy: true, ..self }
     }
 
+    pub fn with_builder_domain(self) -> Self {
+        Self { builder_domain: true, ..self }
+    }
+
+    pub fn with_root(self, object_root: [u8; 32]) -> Self {
+        Self { object_root, ..self }
+    }
+
+    pub fn with_msg(self, msg: &impl TreeHash) -> Self {
+        Self { object_root: msg.tree_hash_root().0, ..self }
+    }
+}
+
+/// Request for an ECDSA signature with a proxy key, identified by its address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignEcdsaRequest {
+    pub id: String,
+    pub address: Address,
+    pub object_root: [u8; 32],
+    /// Sign with the builder domain instead of the module domain, only allowed
+    /// for privileged modules
+    #[serde(default)]
+    pub builder_domain: bool,
+}
+
+impl SignEcdsaRequest {
+    pub fn new(id: impl Into<String>, address: Address, object_root: [u8; 32]) -> Self {
+        Self { id: id.into(), address, object_root, builder_domain: false }
+    }
+
+    pub fn builder(id: impl Into<String>, address: Address) -> Self {
+        Self::new(id, address, [0; 32])
+    }
+
+    pub fn with_builder_domain(self) -> Self {
+        Self { builder_domain: true, ..self }
+    }
+
     pub fn with_root(self, object_root: [u8; 32]) -> Self {
         Self { object_root, ..self }
     }
@@ -66,3 +245,318 @@ impl SignRequest {
         Self { object_root: msg.tree_hash_root().0, ..self }
     }
 }
+
+/// Request for a signature of a structured message, which the signer decodes
+/// and hashes itself, see [`MessageSchema`]. Otherwise the same as
+/// [`SignRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignMessageRequest {
+    pub id: String,
+    pub pubkey: BlsPublicKey,
+    pub is_proxy: bool,
+    pub message: SchemaMessage,
+    #[serde(default)]
+    pub builder_domain: bool,
+}
+
+impl SignMessageRequest {
+    pub fn new(id: impl Into<String>, pubkey: BlsPublicKey, msg: &impl MessageSchema) -> Self {
+        Self {
+            id: id.into(),
+            pubkey,
+            is_proxy: false,
+            message: SchemaMessage::new(msg),
+            builder_domain: false,
+        }
+    }
+
+    pub fn is_proxy(self) -> Self {
+        Self { is_proxy: true, ..self }
+    }
+
+    pub fn with_builder_domain(self) -> Self {
+        Self { builder_domain: true, ..self }
+    }
+
+    /// Request for the root of the decoded message
+    pub fn to_raw_request(&self, object_root: [u8; 32]) -> SignRequest {
+        SignRequest {
+            id: self.id.clone(),
+            pubkey: self.pubkey,
+            is_proxy: self.is_proxy,
+            object_root,
+            builder_domain: self.builder_domain,
+        }
+    }
+}
+
+/// Request for an ECDSA signature of a structured message, see
+/// [`SignMessageRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignEcdsaMessageRequest {
+    pub id: String,
+    pub address: Address,
+    pub message: SchemaMessage,
+    #[serde(default)]
+    pub builder_domain: bool,
+}
+
+impl SignEcdsaMessageRequest {
+    pub fn new(id: impl Into<String>, address: Address, msg: &impl MessageSchema) -> Self {
+        Self { id: id.into(), address, message: SchemaMessage::new(msg), builder_domain: false }
+    }
+
+    pub fn with_builder_domain(self) -> Self {
+        Self { builder_domain: true, ..self }
+    }
+
+    /// Request for the root of the decoded message
+    pub fn to_raw_request(&self, object_root: [u8; 32]) -> SignEcdsaRequest {
+        SignEcdsaRequest {
+            id: self.id.clone(),
+            address: self.address,
+            object_root,
+            builder_domain: self.builder_domain,
+        }
+    }
+}
+
+/// Request for a signature of a validator registration with a consensus key,
+/// with the builder domain. Only allowed for privileged modules. Unlike other
+/// objects, registrations can also be signed by Web3Signer keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignValidatorRegistrationRequest {
+    pub id: String,
+    pub registration: ValidatorRegistrationMessage,
+}
+
+impl SignValidatorRegistrationRequest {
+    pub fn new(id: impl Into<String>, registration: ValidatorRegistrationMessage) -> Self {
+        Self { id: id.into(), registration }
+    }
+
+    /// Request for the root of the registration
+    pub fn to_raw_request(&self) -> SignRequest {
+        SignRequest::builder(self.id.clone(), self.registration.pubkey)
+            .with_root(compute_validator_registration_root(&self.registration))
+            .with_builder_domain()
+    }
+}
+
+/// Type of proxy key to generate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionScheme {
+    #[default]
+    Bls,
+    Ecdsa,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateProxyRequest {
+    /// Id of the module requesting the proxy
+    pub id: String,
+    /// Consensus pubkey which will delegate to the new proxy
+    pub pubkey: BlsPublicKey,
+    #[serde(default)]
+    pub scheme: EncryptionScheme,
+    /// First epoch in which the delegation is no longer valid. If not set, the
+    /// proxy is valid until revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_epoch: Option<u64>,
+}
+
+impl GenerateProxyRequest {
+    /// Request for a BLS proxy key
+    pub fn new(id: impl Into<String>, pubkey: BlsPublicKey) -> Self {
+        Self { id: id.into(), pubkey, scheme: EncryptionScheme::Bls, expiry_epoch: None }
+    }
+
+    /// Request for an ECDSA proxy key
+    pub fn new_ecdsa(id: impl Into<String>, pubkey: BlsPublicKey) -> Self {
+        Self { id: id.into(), pubkey, scheme: EncryptionScheme::Ecdsa, expiry_epoch: None }
+    }
+
+    pub fn with_expiry(self, expiry_epoch: u64) -> Self {
+        Self { expiry_epoch: Some(expiry_epoch), ..self }
+    }
+}
+
+/// A BLS proxy pubkey, or the address of an ECDSA proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProxyId {
+    Bls(BlsPublicKey),
+    Ecdsa(Address),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeProxyRequest {
+    /// Id of the module which generated the proxy
+    pub id: String,
+    pub proxy: ProxyId,
+}
+
+impl RevokeProxyRequest {
+    /// Request to revoke a BLS proxy key
+    pub fn new(id: impl Into<String>, proxy: BlsPublicKey) -> Self {
+        Self { id: id.into(), proxy: ProxyId::Bls(proxy) }
+    }
+
+    /// Request to revoke an ECDSA proxy key
+    pub fn new_ecdsa(id: impl Into<String>, proxy: Address) -> Self {
+        Self { id: id.into(), proxy: ProxyId::Ecdsa(proxy) }
+    }
+}
+
+/// Whether a proxy can still be used to sign
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyStatus {
+    Active,
+    Expired,
+    Revoked,
+}
+
+/// A proxy generated by a module, with its status, see
+/// [`GetPubkeysResponse`](super::client::GetPubkeysResponse)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyInfo {
+    pub proxy: ProxyId,
+    pub delegator: BlsPublicKey,
+    pub status: ProxyStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_epoch: Option<u64>,
+}
+
+/// Domain of a signature to verify
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureDomain {
+    /// The object root is signed as is with the builder domain, as delegations
+    /// and messages of privileged modules
+    Builder,
+    /// The object root is scoped to the module with the given id, and signed
+    /// with the commit boost domain
+    Module(String),
+}
+
+impl SignatureDomain {
+    pub fn signing_root(&self, chain: Chain, object_root: [u8; 32]) -> [u8; 32] {
+        match self {
+            Self::Builder => compute_signing_root(object_root, chain.builder_domain()),
+            Self::Module(module_id) => compute_module_signing_root(chain, module_id, object_root),
+        }
+    }
+}
+
+/// Request to verify a BLS signature, made by any key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifySignatureRequest {
+    pub pubkey: BlsPublicKey,
+    pub object_root: [u8; 32],
+    pub domain: SignatureDomain,
+    pub signature: BlsSignature,
+}
+
+/// Request to verify an ECDSA signature, made by any address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyEcdsaSignatureRequest {
+    pub address: Address,
+    pub object_root: [u8; 32],
+    pub domain: SignatureDomain,
+    pub signature: EcdsaSignature,
+}
+
+/// Request for the delegation of a proxy loaded in the signer, generated by any
+/// module
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GetDelegationRequest {
+    pub proxy: ProxyId,
+}
+
+/// A message signed by a proxy key, see [`VerifyDelegationRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxySignedMessage<S> {
+    pub object_root: [u8; 32],
+    pub domain: SignatureDomain,
+    pub signature: S,
+}
+
+/// Request to verify a delegation, and optionally a message signed by its
+/// proxy: the delegation must be signed by the delegator with the builder
+/// domain, not be expired and not be revoked in the signer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyDelegationRequest<T: ProxyKey, S> {
+    pub delegation: SignedProxyDelegation<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<ProxySignedMessage<S>>,
+}
+
+pub type VerifyDelegationRequestBls = VerifyDelegationRequest<BlsPublicKey, BlsSignature>;
+pub type VerifyDelegationRequestEcdsa = VerifyDelegationRequest<Address, EcdsaSignature>;
+
+impl<T: ProxyKey, S> VerifyDelegationRequest<T, S> {
+    pub fn new(delegation: SignedProxyDelegation<T>) -> Self {
+        Self { delegation, message: None }
+    }
+
+    /// Also verify a signature of the proxy
+    pub fn with_message(
+        self,
+        object_root: [u8; 32],
+        domain: SignatureDomain,
+        signature: S,
+    ) -> Self {
+        Self { message: Some(ProxySignedMessage { object_root, domain, signature }), ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+    use ssz::{Decode, Encode};
+    use tree_hash::TreeHash;
+    use tree_hash_derive::TreeHash;
+
+    use super::{ProxyDelegation, RevokedProxy};
+
+    #[derive(TreeHash)]
+    struct FixedProxyDelegation {
+        delegator: BlsPublicKey,
+        proxy: BlsPublicKey,
+        expiry_epoch: u64,
+    }
+
+    #[test]
+    fn test_delegation_roots() {
+        let delegator = BlsPublicKey::repeat_byte(1);
+        let proxy = BlsPublicKey::repeat_byte(2);
+
+        // delegations without expiry are hashed with an expiry epoch of 0
+        let delegation = ProxyDelegation::new(delegator, proxy);
+        let fixed = FixedProxyDelegation { delegator, proxy, expiry_epoch: 0 };
+        assert_eq!(delegation.tree_hash_root(), fixed.tree_hash_root());
+
+        let expiring = ProxyDelegation { expiry_epoch: Some(10), ..delegation };
+        let fixed = FixedProxyDelegation { expiry_epoch: 10, ..fixed };
+        assert_eq!(expiring.tree_hash_root(), fixed.tree_hash_root());
+        assert_ne!(expiring.tree_hash_root(), delegation.tree_hash_root());
+        assert!(!expiring.is_expired(9));
+        assert!(expiring.is_expired(10));
+
+        let revoked = RevokedProxy { delegator, proxy, revoked_epoch: 10 };
+        assert_ne!(revoked.tree_hash_root(), expiring.tree_hash_root());
+    }
+
+    #[test]
+    fn test_delegation_ssz() {
+        let delegation =
+            ProxyDelegation::new(BlsPublicKey::repeat_byte(1), Address::repeat_byte(2));
+        let decoded =
+            ProxyDelegation::<Address>::from_ssz_bytes(&delegation.as_ssz_bytes()).unwrap();
+        assert_eq!(decoded.delegator, delegation.delegator);
+        assert_eq!(decoded.proxy, delegation.proxy);
+        assert_eq!(decoded.expiry_epoch, delegation.expiry_epoch);
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::RwLock;
+
+use alloy::primitives::{keccak256, Bytes, B256};
+use eyre::{bail, eyre};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use self::examples::{Datagram, PreconfCommitment};
+
+/// A message type which the signer decodes and hashes itself, instead of
+/// signing a root sent by the module. To add a schema, implement this trait and
+/// register the type with [`register_schema`]
+pub trait MessageSchema: Encode + Decode + TreeHash {
+    /// Name the schema is registered under
+    const NAME: &'static str;
+
+    /// Slot the message is for, checked against the `max_slot_lookahead` of
+    /// the module policy
+    fn slot(&self) -> Option<u64> {
+        None
+    }
+
+    /// Human readable description, logged by the signer
+    fn summary(&self) -> String;
+}
+
+/// A registered schema, with the function decoding its messages
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaEntry {
+    pub name: &'static str,
+    pub decode: fn(&[u8]) -> eyre::Result<DecodedMessage>,
+}
+
+impl SchemaEntry {
+    pub const fn of<M: MessageSchema>() -> Self {
+        Self { name: M::NAME, decode: decode::<M> }
+    }
+}
+
+lazy_static! {
+    /// Schemas known to the signer and to the module clients, which decode the
+    /// messages to compute their root
+    static ref SCHEMA_REGISTRY: RwLock<Vec<SchemaEntry>> = RwLock::new(vec![
+        SchemaEntry::of::<Datagram>(),
+        SchemaEntry::of::<PreconfCommitment>(),
+    ]);
+}
+
+/// Registers a schema defined outside this crate. Custom signer binaries must
+/// register their schemas before starting the signer, and modules before
+/// sending messages of them
+pub fn register_schema<M: MessageSchema>() -> eyre::Result<()> {
+    let mut registry = SCHEMA_REGISTRY.write().expect("schema registry poisoned");
+    if registry.iter().any(|entry| entry.name == M::NAME) {
+        bail!("schema {} is already registered", M::NAME);
+    }
+    registry.push(SchemaEntry::of::<M>());
+
+    Ok(())
+}
+
+/// Registered schema with the given name
+pub fn find_schema(name: &str) -> Option<SchemaEntry> {
+    let registry = SCHEMA_REGISTRY.read().expect("schema registry poisoned");
+    registry.iter().find(|entry| entry.name == name).copied()
+}
+
+/// Names of the registered schemas
+pub fn schema_names() -> Vec<&'static str> {
+    let registry = SCHEMA_REGISTRY.read().expect("schema registry poisoned");
+    registry.iter().map(|entry| entry.name).collect()
+}
+
+/// Example schemas, which don't follow any published spec. They show how to
+/// implement [`MessageSchema`], and are used by the examples and tests: modules
+/// committing to a standard format should register their own type
+pub mod examples {
+    use alloy::primitives::hex;
+    use ssz_derive::{Decode, Encode};
+    use tree_hash_derive::TreeHash;
+
+    use super::MessageSchema;
+
+    /// Arbitrary data, as committed to by the DA commit example
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TreeHash)]
+    pub struct Datagram {
+        pub data: u64,
+    }
+
+    impl MessageSchema for Datagram {
+        const NAME: &'static str = "Datagram";
+
+        fn summary(&self) -> String {
+            format!("datagram {}", self.data)
+        }
+    }
+
+    /// Commitment to include a transaction in the block of a slot, with a
+    /// made up layout
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TreeHash)]
+    pub struct PreconfCommitment {
+        pub slot: u64,
+        pub tx_hash: [u8; 32],
+    }
+
+    impl MessageSchema for PreconfCommitment {
+        const NAME: &'static str = "PreconfCommitment";
+
+        fn slot(&self) -> Option<u64> {
+            Some(self.slot)
+        }
+
+        fn summary(&self) -> String {
+            format!("inclusion of tx {} in slot {}", hex::encode_prefixed(self.tx_hash), self.slot)
+        }
+    }
+}
+
+/// Object root signed for a structured message. The schema is hashed with the
+/// message, so that messages of different schemas with the same root don't
+/// have the same signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TreeHash)]
+pub struct SchemaMessageRoot {
+    /// Keccak hash of the schema name
+    pub schema_id: B256,
+    pub message_root: B256,
+}
+
+impl SchemaMessageRoot {
+    pub fn new<M: MessageSchema>(msg: &M) -> Self {
+        Self { schema_id: keccak256(M::NAME), message_root: msg.tree_hash_root().0.into() }
+    }
+}
+
+/// SSZ encoded message of a registered schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaMessage {
+    pub schema: String,
+    pub ssz: Bytes,
+}
+
+impl SchemaMessage {
+    pub fn new<M: MessageSchema>(msg: &M) -> Self {
+        Self { schema: M::NAME.to_owned(), ssz: msg.as_ssz_bytes().into() }
+    }
+
+    pub fn decode(&self) -> eyre::Result<DecodedMessage> {
+        decode_message(&self.schema, &self.ssz)
+    }
+}
+
+/// What the signer knows of a message after decoding it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedMessage {
+    pub schema: &'static str,
+    /// Root of the [`SchemaMessageRoot`] of the message
+    pub object_root: [u8; 32],
+    pub slot: Option<u64>,
+    pub summary: String,
+}
+
+impl DecodedMessage {
+    fn new<M: MessageSchema>(msg: M) -> Self {
+        Self {
+            schema: M::NAME,
+            object_root: SchemaMessageRoot::new(&msg).tree_hash_root().0,
+            slot: msg.slot(),
+            summary: msg.summary(),
+        }
+    }
+}
+
+/// Decodes a message with the given schema. Trailing or missing bytes fail
+/// the decoding, so the root is always of the full message
+pub fn decode_message(schema: &str, ssz: &[u8]) -> eyre::Result<DecodedMessage> {
+    let entry = find_schema(schema).ok_or_else(|| eyre!("unknown schema {schema}"))?;
+    (entry.decode)(ssz)
+}
+
+fn decode<M: MessageSchema>(ssz: &[u8]) -> eyre::Result<DecodedMessage> {
+    let msg =
+        M::from_ssz_bytes(ssz).map_err(|err| eyre!("invalid {} message: {err:?}", M::NAME))?;
+    Ok(DecodedMessage::new(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_derive::{Decode, Encode};
+    use tree_hash::TreeHash;
+    use tree_hash_derive::TreeHash;
+
+    use super::{
+        decode_message,
+        examples::{Datagram, PreconfCommitment},
+        find_schema, register_schema, schema_names, MessageSchema, SchemaMessage,
+        SchemaMessageRoot,
+    };
+
+    #[test]
+    fn test_decode_message() {
+        let commitment = PreconfCommitment { slot: 10, tx_hash: [1; 32] };
+        let decoded = SchemaMessage::new(&commitment).decode().unwrap();
+        assert_eq!(decoded.schema, "PreconfCommitment");
+        assert_eq!(decoded.object_root, SchemaMessageRoot::new(&commitment).tree_hash_root().0);
+        assert_ne!(decoded.object_root, commitment.tree_hash_root().0);
+        assert_eq!(decoded.slot, Some(10));
+
+        let datagram = SchemaMessage::new(&Datagram { data: 1 });
+        assert_eq!(datagram.decode().unwrap().slot, None);
+
+        // messages are only decoded with the schema they are sent with
+        assert!(decode_message(Datagram::NAME, &SchemaMessage::new(&commitment).ssz).is_err());
+        assert!(decode_message("Unknown", &datagram.ssz).is_err());
+
+        let mut extra = datagram.ssz.to_vec();
+        extra.push(0);
+        assert!(decode_message(Datagram::NAME, &extra).is_err());
+
+        for schema in schema_names() {
+            assert!(decode_message(schema, &[]).is_err());
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TreeHash)]
+    struct Downstream {
+        value: u64,
+    }
+
+    impl MessageSchema for Downstream {
+        const NAME: &'static str = "Downstream";
+
+        fn summary(&self) -> String {
+            format!("downstream {}", self.value)
+        }
+    }
+
+    #[test]
+    fn test_register_schema() {
+        let message = SchemaMessage::new(&Downstream { value: 7 });
+        assert!(find_schema(Downstream::NAME).is_none());
+        assert!(message.decode().is_err());
+
+        register_schema::<Downstream>().unwrap();
+        assert!(schema_names().contains(&Downstream::NAME));
+        assert_eq!(message.decode().unwrap().summary, "downstream 7");
+
+        // registered names can't be taken over
+        assert!(register_schema::<Downstream>().is_err());
+        assert!(register_schema::<Datagram>().is_err());
+    }
+}
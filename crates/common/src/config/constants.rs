@@ -14,7 +14,77 @@ pub const SIGNER_DIR_KEYS: &str = "/keys";
 pub const SIGNER_DIR_SECRETS_ENV: &str = "SIGNER_LOADER_DIR_SECRETS";
 pub const SIGNER_DIR_SECRETS: &str = "/secrets";
 
+pub const PROXY_DIR_KEYS_ENV: &str = "CB_PROXY_KEYS_DIR";
+pub const PROXY_DIR_KEYS: &str = "/proxy_keys";
+pub const PROXY_DIR_SECRETS_ENV: &str = "CB_PROXY_SECRETS_DIR";
+pub const PROXY_DIR_SECRETS: &str = "/proxy_secrets";
+
+pub const KEYMANAGER_DIR_KEYS_ENV: &str = "CB_KEYMANAGER_KEYS_DIR";
+pub const KEYMANAGER_DIR_KEYS: &str = "/keymanager_keys";
+pub const KEYMANAGER_DIR_SECRETS_ENV: &str = "CB_KEYMANAGER_SECRETS_DIR";
+pub const KEYMANAGER_DIR_SECRETS: &str = "/keymanager_secrets";
+pub const KEYMANAGER_TOKEN_ENV: &str = "CB_KEYMANAGER_TOKEN";
+
+pub const THRESHOLD_SECRETS_ENV: &str = "CB_THRESHOLD_SECRETS";
+
+pub const PKCS11_PIN_ENV: &str = "CB_PKCS11_PIN";
+pub const PKCS11_DIR_KEYS_ENV: &str = "CB_PKCS11_KEYS_DIR";
+pub const PKCS11_DIR_KEYS: &str = "/pkcs11_keys";
+
+pub const SIGNER_SOCKET_DIR_ENV: &str = "CB_SIGNER_SOCKET_DIR";
+pub const SIGNER_SOCKET_DIR: &str = "/run/commit-boost";
+pub const SIGNER_SOCKET_FILE: &str = "signer.sock";
+
+/// Certificates of the PBS and signer listeners, when served over TLS
+pub const TLS_CERT_ENV: &str = "CB_TLS_CERT_FILE";
+pub const TLS_CERT: &str = "/tls/server.crt";
+pub const TLS_KEY_ENV: &str = "CB_TLS_KEY_FILE";
+pub const TLS_KEY: &str = "/tls/server.key";
+pub const TLS_CLIENT_CA_CERT_ENV: &str = "CB_TLS_CLIENT_CA_CERT_FILE";
+pub const TLS_CLIENT_CA_CERT: &str = "/tls/client_ca.crt";
+
+/// Certificates of the connections between the nodes of a distributed validator
+pub const PEER_CA_CERT_ENV: &str = "CB_PEER_CA_CERT_FILE";
+pub const PEER_CA_CERT: &str = "/tls/peer_ca.crt";
+pub const PEER_CERT_ENV: &str = "CB_PEER_CERT_FILE";
+pub const PEER_CERT: &str = "/tls/peer.crt";
+pub const PEER_KEY_ENV: &str = "CB_PEER_KEY_FILE";
+pub const PEER_KEY: &str = "/tls/peer.key";
+
+/// Certificates of the commit modules, to connect to a signer served over TLS
+pub const SIGNER_CA_CERT_ENV: &str = "CB_SIGNER_CA_CERT_FILE";
+pub const SIGNER_CA_CERT: &str = "/tls/signer_ca.crt";
+pub const MODULE_CERT_ENV: &str = "CB_MODULE_CERT_FILE";
+pub const MODULE_CERT: &str = "/tls/module.crt";
+pub const MODULE_KEY_ENV: &str = "CB_MODULE_KEY_FILE";
+pub const MODULE_KEY: &str = "/tls/module.key";
+
+/// Overrides the address services listen on, e.g. in containers where the
+/// configured host is where the port is published instead
+pub const LISTEN_HOST_ENV: &str = "CB_LISTEN_HOST";
+
+pub const AUDIT_DIR_ENV: &str = "CB_AUDIT_DIR";
+pub const AUDIT_DIR: &str = "/audit";
+pub const AUDIT_KEY_ENV: &str = "CB_AUDIT_KEY_FILE";
+pub const AUDIT_KEY: &str = "/audit.key";
+
+pub const DIRK_CERT_ENV: &str = "CB_DIRK_CERT_FILE";
+pub const DIRK_CERT: &str = "/dirk/client.crt";
+pub const DIRK_KEY_ENV: &str = "CB_DIRK_KEY_FILE";
+pub const DIRK_KEY: &str = "/dirk/client.key";
+pub const DIRK_CA_CERT_ENV: &str = "CB_DIRK_CA_CERT_FILE";
+pub const DIRK_CA_CERT: &str = "/dirk/ca.crt";
+
 pub const JWTS_ENV: &str = "CB_JWTS";
+pub const JWTS_FILE_ENV: &str = "CB_JWTS_FILE";
+pub const JWTS_FILE: &str = "/jwts.json";
+
+pub const LOGS_DIR_ENV: &str = "CB_LOGS_DIR";
+pub const LOGS_DIR_DEFAULT: &str = "/var/log/commit-boost";
+
+/// Ids used in logs and traces for the default services
+pub const PBS_MODULE_NAME: &str = "pbs";
+pub const SIGNER_MODULE_NAME: &str = "signer";
 
 // TODO: replace these with an actual image in the registry
 pub const PBS_DEFAULT_IMAGE: &str = "commitboost_pbs_default";
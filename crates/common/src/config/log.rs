@@ -0,0 +1,106 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{constants::CB_CONFIG_ENV, load_file_from_env, LOGS_DIR_ENV};
+use crate::utils::default_u64;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogsSettings {
+    /// Format of the logs, both on stdout and on file
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Default log level, e.g. "info" or "cb_pbs=debug,info". Overridden by
+    /// `RUST_LOG` if set
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    /// Log level by service, keyed by module id, or "pbs" and "signer"
+    #[serde(default)]
+    pub services: HashMap<String, String>,
+    /// Optional rolling log files, one per service
+    pub file: Option<FileLogSettings>,
+}
+
+impl Default for LogsSettings {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            level: default_log_level(),
+            services: HashMap::new(),
+            file: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable text
+    #[default]
+    Pretty,
+    /// One JSON object per line, with the span fields (e.g. `slot`,
+    /// `relay_id`, `req_id`) flattened at the top level
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FileLogSettings {
+    /// Directory on the host where log files are written
+    pub dir_path: PathBuf,
+    /// How often log files are rotated
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// Optional max size of a log file in MB, after which it's rotated
+    pub max_size_mb: Option<u64>,
+    /// How many rotated log files to keep
+    #[serde(default = "default_u64::<10>")]
+    pub max_files: u64,
+}
+
+impl FileLogSettings {
+    /// Directory where log files are written, inside Docker this is the
+    /// mounted log directory
+    pub fn dir(&self) -> PathBuf {
+        std::env::var(LOGS_DIR_ENV).map(PathBuf::from).unwrap_or(self.dir_path.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Hourly,
+    /// Only rotate on size, if `max_size_mb` is set
+    Never,
+}
+
+impl LogsSettings {
+    /// Loads the `[logs]` section from the config file. Returns the default
+    /// settings if the config file is not set, e.g. when running a module
+    /// outside of Docker
+    pub fn load_from_env() -> Result<Self> {
+        #[derive(Deserialize)]
+        struct StubConfig {
+            #[serde(default)]
+            logs: LogsSettings,
+        }
+
+        if std::env::var(CB_CONFIG_ENV).is_err() {
+            return Ok(Self::default());
+        }
+
+        let config: StubConfig = load_file_from_env(CB_CONFIG_ENV)?;
+        Ok(config.logs)
+    }
+
+    /// Log level for the given service
+    pub fn level_for(&self, module_id: &str) -> &str {
+        self.services.get(module_id).unwrap_or(&self.level)
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_owned()
+}
@@ -4,17 +4,23 @@ use serde::{Deserialize, Serialize};
 use crate::types::Chain;
 
 mod constants;
+mod log;
 mod metrics;
 mod module;
+mod otlp;
 mod pbs;
 mod signer;
+mod tls;
 mod utils;
 
 pub use constants::*;
+pub use log::*;
 pub use metrics::*;
 pub use module::*;
+pub use otlp::*;
 pub use pbs::*;
 pub use signer::*;
+pub use tls::*;
 pub use utils::*;
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -26,6 +32,9 @@ pub struct CommitBoostConfig {
     pub modules: Option<Vec<StaticModuleConfig>>,
     pub signer: Option<SignerConfig>,
     pub metrics: MetricsConfig,
+    pub otlp: Option<OtlpConfig>,
+    #[serde(default)]
+    pub logs: LogsSettings,
 }
 
 impl CommitBoostConfig {
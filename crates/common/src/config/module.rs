@@ -1,14 +1,16 @@
+use std::path::PathBuf;
+
 use eyre::{ContextCompat, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use toml::Table;
 
 use crate::{
-    commit::client::SignerClient,
+    commit::client::{SignerClient, SignerClientTls},
     config::{
         constants::{CB_CONFIG_ENV, MODULE_ID_ENV, MODULE_JWT_ENV, SIGNER_SERVER_ENV},
         load_env_var,
         utils::load_file_from_env,
-        BUILDER_SERVER_ENV,
+        SigningPolicy, BUILDER_SERVER_ENV,
     },
     types::Chain,
 };
@@ -31,6 +33,22 @@ pub struct StaticModuleConfig {
     /// Type of the module
     #[serde(rename = "type")]
     pub kind: ModuleKind,
+    /// Whether the module can request signatures with the builder domain. By
+    /// default, signatures are scoped to the module, so that they can't be
+    /// used as builder messages
+    #[serde(default)]
+    pub allow_builder_domain: bool,
+    /// Restrictions on the signatures the module can request
+    pub policy: Option<SigningPolicy>,
+    /// Client certificate of the module, needed if the signer requires mTLS
+    pub tls: Option<ModuleTlsConfig>,
+}
+
+/// Client certificate and key of a commit module, as PEM files
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModuleTlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
 }
 
 /// Runtime config to start a module
@@ -49,7 +67,7 @@ pub struct StartCommitModuleConfig<T = ()> {
 /// Loads a module config from the environment and config file:
 /// - [MODULE_ID_ENV] - the id of the module to load
 /// - [CB_CONFIG_ENV] - the path to the config file
-/// - [MODULE_JWT_ENV] - the jwt token for the module
+/// - [MODULE_JWT_ENV] - the secret to sign the jwts of the module with
 // TODO: add metrics url here
 pub fn load_commit_module_config<T: DeserializeOwned>() -> Result<StartCommitModuleConfig<T>> {
     let module_id = load_env_var(MODULE_ID_ENV)?;
@@ -98,7 +116,11 @@ pub fn load_commit_module_config<T: DeserializeOwned>() -> Result<StartCommitMod
         .find(|m| m.static_config.id == module_id)
         .wrap_err(format!("failed to find module for {module_id}"))?;
 
-    let signer_client = SignerClient::new(signer_server_address, &module_jwt)?;
+    let mut signer_client = SignerClient::new(signer_server_address, &module_id, &module_jwt)?
+        .with_chain(cb_config.chain);
+    if let Some(tls) = SignerClientTls::from_env() {
+        signer_client = signer_client.with_tls(&tls)?;
+    }
 
     Ok(StartCommitModuleConfig {
         id: module_config.static_config.id,
@@ -0,0 +1,35 @@
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{constants::CB_CONFIG_ENV, load_file_from_env};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OtlpConfig {
+    /// Base url of the OTLP/HTTP collector, e.g. `http://jaeger:4318`. Spans
+    /// are sent to `{endpoint}/v1/traces`
+    pub endpoint: Url,
+}
+
+impl OtlpConfig {
+    pub fn traces_url(&self) -> Result<Url> {
+        Ok(self.endpoint.join("v1/traces")?)
+    }
+
+    /// Loads the optional `[otlp]` section from the config file. Returns None
+    /// if the config file is not set, e.g. when running a module outside of
+    /// Docker
+    pub fn load_from_env() -> Result<Option<Self>> {
+        #[derive(Deserialize)]
+        struct StubConfig {
+            otlp: Option<OtlpConfig>,
+        }
+
+        if std::env::var(CB_CONFIG_ENV).is_err() {
+            return Ok(None);
+        }
+
+        let config: StubConfig = load_file_from_env(CB_CONFIG_ENV)?;
+        Ok(config.otlp)
+    }
+}
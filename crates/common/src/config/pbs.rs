@@ -1,6 +1,10 @@
 //! Configuration for the PBS module
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 
 use alloy::primitives::U256;
 use eyre::Result;
@@ -8,11 +12,14 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use super::{constants::PBS_DEFAULT_IMAGE, CommitBoostConfig};
 use crate::{
-    commit::client::SignerClient,
-    config::{load_env_var, load_file_from_env, CB_CONFIG_ENV, MODULE_JWT_ENV, SIGNER_SERVER_ENV},
+    commit::client::{SignerClient, SignerClientTls},
+    config::{
+        listen_address, load_env_var, load_file_from_env, TlsConfig, CB_CONFIG_ENV, MODULE_JWT_ENV,
+        PBS_MODULE_NAME, SIGNER_SERVER_ENV,
+    },
     pbs::{BuilderEventPublisher, DefaultTimeout, RelayClient, RelayEntry, LATE_IN_SLOT_TIME_MS},
     types::Chain,
-    utils::{as_eth_str, default_bool, default_u256, default_u64},
+    utils::{as_eth_str, default_bool, default_host, default_u256, default_u64},
 };
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -33,10 +40,16 @@ pub struct RelayConfig {
     pub frequency_get_header_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PbsConfig {
+    /// Address to listen on, e.g. `::` for IPv6 or `127.0.0.1` for loopback
+    /// only. In Docker, this is the host the port is published on
+    #[serde(default = "default_host")]
+    pub host: IpAddr,
     /// Port to receive BuilderAPI calls from beacon node
     pub port: u16,
+    /// Serve the BuilderAPI over TLS
+    pub tls: Option<TlsConfig>,
     /// Whether to forward `get_status`` to relays or skip it
     pub relay_check: bool,
     /// Timeout for get_header request in milliseconds
@@ -59,8 +72,19 @@ pub struct PbsConfig {
     pub late_in_slot_time_ms: u64,
 }
 
+impl PbsConfig {
+    /// Overrides the TLS paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        Self { tls: self.tls.map(TlsConfig::with_env_paths), ..self }
+    }
+
+    pub fn listen_address(&self) -> Result<SocketAddr> {
+        listen_address(self.host, self.port)
+    }
+}
+
 /// Static pbs config from config file
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct StaticPbsConfig {
     /// Docker image of the module
     #[serde(default = "default_pbs")]
@@ -105,7 +129,7 @@ pub fn load_pbs_config() -> Result<PbsModuleConfig<()>> {
 
     Ok(PbsModuleConfig {
         chain: config.chain,
-        pbs_config: Arc::new(config.pbs.pbs_config),
+        pbs_config: Arc::new(config.pbs.pbs_config.with_env_paths()),
         relays: relay_clients,
         signer_client: None,
         event_publiher: maybe_publiher,
@@ -137,17 +161,23 @@ pub fn load_pbs_custom_config<T: DeserializeOwned>() -> Result<PbsModuleConfig<T
     let maybe_publiher = BuilderEventPublisher::new_from_env();
 
     let signer_client = if cb_config.pbs.static_config.with_signer {
-        // if custom pbs requires a signer client, load jwt
+        // if custom pbs requires a signer client, load jwt secret
         let module_jwt = load_env_var(MODULE_JWT_ENV)?;
         let signer_server_address = load_env_var(SIGNER_SERVER_ENV)?;
-        Some(SignerClient::new(signer_server_address, &module_jwt)?)
+        let mut signer_client =
+            SignerClient::new(signer_server_address, PBS_MODULE_NAME, &module_jwt)?
+                .with_chain(cb_config.chain);
+        if let Some(tls) = SignerClientTls::from_env() {
+            signer_client = signer_client.with_tls(&tls)?;
+        }
+        Some(signer_client)
     } else {
         None
     };
 
     Ok(PbsModuleConfig {
         chain: cb_config.chain,
-        pbs_config: Arc::new(cb_config.pbs.static_config.pbs_config),
+        pbs_config: Arc::new(cb_config.pbs.static_config.pbs_config.with_env_paths()),
         relays: relay_clients,
         signer_client,
         event_publiher: maybe_publiher,
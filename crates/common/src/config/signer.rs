@@ -1,14 +1,29 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+};
 
+use alloy::rpc::types::beacon::BlsPublicKey;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 use super::{
-    constants::{SIGNER_IMAGE, SIGNER_SERVER_ENV},
-    utils::{load_env_var, load_jwts},
-    CommitBoostConfig,
+    constants::{
+        JWTS_FILE_ENV, KEYMANAGER_DIR_KEYS_ENV, KEYMANAGER_DIR_SECRETS_ENV, KEYMANAGER_TOKEN_ENV,
+        PEER_CA_CERT_ENV, PEER_CERT_ENV, PEER_KEY_ENV, SIGNER_IMAGE, SIGNER_SERVER_ENV,
+        SIGNER_SOCKET_DIR_ENV, SIGNER_SOCKET_FILE,
+    },
+    utils::{listen_address, load_env_var, load_jwts, load_threshold_secrets},
+    CommitBoostConfig, TlsConfig,
+};
+use crate::{
+    loader::SignerLoader,
+    signer::{AuditLog, ProxyStore},
+    types::Chain,
+    utils::{default_bool, default_host},
 };
-use crate::{loader::SignerLoader, types::Chain};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SignerConfig {
@@ -17,18 +32,264 @@ pub struct SignerConfig {
     pub docker_image: String,
     /// Which keys to load
     pub loader: SignerLoader,
+    /// Where to persist proxy keys. If not set, proxies are lost on restart
+    pub store: Option<ProxyStore>,
+    /// Where to write the audit log of the signature requests, if at all
+    pub audit: Option<AuditLog>,
+    /// Admin API to import and remove consensus keys at runtime, if enabled
+    pub keymanager: Option<KeymanagerConfig>,
+    /// Distributed validator cluster this signer is part of, if any
+    pub threshold: Option<ThresholdConfig>,
+    /// Serve the Signer API on a Unix socket instead of the signer network
+    pub socket: Option<SignerSocketConfig>,
+    /// Address to listen on, e.g. `::` for IPv6 or `127.0.0.1` for loopback
+    /// only. In Docker, this is the host the signer port is published on
+    #[serde(default = "default_host")]
+    pub host: IpAddr,
+    /// Serve the Signer API over TLS, with mTLS if a client CA is set
+    pub tls: Option<TlsConfig>,
+    /// Protections of the key material in the signer process
+    #[serde(default)]
+    pub hardening: SignerHardeningConfig,
+    /// Named groups of consensus keys, which module policies can refer to
+    #[serde(default)]
+    pub key_groups: HashMap<String, Vec<BlsPublicKey>>,
+    /// Beacon node to fetch proposer duties from, needed by policies with a
+    /// proposal window
+    pub beacon_url: Option<Url>,
+}
+
+/// Keymanager compatible API, to import and remove consensus keys without
+/// restarting the signer. Imported keystores are saved in the Lighthouse
+/// layout, and loaded again on restart:
+/// - `keys_path/<pubkey>/voting-keystore.json`
+/// - `secrets_path/<pubkey>`, the keystore password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeymanagerConfig {
+    /// Address of the admin listener, loopback only by default. In Docker, the
+    /// port is published on localhost, and the listener is also reachable by
+    /// the other containers on the signer network
+    #[serde(default = "default_keymanager_host")]
+    pub host: IpAddr,
+    pub port: u16,
+    pub keys_path: PathBuf,
+    pub secrets_path: PathBuf,
+}
+
+fn default_keymanager_host() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+impl KeymanagerConfig {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        Self {
+            host: self.host,
+            port: self.port,
+            keys_path: std::env::var(KEYMANAGER_DIR_KEYS_ENV)
+                .map(PathBuf::from)
+                .unwrap_or(self.keys_path),
+            secrets_path: std::env::var(KEYMANAGER_DIR_SECRETS_ENV)
+                .map(PathBuf::from)
+                .unwrap_or(self.secrets_path),
+        }
+    }
+}
+
+/// Unix socket the Signer API is served on, at `dir_path/signer.sock`. The
+/// directory is shared with the commit modules, and access to the socket is
+/// controlled by its file permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerSocketConfig {
+    pub dir_path: PathBuf,
+    /// Permissions of the socket file, only the owner and group can connect
+    /// by default
+    #[serde(default = "default_socket_mode")]
+    pub mode: u32,
+}
+
+impl SignerSocketConfig {
+    /// Overrides the path with the one mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        Self {
+            dir_path: std::env::var(SIGNER_SOCKET_DIR_ENV)
+                .map(PathBuf::from)
+                .unwrap_or(self.dir_path),
+            mode: self.mode,
+        }
+    }
+
+    pub fn socket_path(&self) -> PathBuf {
+        self.dir_path.join(SIGNER_SOCKET_FILE)
+    }
+}
+
+fn default_socket_mode() -> u32 {
+    0o660
+}
+
+/// Protections of the key material held in memory by the signer process
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SignerHardeningConfig {
+    /// Lock the process memory with `mlockall`, so that keys are never swapped
+    /// to disk. Needs the `IPC_LOCK` capability, which is added to the signer
+    /// container when set
+    #[serde(default)]
+    pub lock_memory: bool,
+    /// Disable core dumps, and on Linux mark the process as non-dumpable so
+    /// other processes of the same user can't read its memory
+    #[serde(default = "default_bool::<true>")]
+    pub disable_core_dumps: bool,
+}
+
+impl Default for SignerHardeningConfig {
+    fn default() -> Self {
+        Self { lock_memory: false, disable_core_dumps: true }
+    }
+}
+
+/// Distributed validator mode: this signer holds one share of each account of
+/// the cluster, and collects partial signatures from the other nodes to recover
+/// a signature of the whole key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdConfig {
+    /// Index of the shares held by this signer, i.e. the non-zero point at
+    /// which they were evaluated
+    pub share_index: u64,
+    /// Number of partial signatures needed to recover a signature
+    pub threshold: usize,
+    /// Port on which the other nodes request partial signatures. Only that
+    /// endpoint is served on it, the Signer API stays on the signer port
+    pub port: u16,
+    /// Other nodes in the cluster
+    pub peers: Vec<ThresholdPeerConfig>,
+    pub accounts: Vec<ThresholdAccount>,
+    /// Certificates of the connections to the peers. If not set, the peer port
+    /// is served with the signer certificate but without client certificates,
+    /// and the peers are verified with the system roots
+    pub tls: Option<PeerTlsConfig>,
+}
+
+impl ThresholdConfig {
+    /// Overrides the certificate paths with the ones mounted in the container,
+    /// if set
+    pub fn with_env_paths(self) -> Self {
+        Self { tls: self.tls.map(PeerTlsConfig::with_env_paths), ..self }
+    }
+}
+
+/// mTLS between the nodes of a cluster, whose certificates are all signed by
+/// the same CA. The peer port is served with the signer certificate, so the
+/// signer must be served over TLS too
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerTlsConfig {
+    /// CA of the cluster. The peers must present a client certificate signed
+    /// by it, and their server certificates are verified with it
+    pub ca_cert_path: PathBuf,
+    /// Client certificate and key presented to the peers
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl PeerTlsConfig {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        let env_path = |env, default| std::env::var(env).map(PathBuf::from).unwrap_or(default);
+
+        Self {
+            ca_cert_path: env_path(PEER_CA_CERT_ENV, self.ca_cert_path),
+            cert_path: env_path(PEER_CERT_ENV, self.cert_path),
+            key_path: env_path(PEER_KEY_ENV, self.key_path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdPeerConfig {
+    pub share_index: u64,
+    /// Address of the peer listener, i.e. `threshold.port` of the node
+    pub url: Url,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdAccount {
+    /// Composite pubkey of the validator
+    pub pubkey: BlsPublicKey,
+    /// Pubkey of the share loaded in this signer
+    pub share_pubkey: BlsPublicKey,
+    /// Pubkeys of the shares held by the other nodes, in the order of `peers`.
+    /// Partial signatures are checked against them
+    pub peer_share_pubkeys: Vec<BlsPublicKey>,
+}
+
+/// Restrictions on the signatures a module can request. Modules without a
+/// policy can sign with all the keys, at any time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningPolicy {
+    /// Consensus keys the module can sign with, directly or through proxies.
+    /// If neither `pubkeys` nor `key_groups` is set, all keys are allowed
+    #[serde(default)]
+    pub pubkeys: Vec<BlsPublicKey>,
+    /// Groups from `signer.key_groups` the module can sign with
+    #[serde(default)]
+    pub key_groups: Vec<String>,
+    /// Only allow signing with proxy keys, consensus keys are still used to
+    /// delegate to new proxies
+    #[serde(default)]
+    pub proxy_only: bool,
+    /// Max signatures the module can request in a slot, across all keys
+    pub max_signatures_per_slot: Option<u64>,
+    /// Max signatures the module can request in an epoch, across all keys
+    pub max_signatures_per_epoch: Option<u64>,
+    /// UTC hours in which the module can sign, as `[start, end)` ranges which
+    /// can wrap around midnight, e.g. `[[22, 6]]`
+    #[serde(default)]
+    pub allowed_hours: Vec<(u8, u8)>,
+    /// Only allow signing with a key in the slots up to its next proposal,
+    /// e.g. 2 allows the proposal slot and the two before it. Requires
+    /// `signer.beacon_url`
+    pub proposal_window_slots: Option<u64>,
+    /// Schemas of the structured messages the module can sign, e.g.
+    /// `["PreconfCommitment"]`. If set, the module can't request signatures of
+    /// raw object roots
+    #[serde(default)]
+    pub message_schemas: Vec<String>,
+    /// Structured messages with a slot must be for the current slot or at most
+    /// this many slots after it
+    pub max_slot_lookahead: Option<u64>,
 }
 
 fn default_signer() -> String {
     SIGNER_IMAGE.to_string()
 }
 
-#[derive(Debug)]
 pub struct StartSignerConfig {
     pub chain: Chain,
     pub loader: SignerLoader,
+    pub store: Option<ProxyStore>,
+    pub audit: Option<AuditLog>,
+    pub keymanager: Option<KeymanagerConfig>,
+    /// Bearer token of the keymanager API, set if `keymanager` is
+    pub keymanager_token: Option<String>,
+    pub threshold: Option<ThresholdConfig>,
+    /// Secrets shared with each of the other nodes of the cluster, by share
+    /// index, set if `threshold` is
+    pub threshold_secrets: HashMap<u64, String>,
+    pub socket: Option<SignerSocketConfig>,
+    pub server_host: IpAddr,
     pub server_port: u16,
-    pub jwts: HashMap<String, String>,
+    pub tls: Option<TlsConfig>,
+    pub hardening: SignerHardeningConfig,
+    /// Map of module ids to their JWT secrets
+    pub jwts: HashMap<String, Vec<String>>,
+    /// File to reload the JWT secrets from on SIGHUP, if any
+    pub jwts_path: Option<PathBuf>,
+    /// Ids of the modules allowed to sign with the builder domain
+    pub builder_domain_modules: HashSet<String>,
+    /// Policies of the modules which have one
+    pub policies: HashMap<String, SigningPolicy>,
+    pub key_groups: HashMap<String, Vec<BlsPublicKey>>,
+    pub beacon_url: Option<Url>,
 }
 
 impl StartSignerConfig {
@@ -36,13 +297,77 @@ impl StartSignerConfig {
         let config = CommitBoostConfig::from_env_path()?;
 
         let jwts = load_jwts()?;
+        let jwts_path = std::env::var(JWTS_FILE_ENV).ok().map(PathBuf::from);
         let server_port = load_env_var(SIGNER_SERVER_ENV)?.parse()?;
 
+        let signer_config = config.signer.expect("Signer config is missing");
+        let server_host = listen_address(signer_config.host, server_port)?.ip();
+        let keymanager_token = match signer_config.keymanager {
+            Some(_) => Some(load_env_var(KEYMANAGER_TOKEN_ENV)?),
+            None => None,
+        };
+        let threshold_secrets = match signer_config.threshold {
+            Some(_) => load_threshold_secrets()?,
+            None => HashMap::new(),
+        };
+
+        let mut builder_domain_modules = HashSet::new();
+        let mut policies = HashMap::new();
+        for module in config.modules.unwrap_or_default() {
+            if module.allow_builder_domain {
+                builder_domain_modules.insert(module.id.clone());
+            }
+
+            if let Some(policy) = module.policy {
+                policies.insert(module.id, policy);
+            }
+        }
+
         Ok(StartSignerConfig {
             chain: config.chain,
-            loader: config.signer.expect("Signer config is missing").loader,
+            loader: signer_config.loader,
+            store: signer_config.store.map(ProxyStore::with_env_paths),
+            audit: signer_config.audit.map(AuditLog::with_env_paths),
+            keymanager: signer_config.keymanager.map(KeymanagerConfig::with_env_paths),
+            keymanager_token,
+            threshold: signer_config.threshold.map(ThresholdConfig::with_env_paths),
+            threshold_secrets,
+            socket: signer_config.socket.map(SignerSocketConfig::with_env_paths),
+            server_host,
             server_port,
+            tls: signer_config.tls.map(TlsConfig::with_env_paths),
+            hardening: signer_config.hardening,
             jwts,
+            jwts_path,
+            builder_domain_modules,
+            policies,
+            key_groups: signer_config.key_groups,
+            beacon_url: signer_config.beacon_url,
         })
     }
 }
+
+/// Leaves out the JWT secrets, the keymanager token and the threshold secrets
+impl std::fmt::Debug for StartSignerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StartSignerConfig")
+            .field("chain", &self.chain)
+            .field("loader", &self.loader)
+            .field("store", &self.store)
+            .field("audit", &self.audit)
+            .field("keymanager", &self.keymanager)
+            .field("threshold", &self.threshold)
+            .field("socket", &self.socket)
+            .field("server_host", &self.server_host)
+            .field("server_port", &self.server_port)
+            .field("tls", &self.tls)
+            .field("hardening", &self.hardening)
+            .field("modules", &self.jwts.keys().collect::<Vec<_>>())
+            .field("jwts_path", &self.jwts_path)
+            .field("builder_domain_modules", &self.builder_domain_modules)
+            .field("policies", &self.policies)
+            .field("key_groups", &self.key_groups)
+            .field("beacon_url", &self.beacon_url)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use eyre::{ensure, eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use tokio_rustls::rustls::{
+    crypto::ring::default_provider,
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+
+use super::constants::{TLS_CERT_ENV, TLS_CLIENT_CA_CERT_ENV, TLS_KEY_ENV};
+
+/// Serves a listener over TLS, with the certificates as PEM files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// CA of the server certificate, trusted by the commit modules to connect
+    /// to the signer. If not set, modules verify the signer with the system
+    /// roots
+    pub ca_cert_path: Option<PathBuf>,
+    /// If set, clients must present a certificate signed by this CA (mTLS)
+    pub client_ca_cert_path: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        let env_path = |env, default| std::env::var(env).map(PathBuf::from).unwrap_or(default);
+
+        Self {
+            cert_path: env_path(TLS_CERT_ENV, self.cert_path),
+            key_path: env_path(TLS_KEY_ENV, self.key_path),
+            ca_cert_path: self.ca_cert_path,
+            client_ca_cert_path: self
+                .client_ca_cert_path
+                .map(|path| env_path(TLS_CLIENT_CA_CERT_ENV, path)),
+        }
+    }
+
+    /// Loads the certificates, failing if any of them is missing or invalid
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>> {
+        let provider = Arc::new(default_provider());
+        let certs = load_certs(&self.cert_path)?;
+        let key = load_key(&self.key_path)?;
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?;
+        let builder = match &self.client_ca_cert_path {
+            Some(path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in load_certs(path)? {
+                    roots.add(cert)?;
+                }
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(roots.into(), provider).build()?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut config = builder.with_single_cert(certs, key)?;
+        // connections are only served with http1
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Ok(Arc::new(config))
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path).wrap_err(format!("failed reading {}", path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file)).collect::<Result<Vec<_>, _>>()?;
+    ensure!(!certs.is_empty(), "no certificate in {}", path.display());
+
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    let file = File::open(path).wrap_err(format!("failed reading {}", path.display()))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))?
+        .ok_or_else(|| eyre!("no private key in {}", path.display()))
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+};
 
 use eyre::{Context, Result};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize};
 
-use super::constants::JWTS_ENV;
+use super::constants::{JWTS_ENV, JWTS_FILE_ENV, LISTEN_HOST_ENV, THRESHOLD_SECRETS_ENV};
 
 pub fn load_env_var(env: &str) -> Result<String> {
     std::env::var(env).wrap_err("{env} is not set")
@@ -20,8 +24,62 @@ pub fn load_file_from_env<T: DeserializeOwned>(env: &str) -> Result<T> {
     load_from_file(&path)
 }
 
-/// Loads a map of module id -> jwt token from a json env
-pub fn load_jwts() -> Result<HashMap<String, String>> {
+/// Address a service listens on, with the host in [LISTEN_HOST_ENV] if set.
+/// In Docker the configured host is the one the port is published on, and
+/// services listen on all the interfaces of their container. Fails if the
+/// env var is set but is not an ip address
+pub fn listen_address(host: IpAddr, port: u16) -> Result<SocketAddr> {
+    let host = match std::env::var(LISTEN_HOST_ENV) {
+        Ok(env) => env.parse().wrap_err(format!("invalid {LISTEN_HOST_ENV}: {env}"))?,
+        Err(_) => host,
+    };
+
+    Ok(SocketAddr::new(host, port))
+}
+
+/// Loads the secrets shared with each peer of a distributed validator
+/// cluster, from the json map of share index -> secret in
+/// [THRESHOLD_SECRETS_ENV]
+pub fn load_threshold_secrets() -> Result<HashMap<u64, String>> {
+    let secrets = load_env_var(THRESHOLD_SECRETS_ENV)?;
+    serde_json::from_str(&secrets).wrap_err("could not deserialize json from string")
+}
+
+/// Loads a map of module id -> jwt secrets from the json file in
+/// [JWTS_FILE_ENV] if set, or from the json in [JWTS_ENV]
+pub fn load_jwts() -> Result<HashMap<String, Vec<String>>> {
+    if let Ok(path) = std::env::var(JWTS_FILE_ENV) {
+        return load_jwts_file(Path::new(&path));
+    }
+
     let jwts = std::env::var(JWTS_ENV).wrap_err(format!("{JWTS_ENV} is not set"))?;
-    serde_json::from_str(&jwts).wrap_err("could not deserialize json from string")
+    parse_jwts(&jwts)
+}
+
+pub fn load_jwts_file(path: &Path) -> Result<HashMap<String, Vec<String>>> {
+    let jwts = std::fs::read_to_string(path)
+        .wrap_err(format!("Unable to read jwts file: {}", path.display()))?;
+    parse_jwts(&jwts)
+}
+
+/// Each module has either a single secret, or a list of secrets while they are
+/// rotated
+fn parse_jwts(jwts: &str) -> Result<HashMap<String, Vec<String>>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Secrets {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let jwts: HashMap<String, Secrets> =
+        serde_json::from_str(jwts).wrap_err("could not deserialize json from string")?;
+
+    Ok(jwts
+        .into_iter()
+        .map(|(id, secrets)| match secrets {
+            Secrets::One(secret) => (id, vec![secret]),
+            Secrets::Many(secrets) => (id, secrets),
+        })
+        .collect())
 }
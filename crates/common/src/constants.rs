@@ -1,6 +1,8 @@
 // TODO: replace with full chain spec, allow loading from file
 
 pub const APPLICATION_BUILDER_DOMAIN: [u8; 4] = [0, 0, 0, 1];
+/// Domain type of the signatures requested by commit modules, "Comm" reversed
+pub const COMMIT_BOOST_DOMAIN: [u8; 4] = [0x6d, 0x6d, 0x6f, 0x43];
 pub const GENESIS_VALIDATORS_ROOT: [u8; 32] = [0; 32];
 
 // MAINNET
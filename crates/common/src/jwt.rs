@@ -0,0 +1,91 @@
+use eyre::{bail, ensure, WrapErr};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::utcnow_sec;
+
+/// How long the JWTs created by modules are valid for
+pub const JWT_EXPIRY_SECONDS: u64 = 300;
+
+/// Claims of the JWTs sent by modules to the Signer API, signed with HS256
+/// with the module secret
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    pub module: String,
+    /// Unix timestamp in seconds after which the JWT is rejected
+    pub exp: u64,
+}
+
+/// Creates a JWT for the module, valid for [JWT_EXPIRY_SECONDS]
+pub fn create_jwt(module_id: &str, secret: &str) -> jsonwebtoken::errors::Result<(String, u64)> {
+    let claims = JwtClaims { module: module_id.to_owned(), exp: utcnow_sec() + JWT_EXPIRY_SECONDS };
+    let jwt = encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )?;
+
+    Ok((jwt, claims.exp))
+}
+
+/// Reads the module id from the JWT, without validating it
+pub fn decode_jwt_module(jwt: &str) -> eyre::Result<String> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+
+    let claims = decode::<JwtClaims>(jwt, &DecodingKey::from_secret(&[]), &validation)
+        .wrap_err("invalid jwt")?
+        .claims;
+
+    Ok(claims.module)
+}
+
+/// Checks that the JWT was issued for the module, is not expired, and is signed
+/// with one of its secrets. Modules can have more than one secret while they
+/// are rotated
+pub fn validate_jwt(jwt: &str, module_id: &str, secrets: &[String]) -> eyre::Result<()> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.leeway = 0;
+
+    for secret in secrets {
+        let Ok(data) =
+            decode::<JwtClaims>(jwt, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        else {
+            continue;
+        };
+
+        ensure!(data.claims.module == module_id, "jwt issued for module {}", data.claims.module);
+        return Ok(());
+    }
+
+    bail!("jwt not signed with a valid secret, or expired")
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    use super::{create_jwt, decode_jwt_module, validate_jwt, JwtClaims};
+    use crate::utils::utcnow_sec;
+
+    #[test]
+    fn test_validate_jwt() {
+        let secrets = vec!["new_secret".to_owned(), "old_secret".to_owned()];
+
+        let (jwt, _) = create_jwt("MODULE", "old_secret").unwrap();
+        assert_eq!(decode_jwt_module(&jwt).unwrap(), "MODULE");
+        assert!(validate_jwt(&jwt, "MODULE", &secrets).is_ok());
+        assert!(validate_jwt(&jwt, "OTHER", &secrets).is_err());
+        assert!(validate_jwt(&jwt, "MODULE", &secrets[..1]).is_err());
+
+        // tokens which merely contain a valid jwt or secret are rejected
+        assert!(validate_jwt(&format!("{jwt}x"), "MODULE", &secrets).is_err());
+        assert!(validate_jwt("old_secret", "MODULE", &secrets).is_err());
+
+        let expired = JwtClaims { module: "MODULE".to_owned(), exp: utcnow_sec() - 1 };
+        let jwt =
+            encode(&Header::default(), &expired, &EncodingKey::from_secret(b"new_secret")).unwrap();
+        assert!(validate_jwt(&jwt, "MODULE", &secrets).is_err());
+    }
+}
@@ -4,8 +4,10 @@ pub mod commit;
 pub mod config;
 pub mod constants;
 pub mod error;
+pub mod jwt;
 pub mod loader;
 pub mod pbs;
+pub mod server;
 pub mod signature;
 pub mod signer;
 pub mod types;
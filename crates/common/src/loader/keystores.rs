@@ -0,0 +1,581 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use alloy::{primitives::hex::FromHex, rpc::types::beacon::BlsPublicKey};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use eth2_keystore::{json_keystore::Crypto, Keystore};
+use eyre::{bail, ensure, eyre, OptionExt, WrapErr};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use zeroize::Zeroizing;
+
+use crate::{
+    signer::Signer,
+    utils::{create_private_dir_all, write_private_file},
+};
+
+const VALIDATOR_DEFINITIONS_FILE: &str = "validator_definitions.yml";
+const VOTING_KEYSTORE_FILE: &str = "voting-keystore.json";
+const NIMBUS_KEYSTORE_FILE: &str = "keystore.json";
+const PRYSM_KEYSTORE_FILE: &str = "direct/accounts/all-accounts.keystore.json";
+
+/// On-disk layout of the validator keys, `keys_path` and `secrets_path` are the
+/// ones in the loader config:
+/// - `lighthouse`: `keys_path/<pubkey>/voting-keystore.json` and
+///   `secrets_path/<pubkey>`, or the keys in
+///   `keys_path/validator_definitions.yml` if present
+/// - `teku`: `keys_path/<name>.json` and `secrets_path/<name>.txt`
+/// - `prysm`: `keys_path/direct/accounts/all-accounts.keystore.json`, with
+///   `secrets_path` the wallet password file
+/// - `lodestar`: `keys_path/<pubkey>/voting-keystore.json` and
+///   `secrets_path/<pubkey>`
+/// - `nimbus`: `keys_path/<pubkey>/keystore.json` and `secrets_path/<pubkey>`
+///
+/// Except for Prysm, `secrets_path` can also be a single file, with the
+/// password of all the keystores
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidatorKeysFormat {
+    #[default]
+    Lighthouse,
+    Teku,
+    Prysm,
+    Lodestar,
+    Nimbus,
+}
+
+/// Loads all the keys in the directory. Keys which fail to load are reported
+/// one by one and skipped, but at least one key must load if any is found
+pub fn load_validators_dir(
+    keys_path: &Path,
+    secrets_path: &Path,
+    format: ValidatorKeysFormat,
+) -> eyre::Result<Vec<Signer>> {
+    let results = load_entries(keys_path, secrets_path, format)?;
+
+    let mut signers = Vec::with_capacity(results.len());
+    let mut failed = 0;
+
+    for (key, res) in results {
+        match res {
+            Ok(signer) => signers.push(signer),
+            Err(err) => {
+                error!(key, ?err, "Failed to load validator key");
+                failed += 1;
+            }
+        }
+    }
+
+    info!(?format, loaded = signers.len(), failed, "Loaded validator keys");
+
+    if signers.is_empty() {
+        if failed > 0 {
+            bail!("failed to load all {failed} validator keys");
+        }
+        warn!(keys_path = %keys_path.display(), "No validator keys found");
+    }
+
+    Ok(signers)
+}
+
+/// Result of loading each key found, with the key path for reporting
+fn load_entries(
+    keys_path: &Path,
+    secrets_path: &Path,
+    format: ValidatorKeysFormat,
+) -> eyre::Result<Vec<(String, eyre::Result<Signer>)>> {
+    ensure!(keys_path.is_dir(), "keys directory {} not found", keys_path.display());
+    let passwords = Passwords::new(secrets_path)?;
+
+    match format {
+        ValidatorKeysFormat::Lighthouse => {
+            let definitions = keys_path.join(VALIDATOR_DEFINITIONS_FILE);
+            if definitions.is_file() {
+                load_definitions(&definitions, keys_path, secrets_path, &passwords)
+            } else {
+                load_pubkey_dirs(keys_path, VOTING_KEYSTORE_FILE, &passwords)
+            }
+        }
+        ValidatorKeysFormat::Lodestar => {
+            load_pubkey_dirs(keys_path, VOTING_KEYSTORE_FILE, &passwords)
+        }
+        ValidatorKeysFormat::Nimbus => {
+            load_pubkey_dirs(keys_path, NIMBUS_KEYSTORE_FILE, &passwords)
+        }
+        ValidatorKeysFormat::Teku => load_teku(keys_path, &passwords),
+        ValidatorKeysFormat::Prysm => load_prysm(keys_path, &passwords),
+    }
+}
+
+/// Passwords are either one file per key in a directory, or a single file
+/// shared by all keys
+enum Passwords {
+    Dir(PathBuf),
+    Shared(Zeroizing<Vec<u8>>),
+}
+
+impl Passwords {
+    fn new(secrets_path: &Path) -> eyre::Result<Self> {
+        if secrets_path.is_file() {
+            Ok(Self::Shared(read_password(secrets_path)?))
+        } else if secrets_path.is_dir() {
+            Ok(Self::Dir(secrets_path.to_path_buf()))
+        } else {
+            bail!("secrets path {} not found", secrets_path.display())
+        }
+    }
+
+    fn get(&self, name: &str) -> eyre::Result<Zeroizing<Vec<u8>>> {
+        match self {
+            Self::Dir(dir) => read_password(&dir.join(name)),
+            Self::Shared(password) => Ok(password.clone()),
+        }
+    }
+}
+
+/// Reads a password file, without trailing newlines
+fn read_password(path: &Path) -> eyre::Result<Zeroizing<Vec<u8>>> {
+    let mut password = Zeroizing::new(
+        fs::read(path).wrap_err_with(|| format!("failed reading password {}", path.display()))?,
+    );
+    while matches!(password.last(), Some(b'\n' | b'\r')) {
+        password.pop();
+    }
+
+    Ok(password)
+}
+
+fn load_keystore(path: &Path, password: &[u8]) -> eyre::Result<Signer> {
+    let keystore =
+        Keystore::from_json_file(path).map_err(|err| eyre!("failed reading keystore: {err:?}"))?;
+    decrypt_keystore(&keystore, password)
+}
+
+fn decrypt_keystore(keystore: &Keystore, password: &[u8]) -> eyre::Result<Signer> {
+    let keypair = keystore
+        .decrypt_keypair(password)
+        .map_err(|err| eyre!("failed decrypting keystore: {err:?}"))?;
+
+    Signer::new_from_bytes(keypair.sk.serialize().as_bytes())
+}
+
+/// Decrypts an EIP-2335 keystore, e.g. one imported via the keymanager API
+pub fn decrypt_keystore_json(keystore: &str, password: &[u8]) -> eyre::Result<Signer> {
+    let keystore = Keystore::from_json_str(keystore)
+        .map_err(|err| eyre!("failed reading keystore: {err:?}"))?;
+    decrypt_keystore(&keystore, password)
+}
+
+/// Saves a keystore in the Lighthouse layout, so that it's loaded again with
+/// [ValidatorKeysFormat::Lighthouse]. The files are only readable by the owner
+pub fn save_keystore(
+    keys_path: &Path,
+    secrets_path: &Path,
+    pubkey: &BlsPublicKey,
+    keystore: &str,
+    password: &str,
+) -> eyre::Result<()> {
+    let name = pubkey.to_string();
+
+    create_private_dir_all(secrets_path)?;
+    write_private_file(secrets_path.join(&name), password)?;
+
+    let keys_dir = keys_path.join(&name);
+    create_private_dir_all(&keys_dir)?;
+    write_private_file(keys_dir.join(VOTING_KEYSTORE_FILE), keystore)?;
+
+    Ok(())
+}
+
+/// Whether a keystore for the pubkey was saved with [save_keystore]
+pub fn has_keystore(keys_path: &Path, pubkey: &BlsPublicKey) -> bool {
+    keys_path.join(pubkey.to_string()).join(VOTING_KEYSTORE_FILE).is_file()
+}
+
+/// Removes a keystore saved with [save_keystore], and its password
+pub fn remove_keystore(
+    keys_path: &Path,
+    secrets_path: &Path,
+    pubkey: &BlsPublicKey,
+) -> eyre::Result<()> {
+    let name = pubkey.to_string();
+
+    fs::remove_dir_all(keys_path.join(&name))?;
+    let password_path = secrets_path.join(&name);
+    if password_path.exists() {
+        fs::remove_file(password_path)?;
+    }
+
+    Ok(())
+}
+
+/// One directory per key, named after the pubkey, with the password file
+/// named after the pubkey too
+fn load_pubkey_dirs(
+    keys_path: &Path,
+    keystore_file: &str,
+    passwords: &Passwords,
+) -> eyre::Result<Vec<(String, eyre::Result<Signer>)>> {
+    let mut results = Vec::new();
+
+    for entry in fs::read_dir(keys_path)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Ok(pubkey) = BlsPublicKey::from_hex(name) else {
+            continue;
+        };
+        if !path.is_dir() {
+            continue;
+        }
+
+        let keystore_path = path.join(keystore_file);
+        let res = passwords.get(name).and_then(|password| {
+            let signer = load_keystore(&keystore_path, &password)?;
+            ensure!(signer.pubkey() == pubkey, "keystore pubkey doesn't match directory name");
+            Ok(signer)
+        });
+
+        results.push((keystore_path.display().to_string(), res));
+    }
+
+    Ok(results)
+}
+
+fn load_teku(
+    keys_path: &Path,
+    passwords: &Passwords,
+) -> eyre::Result<Vec<(String, eyre::Result<Signer>)>> {
+    let mut results = Vec::new();
+
+    for entry in fs::read_dir(keys_path)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let res = passwords
+            .get(&format!("{name}.txt"))
+            .and_then(|password| load_keystore(&path, &password));
+
+        results.push((path.display().to_string(), res));
+    }
+
+    Ok(results)
+}
+
+/// Entry of a Lighthouse `validator_definitions.yml`, only local keystores are
+/// loaded
+#[derive(Deserialize)]
+struct ValidatorDefinition {
+    enabled: bool,
+    voting_public_key: BlsPublicKey,
+    #[serde(rename = "type")]
+    kind: String,
+    voting_keystore_path: Option<PathBuf>,
+    voting_keystore_password_path: Option<PathBuf>,
+    voting_keystore_password: Option<Zeroizing<String>>,
+}
+
+/// Paths in the definitions are absolute paths on the host, so when they
+/// don't exist (e.g. in the container) they are looked up in the mounted
+/// directories instead
+fn load_definitions(
+    definitions_path: &Path,
+    keys_path: &Path,
+    secrets_path: &Path,
+    passwords: &Passwords,
+) -> eyre::Result<Vec<(String, eyre::Result<Signer>)>> {
+    let definitions: Vec<ValidatorDefinition> =
+        serde_yaml::from_reader(File::open(definitions_path)?)
+            .wrap_err("failed parsing validator definitions")?;
+
+    let mut results = Vec::new();
+
+    for definition in definitions {
+        let pubkey = definition.voting_public_key.to_string();
+        if !definition.enabled || definition.kind != "local_keystore" {
+            info!(pubkey, kind = definition.kind, "Skipping validator definition");
+            continue;
+        }
+
+        let res = load_definition(definition, keys_path, secrets_path, passwords);
+        results.push((pubkey, res));
+    }
+
+    Ok(results)
+}
+
+fn load_definition(
+    definition: ValidatorDefinition,
+    keys_path: &Path,
+    secrets_path: &Path,
+    passwords: &Passwords,
+) -> eyre::Result<Signer> {
+    let keystore_path =
+        definition.voting_keystore_path.ok_or_eyre("missing voting_keystore_path")?;
+    let keystore_path = rebase(keystore_path, keys_path, 2);
+
+    let password =
+        match (definition.voting_keystore_password, definition.voting_keystore_password_path) {
+            (Some(password), _) => Zeroizing::new(password.as_bytes().to_vec()),
+            (None, Some(password_path)) => read_password(&rebase(password_path, secrets_path, 1))?,
+            (None, None) => passwords.get(&definition.voting_public_key.to_string())?,
+        };
+
+    let signer = load_keystore(&keystore_path, &password)?;
+    ensure!(
+        signer.pubkey() == definition.voting_public_key,
+        "keystore pubkey doesn't match definition"
+    );
+
+    Ok(signer)
+}
+
+/// If the path doesn't exist, keep its last `components` under `base`
+fn rebase(path: PathBuf, base: &Path, components: usize) -> PathBuf {
+    if path.exists() {
+        return path;
+    }
+
+    let tail: Vec<_> = path.components().rev().take(components).collect();
+    tail.into_iter().rev().fold(base.to_path_buf(), |acc, component| acc.join(component))
+}
+
+#[derive(Debug, Deserialize)]
+struct PrysmKeystore {
+    crypto: Crypto,
+}
+
+/// Decrypted content of a Prysm wallet, keys are base64 encoded
+#[derive(Deserialize)]
+struct PrysmAccounts {
+    private_keys: Zeroizing<Vec<String>>,
+    public_keys: Vec<String>,
+}
+
+fn load_prysm(
+    wallet_path: &Path,
+    passwords: &Passwords,
+) -> eyre::Result<Vec<(String, eyre::Result<Signer>)>> {
+    let Passwords::Shared(password) = passwords else {
+        bail!("Prysm wallets need secrets_path to be the wallet password file");
+    };
+
+    let keystore_path = wallet_path.join(PRYSM_KEYSTORE_FILE);
+    let keystore: PrysmKeystore = serde_json::from_reader(File::open(&keystore_path)?)
+        .wrap_err("failed reading Prysm keystore")?;
+    let plain_text = eth2_keystore::decrypt(password, &keystore.crypto)
+        .map_err(|err| eyre!("failed decrypting Prysm keystore: {err:?}"))?;
+    let accounts: PrysmAccounts = serde_json::from_slice(plain_text.as_bytes())?;
+
+    ensure!(
+        accounts.private_keys.len() == accounts.public_keys.len(),
+        "mismatched number of private and public keys"
+    );
+
+    let results = accounts
+        .private_keys
+        .iter()
+        .zip(accounts.public_keys.iter())
+        .map(|(private_key, public_key)| {
+            let res = load_prysm_key(private_key, public_key);
+            (format!("{}:{public_key}", keystore_path.display()), res)
+        })
+        .collect();
+
+    Ok(results)
+}
+
+fn load_prysm_key(private_key: &str, public_key: &str) -> eyre::Result<Signer> {
+    let signer = Signer::new_from_bytes(&Zeroizing::new(STANDARD.decode(private_key)?))?;
+    let pubkey = BlsPublicKey::try_from(STANDARD.decode(public_key)?.as_slice())?;
+    ensure!(signer.pubkey() == pubkey, "private key doesn't match public key");
+
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use alloy::{primitives::hex, rpc::types::beacon::BlsPublicKey};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use eth2_keystore::{
+        json_keystore::{Aes128Ctr, Cipher, Kdf, Pbkdf2, Prf},
+        keypair_from_secret, KeystoreBuilder,
+    };
+
+    use super::{load_entries, ValidatorKeysFormat};
+    use crate::{signature::random_secret, utils::blst_pubkey_to_alloy};
+
+    const PASSWORD: &str = "password";
+
+    /// Cheap kdf, to keep tests fast
+    fn kdf() -> Kdf {
+        Kdf::Pbkdf2(Pbkdf2 { dklen: 32, c: 2, prf: Prf::HmacSha256, salt: vec![1; 32].into() })
+    }
+
+    /// Writes a keystore for a new random key, returns its pubkey
+    fn write_keystore(path: &Path) -> BlsPublicKey {
+        let secret = random_secret().unwrap();
+        let keypair = keypair_from_secret(&secret.to_bytes()).unwrap();
+        let keystore = KeystoreBuilder::new(&keypair, PASSWORD.as_bytes(), String::new())
+            .unwrap()
+            .kdf(kdf())
+            .build()
+            .unwrap();
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        keystore.to_json_writer(fs::File::create(path).unwrap()).unwrap();
+
+        blst_pubkey_to_alloy(&secret.sk_to_pk())
+    }
+
+    fn loaded_pubkeys(
+        keys_path: &Path,
+        secrets_path: &Path,
+        format: ValidatorKeysFormat,
+    ) -> (Vec<BlsPublicKey>, usize) {
+        let results = load_entries(keys_path, secrets_path, format).unwrap();
+        let failed = results.iter().filter(|(_, res)| res.is_err()).count();
+        let mut pubkeys: Vec<_> =
+            results.into_iter().filter_map(|(_, res)| res.ok()).map(|s| s.pubkey()).collect();
+        pubkeys.sort();
+
+        (pubkeys, failed)
+    }
+
+    #[test]
+    fn test_load_validators_dir() {
+        let dir = std::env::temp_dir().join(format!("cb_keystores_{}", std::process::id()));
+        let keys = dir.join("keys");
+        let secrets = dir.join("secrets");
+        fs::create_dir_all(&secrets).unwrap();
+
+        // lighthouse and lodestar layout, one password file per key, one key with a
+        // wrong password
+        let mut expected: Vec<_> = (0..2)
+            .map(|_| {
+                let pubkey = write_keystore(&dir.join("tmp.json"));
+                let key_dir = keys.join(pubkey.to_string());
+                fs::create_dir_all(&key_dir).unwrap();
+                fs::rename(dir.join("tmp.json"), key_dir.join("voting-keystore.json")).unwrap();
+                fs::write(secrets.join(pubkey.to_string()), format!("{PASSWORD}\n")).unwrap();
+                pubkey
+            })
+            .collect();
+        expected.sort();
+
+        let wrong = write_keystore(&dir.join("tmp.json"));
+        fs::create_dir_all(keys.join(wrong.to_string())).unwrap();
+        fs::rename(dir.join("tmp.json"), keys.join(wrong.to_string()).join("voting-keystore.json"))
+            .unwrap();
+        fs::write(secrets.join(wrong.to_string()), "wrong").unwrap();
+
+        for format in [ValidatorKeysFormat::Lighthouse, ValidatorKeysFormat::Lodestar] {
+            assert_eq!(loaded_pubkeys(&keys, &secrets, format), (expected.clone(), 1));
+        }
+
+        // shared password
+        let shared = dir.join("password.txt");
+        fs::write(&shared, PASSWORD).unwrap();
+        let (pubkeys, failed) = loaded_pubkeys(&keys, &shared, ValidatorKeysFormat::Lighthouse);
+        assert_eq!((pubkeys.len(), failed), (3, 0));
+
+        // lighthouse validator definitions, with host paths
+        let definitions = format!(
+            "---\n- enabled: true\n  voting_public_key: \"{0}\"\n  type: local_keystore\n  \
+             voting_keystore_path: /host/validators/{0}/voting-keystore.json\n  \
+             voting_keystore_password_path: /host/secrets/{0}\n- enabled: false\n  \
+             voting_public_key: \"{1}\"\n  type: local_keystore\n  voting_keystore_path: \
+             /host/validators/{1}/voting-keystore.json\n  voting_keystore_password: \"{2}\"\n",
+            expected[0], expected[1], PASSWORD
+        );
+        fs::write(keys.join("validator_definitions.yml"), definitions).unwrap();
+        assert_eq!(
+            loaded_pubkeys(&keys, &secrets, ValidatorKeysFormat::Lighthouse),
+            (vec![expected[0]], 0)
+        );
+
+        // nimbus
+        let nimbus_keys = dir.join("nimbus");
+        let pubkey = write_keystore(&dir.join("tmp.json"));
+        fs::create_dir_all(nimbus_keys.join(pubkey.to_string())).unwrap();
+        fs::rename(
+            dir.join("tmp.json"),
+            nimbus_keys.join(pubkey.to_string()).join("keystore.json"),
+        )
+        .unwrap();
+        assert_eq!(
+            loaded_pubkeys(&nimbus_keys, &shared, ValidatorKeysFormat::Nimbus),
+            (vec![pubkey], 0)
+        );
+
+        // teku
+        let teku_keys = dir.join("teku_keys");
+        let teku_secrets = dir.join("teku_secrets");
+        fs::create_dir_all(&teku_secrets).unwrap();
+        let pubkey = write_keystore(&teku_keys.join("validator_1.json"));
+        fs::write(teku_secrets.join("validator_1.txt"), PASSWORD).unwrap();
+        assert_eq!(
+            loaded_pubkeys(&teku_keys, &teku_secrets, ValidatorKeysFormat::Teku),
+            (vec![pubkey], 0)
+        );
+
+        // prysm
+        let wallet = dir.join("wallet");
+        let secret = random_secret().unwrap();
+        let pubkey = blst_pubkey_to_alloy(&secret.sk_to_pk());
+        let accounts = serde_json::json!({
+            "private_keys": [STANDARD.encode(secret.to_bytes())],
+            "public_keys": [STANDARD.encode(pubkey)],
+        });
+        let iv = vec![2; 16];
+        let (cipher_text, checksum) = eth2_keystore::encrypt(
+            accounts.to_string().as_bytes(),
+            PASSWORD.as_bytes(),
+            &kdf(),
+            &Cipher::Aes128Ctr(Aes128Ctr { iv: iv.clone().into() }),
+        )
+        .unwrap();
+        let keystore = serde_json::json!({
+            "crypto": {
+                "kdf": {
+                    "function": "pbkdf2",
+                    "params": {
+                        "dklen": 32,
+                        "c": 2,
+                        "prf": "hmac-sha256",
+                        "salt": hex::encode([1; 32])
+                    },
+                    "message": ""
+                },
+                "checksum": {
+                    "function": "sha256",
+                    "params": {},
+                    "message": hex::encode(checksum)
+                },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": { "iv": hex::encode(iv) },
+                    "message": hex::encode(cipher_text)
+                }
+            },
+            "version": 4
+        });
+        let keystore_path = wallet.join("direct/accounts/all-accounts.keystore.json");
+        fs::create_dir_all(keystore_path.parent().unwrap()).unwrap();
+        fs::write(&keystore_path, keystore.to_string()).unwrap();
+        assert_eq!(loaded_pubkeys(&wallet, &shared, ValidatorKeysFormat::Prysm), (vec![pubkey], 0));
+        assert!(load_entries(&wallet, &secrets, ValidatorKeysFormat::Prysm).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::WrapErr;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use url::Url;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    config::{
+        load_env_var, PKCS11_PIN_ENV, SIGNER_DIR_KEYS_ENV, SIGNER_DIR_SECRETS_ENV, SIGNER_KEYS_ENV,
+    },
+    signer::{
+        load_pkcs11_signers, DirkClient, DirkTlsConfig, Pkcs11Keys, RemoteSigner, Signer,
+        Web3SignerClient,
+    },
+};
+
+mod keystores;
+
+pub use keystores::{
+    decrypt_keystore_json, has_keystore, load_validators_dir, remove_keystore, save_keystore,
+    ValidatorKeysFormat,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SignerLoader {
+    /// Plain text, do not use in prod
+    File { key_path: String },
+    /// Keystores in the layout of one of the consensus clients
+    ValidatorsDir {
+        keys_path: String,
+        /// Directory of password files, or a single password file for all keys
+        secrets_path: String,
+        #[serde(default)]
+        format: ValidatorKeysFormat,
+    },
+    /// Keys held in a PKCS#11 token, e.g. an HSM. The user PIN is read from
+    /// `CB_PKCS11_PIN`
+    Pkcs11 {
+        /// PKCS#11 module of the token, e.g. `/usr/lib/softhsm/libsofthsm2.so`
+        module_path: PathBuf,
+        token_label: String,
+        keys: Pkcs11Keys,
+    },
+    /// Dirk remote signer, keys never leave Dirk
+    Dirk {
+        url: Url,
+        /// Wallets or accounts to sign with, e.g. `wallet` or `wallet/account`
+        accounts: Vec<String>,
+        tls: Option<DirkTlsConfig>,
+    },
+    /// Web3Signer, keys never leave it. Only validator registrations can be
+    /// signed, since Web3Signer only signs typed messages
+    Remote { url: Url },
+}
+
+impl SignerLoader {
+    /// Loads the keys from the paths mounted in the container if set, otherwise
+    /// from the paths in the config
+    pub async fn load_keys(self) -> eyre::Result<Vec<Signer>> {
+        Ok(match self {
+            SignerLoader::File { key_path } => {
+                let path = std::env::var(SIGNER_KEYS_ENV).unwrap_or(key_path);
+                let file = Zeroizing::new(
+                    fs::read_to_string(&path)
+                        .wrap_err_with(|| format!("unable to read keys file {path}"))?,
+                );
+
+                let keys: Vec<FileKey> = serde_json::from_str(&file)?;
+
+                keys.iter()
+                    .map(|k| Signer::new_from_bytes(&k.secret_key))
+                    .collect::<eyre::Result<Vec<Signer>>>()?
+            }
+            SignerLoader::ValidatorsDir { keys_path, secrets_path, format } => {
+                let keys_path = std::env::var(SIGNER_DIR_KEYS_ENV).unwrap_or(keys_path);
+                let secrets_path = std::env::var(SIGNER_DIR_SECRETS_ENV).unwrap_or(secrets_path);
+                load_validators_dir(Path::new(&keys_path), Path::new(&secrets_path), format)?
+            }
+            SignerLoader::Pkcs11 { module_path, token_label, keys } => {
+                let pin = Zeroizing::new(load_env_var(PKCS11_PIN_ENV)?);
+                load_pkcs11_signers(&module_path, &token_label, &pin, keys.with_env_paths())?
+            }
+            SignerLoader::Dirk { url, accounts, tls } => {
+                load_dirk_keys(url, accounts, tls.map(DirkTlsConfig::with_env_paths)).await?
+            }
+            SignerLoader::Remote { url } => load_remote_keys(url).await?,
+        })
+    }
+}
+
+/// Plain text secret key, wiped from memory once the signer is created
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct FileKey {
+    pub secret_key: [u8; 32],
+}
+
+impl<'de> Deserialize<'de> for FileKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = Zeroizing::new(String::deserialize(deserializer)?);
+        let bytes = Zeroizing::new(
+            alloy::primitives::hex::decode(s.trim_start_matches("0x"))
+                .map_err(de::Error::custom)?,
+        );
+        if bytes.len() != 32 {
+            return Err(de::Error::custom("wrong lenght"));
+        }
+
+        let mut key = FileKey { secret_key: [0; 32] };
+        key.secret_key.copy_from_slice(&bytes);
+        Ok(key)
+    }
+}
+
+/// Discovers the accounts available in Dirk, including distributed ones
+async fn load_dirk_keys(
+    url: Url,
+    accounts: Vec<String>,
+    tls: Option<DirkTlsConfig>,
+) -> eyre::Result<Vec<Signer>> {
+    let client = DirkClient::new(url, tls)?;
+    let signers = client.get_accounts(accounts).await?;
+
+    Ok(signers.into_iter().map(Signer::Dirk).collect())
+}
+
+/// Discovers the keys available in Web3Signer
+async fn load_remote_keys(url: Url) -> eyre::Result<Vec<Signer>> {
+    let client = Web3SignerClient::new(url)?;
+    let pubkeys = client.get_pubkeys().await?;
+
+    Ok(pubkeys
+        .into_iter()
+        .map(|pubkey| Signer::Remote(RemoteSigner { pubkey, client: client.clone() }))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FileKey;
+
+    #[test]
+    fn test_decode() {
+        let s = [
+            0, 136, 227, 100, 165, 57, 106, 129, 181, 15, 235, 189, 200, 120, 70, 99, 251, 144,
+            137, 181, 230, 124, 189, 193, 115, 153, 26, 0, 197, 135, 103, 63,
+        ];
+
+        let d = r#"[
+    "0088e364a5396a81b50febbdc8784663fb9089b5e67cbdc173991a00c587673f",
+    "0088e364a5396a81b50febbdc8784663fb9089b5e67cbdc173991a00c587673f"
+]"#;
+        let decoded: Vec<FileKey> = serde_json::from_str(d).unwrap();
+
+        assert_eq!(decoded[0].secret_key, s)
+    }
+}
@@ -0,0 +1,101 @@
+//! Serving axum apps on listeners which `axum::serve` doesn't support, i.e.
+//! TLS and Unix sockets
+
+use std::{sync::Arc, time::Duration};
+
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto::Builder as ConnectionBuilder,
+    service::TowerToHyperService,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+    time::{sleep, timeout},
+};
+use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
+use tracing::{debug, error};
+
+/// Time a client has to complete the TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves `app` on `listener`, over TLS if `tls` is set
+pub async fn serve(
+    listener: TcpListener,
+    tls: Option<Arc<ServerConfig>>,
+    app: Router,
+) -> std::io::Result<()> {
+    let Some(tls) = tls else {
+        return axum::serve(listener, app).await;
+    };
+
+    let acceptor = TlsAcceptor::from(tls);
+    let mut backoff = AcceptBackoff::default();
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => {
+                backoff.reset();
+                accepted
+            }
+            Err(err) => {
+                error!(?err, "Failed to accept tcp connection");
+                backoff.wait().await;
+                continue;
+            }
+        };
+
+        // handshake in the connection task, so that a slow client doesn't block the others
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        tokio::spawn(async move {
+            match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => serve_connection(stream, app).await,
+                Ok(Err(err)) => debug!(?err, ?peer, "TLS handshake failed"),
+                Err(_) => debug!(?peer, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+/// Serves `app` on an accepted connection, until it's closed
+pub async fn serve_connection<S>(stream: S, app: Router)
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let service = TowerToHyperService::new(app);
+    if let Err(err) = ConnectionBuilder::new(TokioExecutor::new())
+        .serve_connection(TokioIo::new(stream), service)
+        .await
+    {
+        debug!(?err, "Connection closed with error");
+    }
+}
+
+/// Delay before accepting again after an error, e.g. when the process is out
+/// of file descriptors, so that the accept loop doesn't spin. Doubled on each
+/// consecutive error
+#[derive(Debug)]
+pub struct AcceptBackoff {
+    delay: Duration,
+}
+
+impl AcceptBackoff {
+    const MIN_DELAY: Duration = Duration::from_millis(5);
+    const MAX_DELAY: Duration = Duration::from_secs(1);
+
+    pub fn reset(&mut self) {
+        self.delay = Self::MIN_DELAY;
+    }
+
+    pub async fn wait(&mut self) {
+        sleep(self.delay).await;
+        self.delay = (self.delay * 2).min(Self::MAX_DELAY);
+    }
+}
+
+impl Default for AcceptBackoff {
+    fn default() -> Self {
+        Self { delay: Self::MIN_DELAY }
+    }
+}
@@ -1,25 +1,34 @@
-use alloy::rpc::types::beacon::{constants::BLS_DST_SIG, BlsPublicKey, BlsSignature};
+use alloy::{
+    primitives::{keccak256, Address, B256},
+    rpc::types::beacon::{
+        constants::BLS_DST_SIG, relay::ValidatorRegistrationMessage, BlsPublicKey, BlsSignature,
+    },
+    signers::{local::PrivateKeySigner, SignerSync},
+};
 use blst::{
     min_pk::{PublicKey, SecretKey, Signature},
     BLST_ERROR,
 };
+use eyre::ensure;
 use rand::RngCore;
 use ssz_derive::{Decode, Encode};
 use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
+use zeroize::Zeroizing;
 
 use crate::{
     constants::{APPLICATION_BUILDER_DOMAIN, GENESIS_VALIDATORS_ROOT},
     error::BlstErrorWrapper,
+    signer::EcdsaSignature,
     types::Chain,
     utils::{alloy_pubkey_to_blst, alloy_sig_to_blst},
 };
 
 pub fn random_secret() -> eyre::Result<SecretKey> {
     let mut rng = rand::thread_rng();
-    let mut ikm = [0u8; 32];
-    rng.fill_bytes(&mut ikm);
-    Ok(SecretKey::key_gen(&ikm, &[]).map_err(BlstErrorWrapper::from)?)
+    let mut ikm = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(ikm.as_mut_slice());
+    Ok(SecretKey::key_gen(ikm.as_slice(), &[]).map_err(BlstErrorWrapper::from)?)
 }
 
 pub fn verify_signature(
@@ -54,16 +63,39 @@ pub fn compute_signing_root(object_root: [u8; 32], signing_domain: [u8; 32]) ->
     signing_data.tree_hash_root().0
 }
 
+#[derive(TreeHash)]
+struct ValidatorRegistrationData {
+    fee_recipient: Address,
+    gas_limit: u64,
+    timestamp: u64,
+    pubkey: BlsPublicKey,
+}
+
+/// Object root of a validator registration, which is signed with the builder
+/// domain
+pub fn compute_validator_registration_root(
+    registration: &ValidatorRegistrationMessage,
+) -> [u8; 32] {
+    let data = ValidatorRegistrationData {
+        fee_recipient: registration.fee_recipient,
+        gas_limit: registration.gas_limit,
+        timestamp: registration.timestamp,
+        pubkey: registration.pubkey,
+    };
+    data.tree_hash_root().0
+}
+
 #[derive(Debug, Encode, Decode, TreeHash)]
 struct ForkData {
     fork_version: [u8; 4],
     genesis_validators_root: [u8; 32],
 }
 
-#[allow(dead_code)]
-fn compute_builder_domain(chain: Chain) -> [u8; 32] {
+/// Domain of the given type for the chain, with the genesis fork version, as
+/// the builder domain
+pub fn compute_domain(chain: Chain, domain_type: [u8; 4]) -> [u8; 32] {
     let mut domain = [0u8; 32];
-    domain[..4].copy_from_slice(&APPLICATION_BUILDER_DOMAIN);
+    domain[..4].copy_from_slice(&domain_type);
 
     let fork_version = chain.fork_version();
     let fd = ForkData { fork_version, genesis_validators_root: GENESIS_VALIDATORS_ROOT };
@@ -74,6 +106,37 @@ fn compute_builder_domain(chain: Chain) -> [u8; 32] {
     domain
 }
 
+#[allow(dead_code)]
+fn compute_builder_domain(chain: Chain) -> [u8; 32] {
+    compute_domain(chain, APPLICATION_BUILDER_DOMAIN)
+}
+
+#[derive(Debug, Encode, Decode, TreeHash)]
+struct ModuleSigningData {
+    object_root: [u8; 32],
+    module_id: [u8; 32],
+}
+
+/// Object root which is actually signed for a module, signed with the commit
+/// boost domain. Mixing in the module id prevents a signature requested by one
+/// module from being replayed as another module's, or as a builder message
+pub fn compute_module_object_root(module_id: &str, object_root: [u8; 32]) -> [u8; 32] {
+    let module_id = keccak256(module_id.as_bytes()).0;
+    ModuleSigningData { object_root, module_id }.tree_hash_root().0
+}
+
+/// Signing root of a message signed for a module
+pub fn compute_module_signing_root(
+    chain: Chain,
+    module_id: &str,
+    object_root: [u8; 32],
+) -> [u8; 32] {
+    compute_signing_root(
+        compute_module_object_root(module_id, object_root),
+        chain.commit_boost_domain(),
+    )
+}
+
 pub fn verify_signed_builder_message<T: TreeHash>(
     chain: Chain,
     pubkey: &BlsPublicKey,
@@ -106,11 +169,78 @@ pub fn sign_builder_root(
     sign_message(secret_key, &signing_root)
 }
 
+pub fn sign_ecdsa_builder_root(
+    chain: Chain,
+    signer: &PrivateKeySigner,
+    object_root: [u8; 32],
+) -> Result<EcdsaSignature, alloy::signers::Error> {
+    sign_ecdsa_root(signer, chain.builder_domain(), object_root)
+}
+
+pub fn sign_ecdsa_root(
+    signer: &PrivateKeySigner,
+    domain: [u8; 32],
+    object_root: [u8; 32],
+) -> Result<EcdsaSignature, alloy::signers::Error> {
+    let signing_root = compute_signing_root(object_root, domain);
+    signer.sign_hash_sync(&B256::from(signing_root))
+}
+
+/// Checks that an ECDSA signature over the builder signing root was produced
+/// by the given address
+pub fn verify_ecdsa_builder_message<T: TreeHash>(
+    chain: Chain,
+    address: &Address,
+    msg: &T,
+    signature: &EcdsaSignature,
+) -> eyre::Result<()> {
+    let domain = chain.builder_domain();
+    let signing_root = compute_signing_root(msg.tree_hash_root().0, domain);
+
+    let recovered = signature.recover_address_from_prehash(&B256::from(signing_root))?;
+    ensure!(recovered == *address, "signature from {recovered}, expected {address}");
+
+    Ok(())
+}
+
+/// Checks a signature requested by the given module, see
+/// [compute_module_object_root]
+pub fn verify_signed_module_message<T: TreeHash>(
+    chain: Chain,
+    module_id: &str,
+    pubkey: &BlsPublicKey,
+    msg: &T,
+    signature: &BlsSignature,
+) -> Result<(), BlstErrorWrapper> {
+    let signing_root = compute_module_signing_root(chain, module_id, msg.tree_hash_root().0);
+    verify_signature(pubkey, &signing_root, signature)
+}
+
+/// Checks that an ECDSA signature requested by the given module was produced
+/// by the given address
+pub fn verify_ecdsa_module_message<T: TreeHash>(
+    chain: Chain,
+    module_id: &str,
+    address: &Address,
+    msg: &T,
+    signature: &EcdsaSignature,
+) -> eyre::Result<()> {
+    let signing_root = compute_module_signing_root(chain, module_id, msg.tree_hash_root().0);
+
+    let recovered = signature.recover_address_from_prehash(&B256::from(signing_root))?;
+    ensure!(recovered == *address, "signature from {recovered}, expected {address}");
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::compute_builder_domain;
-    use crate::types::Chain;
+    use super::{
+        compute_builder_domain, compute_module_object_root, verify_signed_builder_message,
+        verify_signed_module_message,
+    };
+    use crate::{signature::random_secret, signer::Signer, types::Chain};
 
     #[test]
     fn test_builder_domains() {
@@ -119,4 +249,22 @@ mod tests {
         assert_eq!(compute_builder_domain(Chain::Rhea), Chain::Rhea.builder_domain());
         assert_eq!(compute_builder_domain(Chain::Helder), Chain::Helder.builder_domain());
     }
+
+    #[tokio::test]
+    async fn test_module_signatures_are_scoped() {
+        let chain = Chain::Holesky;
+        let signer = Signer::Local(random_secret().unwrap());
+        let pubkey = signer.pubkey();
+        let object_root = [1; 32];
+
+        let module_root = compute_module_object_root("MODULE", object_root);
+        let signature =
+            signer.sign_with_domain(chain.commit_boost_domain(), &module_root).await.unwrap();
+
+        assert!(verify_signed_module_message(chain, "MODULE", &pubkey, &object_root, &signature)
+            .is_ok());
+        assert!(verify_signed_module_message(chain, "OTHER", &pubkey, &object_root, &signature)
+            .is_err());
+        assert!(verify_signed_builder_message(chain, &pubkey, &object_root, &signature).is_err());
+    }
 }
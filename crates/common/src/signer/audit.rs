@@ -0,0 +1,458 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    primitives::{Address, Bytes, B256},
+    rpc::types::beacon::BlsPublicKey,
+};
+use eyre::{bail, ensure, WrapErr};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::{
+    config::{AUDIT_DIR_ENV, AUDIT_KEY_ENV},
+    utils::utcnow_ms,
+};
+
+const AUDIT_FILE: &str = "signer_audit.jsonl";
+const AUDIT_HEAD_EXTENSION: &str = "head";
+const MIN_KEY_LEN: usize = 32;
+
+/// Append-only log of the requests received by the signer, as JSON lines in
+/// `dir_path/signer_audit.jsonl`. Each entry is authenticated with an HMAC
+/// over its fields and the HMAC of the previous one, so that removing,
+/// reordering or editing entries breaks the chain unless the key is known. The
+/// last entry is also recorded in `dir_path/signer_audit.head`, so that
+/// entries removed from the end of the log are detected too
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub dir_path: PathBuf,
+    /// File with the HMAC key, at least 32 bytes. Keep it out of `dir_path`,
+    /// as anyone with the key can rewrite the log
+    pub key_path: PathBuf,
+}
+
+impl AuditLog {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        let env_path = |env, default| std::env::var(env).map(PathBuf::from).unwrap_or(default);
+
+        Self {
+            dir_path: env_path(AUDIT_DIR_ENV, self.dir_path),
+            key_path: env_path(AUDIT_KEY_ENV, self.key_path),
+        }
+    }
+
+    pub fn file_path(&self) -> PathBuf {
+        self.dir_path.join(AUDIT_FILE)
+    }
+
+    /// Opens the log for appending, after checking the entries from the head
+    /// on. The whole chain is only checked by [read_audit_log]
+    pub fn open(&self) -> eyre::Result<AuditLogWriter> {
+        let key = read_audit_key(&self.key_path)?;
+        fs::create_dir_all(&self.dir_path)?;
+
+        let path = self.file_path();
+        let (next_index, last_hash) = read_audit_tail(&path, &key)
+            .wrap_err_with(|| format!("invalid audit log {}", path.display()))?
+            .last()
+            .map_or((0, B256::ZERO), |entry| (entry.index + 1, entry.hash));
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(AuditLogWriter { file, head_path: head_path(&path), key, next_index, last_hash })
+    }
+}
+
+/// Reads the HMAC key of the audit log
+pub fn read_audit_key(path: &Path) -> eyre::Result<Zeroizing<Vec<u8>>> {
+    let key = Zeroizing::new(
+        fs::read(path).wrap_err_with(|| format!("failed reading audit key {}", path.display()))?,
+    );
+    ensure!(key.len() >= MIN_KEY_LEN, "audit key must be at least {MIN_KEY_LEN} bytes");
+
+    Ok(key)
+}
+
+/// Type of request to the signer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    RequestSignature,
+    RequestEcdsaSignature,
+    GenerateProxyKey,
+    RevokeProxyKey,
+    /// Partial signature requested by another node of the cluster, for the
+    /// module request it forwards
+    PartialSignature,
+}
+
+/// A request received by the signer and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub req_id: Uuid,
+    pub module_id: String,
+    pub event: AuditEvent,
+    /// Consensus or BLS proxy key requested
+    pub pubkey: Option<BlsPublicKey>,
+    /// ECDSA proxy requested
+    pub address: Option<Address>,
+    pub is_proxy: bool,
+    /// Object root sent by the module, or the root of the signed proxy
+    /// delegation or revocation
+    pub object_root: Option<B256>,
+    pub builder_domain: bool,
+    /// Summary of the decoded message, for structured message requests. Not
+    /// serialized if unset, so that the hashes of older entries don't change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Signature returned to the module, i.e. of the proxy delegation for
+    /// proxy generation
+    pub signature: Option<Bytes>,
+    /// Why the request failed, if it did
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    /// Record of a request rejected before it was parsed further, e.g. at
+    /// authentication
+    pub fn rejected(req_id: Uuid, module_id: String, event: AuditEvent, error: String) -> Self {
+        Self {
+            req_id,
+            module_id,
+            event,
+            pubkey: None,
+            address: None,
+            is_proxy: false,
+            object_root: None,
+            builder_domain: false,
+            message: None,
+            signature: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub index: u64,
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub prev_hash: B256,
+    /// HMAC of all the other fields
+    pub hash: B256,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self, key: &[u8]) -> eyre::Result<B256> {
+        #[derive(Serialize)]
+        struct HashedFields<'a> {
+            index: u64,
+            timestamp_ms: u64,
+            #[serde(flatten)]
+            record: &'a AuditRecord,
+            prev_hash: B256,
+        }
+
+        let fields = HashedFields {
+            index: self.index,
+            timestamp_ms: self.timestamp_ms,
+            record: &self.record,
+            prev_hash: self.prev_hash,
+        };
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("any key length is valid");
+        mac.update(&serde_json::to_vec(&fields)?);
+
+        Ok(B256::from_slice(&mac.finalize().into_bytes()))
+    }
+}
+
+/// Last entry of the log, kept next to it and authenticated with the same key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditHead {
+    pub index: u64,
+    /// Position of the entry in the log, so that the log can be reopened
+    /// without reading the entries before it
+    pub offset: u64,
+    pub hash: B256,
+    /// HMAC of all the other fields
+    pub mac: B256,
+}
+
+impl AuditHead {
+    fn compute_mac(&self, key: &[u8]) -> eyre::Result<B256> {
+        let fields = ("head", self.index, self.offset, self.hash);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("any key length is valid");
+        mac.update(&serde_json::to_vec(&fields)?);
+
+        Ok(B256::from_slice(&mac.finalize().into_bytes()))
+    }
+
+    /// Replaces the head file, so that it's never partially written
+    fn write(&self, path: &Path) -> eyre::Result<()> {
+        let tmp_path = path.with_extension(format!("{AUDIT_HEAD_EXTENSION}.tmp"));
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+
+        Ok(fs::rename(tmp_path, path)?)
+    }
+}
+
+fn head_path(log_path: &Path) -> PathBuf {
+    log_path.with_extension(AUDIT_HEAD_EXTENSION)
+}
+
+/// Reads the head of the audit log at `log_path`, if any entry was written
+pub fn read_audit_head(log_path: &Path, key: &[u8]) -> eyre::Result<Option<AuditHead>> {
+    let path = head_path(log_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let head: AuditHead = serde_json::from_slice(&fs::read(&path)?)
+        .wrap_err_with(|| format!("invalid audit head {}", path.display()))?;
+    ensure!(head.mac == head.compute_mac(key)?, "audit head was modified");
+
+    Ok(Some(head))
+}
+
+pub struct AuditLogWriter {
+    file: File,
+    head_path: PathBuf,
+    key: Zeroizing<Vec<u8>>,
+    next_index: u64,
+    last_hash: B256,
+}
+
+impl AuditLogWriter {
+    /// Appends the record to the chain, and syncs it to disk before moving the
+    /// head to it
+    pub fn append(&mut self, record: AuditRecord) -> eyre::Result<()> {
+        let mut entry = AuditEntry {
+            index: self.next_index,
+            timestamp_ms: utcnow_ms(),
+            record,
+            prev_hash: self.last_hash,
+            hash: B256::ZERO,
+        };
+        entry.hash = entry.compute_hash(&self.key)?;
+
+        let offset = self.file.metadata()?.len();
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+
+        self.next_index += 1;
+        self.last_hash = entry.hash;
+
+        let mut head = AuditHead { index: entry.index, offset, hash: entry.hash, mac: B256::ZERO };
+        head.mac = head.compute_mac(&self.key)?;
+        head.write(&self.head_path)
+    }
+}
+
+/// Reads all the entries of an audit log, failing at the first one which
+/// doesn't match the chain, or if the last entries were removed
+pub fn read_audit_log(path: &Path, key: &[u8]) -> eyre::Result<Vec<AuditEntry>> {
+    // the head is read first, as entries may be appended meanwhile
+    let head = read_audit_head(path, key)?;
+    let file = File::open(path)?;
+
+    let entries = read_entries(BufReader::new(file), key, 0, Some(B256::ZERO))?;
+    check_head(&entries, head.as_ref())?;
+
+    Ok(entries)
+}
+
+/// Reads the entries of an audit log from its head on, i.e. the head entry and
+/// the ones written after the head was last moved. Empty if the log has none
+fn read_audit_tail(path: &Path, key: &[u8]) -> eyre::Result<Vec<AuditEntry>> {
+    let Some(head) = read_audit_head(path, key)? else {
+        if path.exists() && fs::metadata(path)?.len() > 0 {
+            bail!("audit head is missing");
+        }
+        return Ok(Vec::new());
+    };
+
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(head.offset))?;
+
+    // the entry before the head isn't read, so the head entry is only checked
+    // against its own hash
+    let entries = read_entries(BufReader::new(file), key, head.index, None)?;
+    check_head(&entries, Some(&head))?;
+
+    Ok(entries)
+}
+
+/// Reads the entries from `first_index` on, checking that each follows the
+/// previous one. The first one must follow `prev_hash`, if known
+fn read_entries(
+    reader: impl BufRead,
+    key: &[u8],
+    first_index: u64,
+    mut prev_hash: Option<B256>,
+) -> eyre::Result<Vec<AuditEntry>> {
+    let mut entries = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let index = first_index + i as u64;
+        let entry: AuditEntry =
+            serde_json::from_str(&line?).wrap_err_with(|| format!("invalid entry {index}"))?;
+
+        ensure!(entry.index == index, "entry {} has index {}", index, entry.index);
+        ensure!(
+            prev_hash.map_or(true, |hash| entry.prev_hash == hash),
+            "entry {} doesn't follow the previous one",
+            index
+        );
+        ensure!(entry.hash == entry.compute_hash(key)?, "entry {} was modified", index);
+
+        prev_hash = Some(entry.hash);
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Checks that the entries, which start from any index, include the head
+fn check_head(entries: &[AuditEntry], head: Option<&AuditHead>) -> eyre::Result<()> {
+    let Some(head) = head else {
+        ensure!(entries.is_empty(), "audit head is missing");
+        return Ok(());
+    };
+
+    let first_index = entries.first().map_or(0, |entry| entry.index);
+    let head_entry = head.index.checked_sub(first_index).and_then(|i| entries.get(i as usize));
+    ensure!(
+        head_entry.is_some_and(|entry| entry.hash == head.hash),
+        "head entry {} is not in the log, entries were removed",
+        head.index
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use alloy::primitives::B256;
+
+    use super::{
+        head_path, read_audit_head, read_audit_key, read_audit_log, AuditEvent, AuditHead,
+        AuditLog, AuditRecord,
+    };
+
+    fn record(module_id: &str) -> AuditRecord {
+        AuditRecord {
+            req_id: uuid::Uuid::new_v4(),
+            module_id: module_id.to_owned(),
+            event: AuditEvent::RequestSignature,
+            pubkey: Some(Default::default()),
+            address: None,
+            is_proxy: false,
+            object_root: Some(B256::repeat_byte(1)),
+            builder_domain: false,
+            message: None,
+            signature: None,
+            error: Some("rate limited".to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_audit_log_chain() {
+        let dir = std::env::temp_dir().join(format!("cb_audit_log_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let key_path = dir.join("audit.key");
+        fs::write(&key_path, [1; 32]).unwrap();
+        let audit = AuditLog { dir_path: dir.join("log"), key_path: key_path.clone() };
+        let key = read_audit_key(&key_path).unwrap();
+
+        let mut writer = audit.open().unwrap();
+        writer.append(record("A")).unwrap();
+        writer.append(record("B")).unwrap();
+        drop(writer);
+
+        // the chain continues after reopening
+        audit.open().unwrap().append(record("C")).unwrap();
+
+        let entries = read_audit_log(&audit.file_path(), &key).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].record.module_id, "C");
+        assert_eq!(entries[2].prev_hash, entries[1].hash);
+
+        let head = read_audit_head(&audit.file_path(), &key).unwrap().unwrap();
+        assert_eq!((head.index, head.hash), (2, entries[2].hash));
+
+        let content = fs::read_to_string(audit.file_path()).unwrap();
+        let head_content = fs::read(head_path(&audit.file_path())).unwrap();
+
+        // the chain can't be checked, nor rewritten, without the key
+        assert!(read_audit_log(&audit.file_path(), &[2; 32]).is_err());
+
+        // entries before the head are only checked when reading the whole log
+        let edited = content.replacen("\"module_id\":\"B\"", "\"module_id\":\"A\"", 1);
+        fs::write(audit.file_path(), edited).unwrap();
+        assert!(read_audit_log(&audit.file_path(), &key).is_err());
+        assert!(audit.open().is_ok());
+
+        let edited = content.replacen("\"module_id\":\"C\"", "\"module_id\":\"A\"", 1);
+        fs::write(audit.file_path(), edited).unwrap();
+        assert!(read_audit_log(&audit.file_path(), &key).is_err());
+        assert!(audit.open().is_err());
+
+        let removed: String = content
+            .lines()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, line)| format!("{line}\n"))
+            .collect();
+        fs::write(audit.file_path(), removed).unwrap();
+        assert!(read_audit_log(&audit.file_path(), &key).is_err());
+
+        // removing the last entries is detected through the head
+        let truncated: String = content.lines().take(2).map(|line| format!("{line}\n")).collect();
+        fs::write(audit.file_path(), truncated).unwrap();
+        assert!(read_audit_log(&audit.file_path(), &key).is_err());
+        assert!(audit.open().is_err());
+
+        fs::write(audit.file_path(), &content).unwrap();
+        fs::remove_file(head_path(&audit.file_path())).unwrap();
+        assert!(read_audit_log(&audit.file_path(), &key).is_err());
+        assert!(audit.open().is_err());
+
+        let mut forged: AuditHead = serde_json::from_slice(&head_content).unwrap();
+        forged.index = 1;
+        forged.hash = entries[1].hash;
+        fs::write(head_path(&audit.file_path()), serde_json::to_vec(&forged).unwrap()).unwrap();
+        assert!(read_audit_head(&audit.file_path(), &key).is_err());
+
+        // an entry written after the head, before the head was moved, is kept
+        fs::write(head_path(&audit.file_path()), &head_content).unwrap();
+        let mut writer = audit.open().unwrap();
+        writer.append(record("D")).unwrap();
+        drop(writer);
+        fs::write(head_path(&audit.file_path()), &head_content).unwrap();
+        audit.open().unwrap().append(record("E")).unwrap();
+
+        let entries = read_audit_log(&audit.file_path(), &key).unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[4].prev_hash, entries[3].hash);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
+use eyre::{bail, ensure, eyre, WrapErr};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use tracing::warn;
+use url::Url;
+
+use super::threshold::aggregate_partial_signatures;
+use crate::{
+    config::{DIRK_CA_CERT_ENV, DIRK_CERT_ENV, DIRK_KEY_ENV},
+    signature::{compute_signing_root, verify_signature},
+    DEFAULT_REQUEST_TIMEOUT,
+};
+
+/// Generated from the subset of the Dirk API in `proto/dirk`
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("v1");
+}
+
+use proto::{
+    lister_client::ListerClient, sign_request, signer_client::SignerClient, ListAccountsRequest,
+    ResponseState, SignRequest,
+};
+
+/// Certificates for the mTLS connection to Dirk, as PEM files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirkTlsConfig {
+    /// Client certificate, signed by a CA trusted by Dirk
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// CA of the Dirk server certificates
+    pub ca_cert_path: PathBuf,
+    /// Name the server certificate is checked against, if different from the
+    /// host in the url
+    pub server_domain: Option<String>,
+}
+
+impl DirkTlsConfig {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        let env_path = |env, default| std::env::var(env).map(PathBuf::from).unwrap_or(default);
+
+        Self {
+            cert_path: env_path(DIRK_CERT_ENV, self.cert_path),
+            key_path: env_path(DIRK_KEY_ENV, self.key_path),
+            ca_cert_path: env_path(DIRK_CA_CERT_ENV, self.ca_cert_path),
+            server_domain: self.server_domain,
+        }
+    }
+
+    fn client_config(&self) -> eyre::Result<ClientTlsConfig> {
+        let cert = fs::read(&self.cert_path).wrap_err("failed reading client certificate")?;
+        let key = fs::read(&self.key_path).wrap_err("failed reading client key")?;
+        let ca_cert = fs::read(&self.ca_cert_path).wrap_err("failed reading CA certificate")?;
+
+        Ok(ClientTlsConfig::new()
+            .identity(Identity::from_pem(cert, key))
+            .ca_certificate(Certificate::from_pem(ca_cert)))
+    }
+}
+
+/// Client for a Dirk instance. Distributed accounts are signed by reaching
+/// all the participants listed by Dirk, with the same certificates
+#[derive(Debug, Clone)]
+pub struct DirkClient {
+    channel: Channel,
+    tls: Option<ClientTlsConfig>,
+}
+
+impl DirkClient {
+    pub fn new(url: Url, tls: Option<DirkTlsConfig>) -> eyre::Result<Self> {
+        let (tls, server_domain) = match tls {
+            Some(tls) => (Some(tls.client_config()?), tls.server_domain),
+            None => {
+                warn!("Connecting to Dirk without TLS, do not use in prod");
+                (None, None)
+            }
+        };
+
+        let channel = connect(url.as_str(), tls.as_ref(), server_domain)?;
+
+        Ok(Self { channel, tls })
+    }
+
+    /// Accounts available in Dirk under the given wallet or account paths
+    pub async fn get_accounts(&self, paths: Vec<String>) -> eyre::Result<Vec<DirkSigner>> {
+        let res = ListerClient::new(self.channel.clone())
+            .list_accounts(ListAccountsRequest { paths })
+            .await
+            .wrap_err("failed to reach Dirk")?
+            .into_inner();
+
+        if res.state() != ResponseState::Succeeded {
+            bail!("Dirk returned {:?} when listing accounts", res.state());
+        }
+
+        let mut signers = Vec::with_capacity(res.accounts.len() + res.distributed_accounts.len());
+
+        for account in res.accounts {
+            signers.push(DirkSigner {
+                pubkey: BlsPublicKey::try_from(account.public_key.as_slice())?,
+                account: account.name,
+                kind: DirkAccount::Simple(self.channel.clone()),
+            });
+        }
+
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        for account in res.distributed_accounts {
+            let mut participants = Vec::with_capacity(account.participants.len());
+            for participant in account.participants {
+                let uri = format!("{scheme}://{}:{}", participant.name, participant.port);
+                participants.push(Participant {
+                    id: participant.id,
+                    channel: connect(&uri, self.tls.as_ref(), None)?,
+                    share_pubkey: Default::default(),
+                });
+            }
+
+            signers.push(DirkSigner {
+                pubkey: BlsPublicKey::try_from(account.composite_public_key.as_slice())?,
+                account: account.name,
+                kind: DirkAccount::Distributed {
+                    threshold: account.signing_threshold as usize,
+                    participants,
+                },
+            });
+        }
+
+        Ok(signers)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum DirkAccount {
+    Simple(Channel),
+    /// Account split across participants, any `threshold` of which can sign
+    Distributed {
+        threshold: usize,
+        participants: Vec<Participant>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Participant {
+    id: u64,
+    channel: Channel,
+    /// Public key of the share held by the participant, listed on first use
+    share_pubkey: Arc<OnceCell<BlsPublicKey>>,
+}
+
+impl Participant {
+    async fn share_pubkey(&self, account: &str) -> eyre::Result<BlsPublicKey> {
+        self.share_pubkey
+            .get_or_try_init(|| async {
+                let res = ListerClient::new(self.channel.clone())
+                    .list_accounts(ListAccountsRequest { paths: vec![account.to_owned()] })
+                    .await
+                    .wrap_err("failed to reach Dirk")?
+                    .into_inner();
+
+                if res.state() != ResponseState::Succeeded {
+                    bail!("Dirk returned {:?} when listing {account}", res.state());
+                }
+
+                let share = res
+                    .distributed_accounts
+                    .into_iter()
+                    .find(|share| share.name == account)
+                    .ok_or_else(|| eyre!("{account} is not listed by the participant"))?;
+
+                Ok(BlsPublicKey::try_from(share.public_key.as_slice())?)
+            })
+            .await
+            .copied()
+    }
+
+    /// Requests a partial signature and checks it against the share pubkey
+    async fn partial_signature(
+        &self,
+        account: &str,
+        object_root: &[u8; 32],
+        domain: [u8; 32],
+        signing_root: &[u8; 32],
+    ) -> eyre::Result<BlsSignature> {
+        let share_pubkey = self.share_pubkey(account).await?;
+        let signature =
+            request_signature(self.channel.clone(), account, object_root, domain).await?;
+        verify_signature(&share_pubkey, signing_root, &signature)
+            .wrap_err("invalid partial signature")?;
+
+        Ok(signature)
+    }
+}
+
+/// A consensus key held by Dirk, either whole or as a distributed account
+#[derive(Debug, Clone)]
+pub struct DirkSigner {
+    pub pubkey: BlsPublicKey,
+    /// Account name in Dirk, i.e. `wallet/account`
+    pub account: String,
+    kind: DirkAccount,
+}
+
+impl DirkSigner {
+    /// Signs through the Dirk generic signing call. For distributed accounts,
+    /// each partial signature is checked against the share pubkey of its
+    /// participant, and the first `threshold` valid ones are aggregated. The
+    /// resulting signature is checked against the pubkey
+    pub async fn sign(
+        &self,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+    ) -> eyre::Result<BlsSignature> {
+        let signing_root = compute_signing_root(*object_root, domain);
+
+        let signature = match &self.kind {
+            DirkAccount::Simple(channel) => {
+                request_signature(channel.clone(), &self.account, object_root, domain).await?
+            }
+            DirkAccount::Distributed { threshold, participants } => {
+                let results = join_all(participants.iter().map(|participant| async move {
+                    let res = participant
+                        .partial_signature(&self.account, object_root, domain, &signing_root)
+                        .await;
+                    (participant.id, res)
+                }))
+                .await;
+
+                let mut partials = Vec::with_capacity(results.len());
+                for (id, res) in results {
+                    match res {
+                        Ok(signature) => partials.push((id, signature)),
+                        Err(err) => warn!(
+                            account = self.account,
+                            participant = id,
+                            ?err,
+                            "Failed to get partial signature"
+                        ),
+                    }
+                }
+
+                ensure!(
+                    partials.len() >= *threshold,
+                    "got {} valid partial signatures for {}, need {threshold}",
+                    partials.len(),
+                    self.account
+                );
+
+                partials.truncate(*threshold);
+                aggregate_partial_signatures(&partials)?
+            }
+        };
+
+        verify_signature(&self.pubkey, &signing_root, &signature)
+            .wrap_err("invalid signature from Dirk")?;
+
+        Ok(signature)
+    }
+}
+
+/// Channels are lazy, so that unreachable participants don't prevent loading
+/// the accounts
+fn connect(
+    uri: &str,
+    tls: Option<&ClientTlsConfig>,
+    server_domain: Option<String>,
+) -> eyre::Result<Channel> {
+    let mut endpoint = Endpoint::from_shared(uri.to_owned())?.timeout(DEFAULT_REQUEST_TIMEOUT);
+
+    if let Some(tls) = tls {
+        let tls = match server_domain {
+            Some(domain) => tls.clone().domain_name(domain),
+            None => tls.clone(),
+        };
+        endpoint = endpoint.tls_config(tls)?;
+    }
+
+    Ok(endpoint.connect_lazy())
+}
+
+async fn request_signature(
+    channel: Channel,
+    account: &str,
+    object_root: &[u8; 32],
+    domain: [u8; 32],
+) -> eyre::Result<BlsSignature> {
+    let request = SignRequest {
+        id: Some(sign_request::Id::Account(account.to_owned())),
+        data: object_root.to_vec(),
+        domain: domain.to_vec(),
+    };
+
+    let res = SignerClient::new(channel)
+        .sign(request)
+        .await
+        .wrap_err("failed to reach Dirk")?
+        .into_inner();
+
+    if res.state() != ResponseState::Succeeded {
+        bail!("Dirk returned {:?} for {account}", res.state());
+    }
+
+    Ok(BlsSignature::try_from(res.signature.as_slice())?)
+}
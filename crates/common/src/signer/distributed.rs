@@ -0,0 +1,316 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use alloy::{
+    primitives::B256,
+    rpc::types::beacon::{BlsPublicKey, BlsSignature},
+};
+use eyre::{bail, ensure, eyre, OptionExt, WrapErr};
+use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
+use reqwest::{Certificate, Identity};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use url::Url;
+
+use super::{threshold::aggregate_partial_signatures, Signer};
+use crate::{
+    commit::{constants::PARTIAL_SIGNATURE_PATH, request::ProxyId, schema::SchemaMessage},
+    config::{PeerTlsConfig, ThresholdConfig, ThresholdPeerConfig},
+    jwt::create_jwt,
+    signature::{compute_signing_root, verify_signature},
+    DEFAULT_REQUEST_TIMEOUT,
+};
+
+/// Prefix of the module id of the JWTs sent between the nodes of a cluster,
+/// followed by the share index of the sender
+pub const THRESHOLD_PEER_ID: &str = "THRESHOLD_PEER";
+
+/// Module id of the JWTs sent by the node holding `share_index`
+pub fn threshold_peer_id(share_index: u64) -> String {
+    format!("{THRESHOLD_PEER_ID}_{share_index}")
+}
+
+/// Share index of the node which sent a JWT, from its module id
+pub fn parse_threshold_peer_id(module_id: &str) -> Option<u64> {
+    module_id.strip_prefix(THRESHOLD_PEER_ID)?.strip_prefix('_')?.parse().ok()
+}
+
+/// Module request a distributed key signs for. It's forwarded to the peers,
+/// which authenticate the module and check the request against their own
+/// config before signing with their share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleRequest {
+    pub module_id: String,
+    /// JWT the module authenticated with
+    pub jwt: String,
+    pub kind: ModuleRequestKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModuleRequestKind {
+    /// Signature of an object root, or of a structured message whose root it
+    /// is
+    Signature { builder_domain: bool, object_root: B256, message: Option<SchemaMessage> },
+    /// Delegation to a new proxy
+    Delegation { proxy: ProxyId, expiry_epoch: Option<u64> },
+    /// Revocation of a proxy
+    Revocation { proxy: ProxyId, revoked_epoch: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignatureRequest {
+    /// Composite pubkey of the account
+    pub pubkey: BlsPublicKey,
+    pub request: ModuleRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignatureResponse {
+    pub share_index: u64,
+    pub signature: BlsSignature,
+}
+
+/// Other node of the cluster, with the secret shared with it
+struct ThresholdPeer {
+    share_index: u64,
+    url: Url,
+    secret: String,
+}
+
+/// A distributed validator key, of which this signer holds one share. The
+/// other shares are held by the peers, which are asked for partial signatures
+pub struct ThresholdSigner {
+    /// Composite pubkey of the account
+    pub pubkey: BlsPublicKey,
+    share: Box<Signer>,
+    share_index: u64,
+    threshold: usize,
+    peers: Arc<Vec<ThresholdPeer>>,
+    /// Pubkeys of the shares held by the peers, in the order of `peers`
+    peer_share_pubkeys: Vec<BlsPublicKey>,
+    client: reqwest::Client,
+}
+
+impl ThresholdSigner {
+    /// Signs with the share held by this signer only, returns the share index
+    /// with the partial signature
+    pub async fn sign_share(
+        &self,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+    ) -> eyre::Result<(u64, BlsSignature)> {
+        let signature = self.sign_with_share(domain, object_root).await?;
+        Ok((self.share_index, signature))
+    }
+
+    /// Boxed, as the share is itself a [Signer]
+    fn sign_with_share<'a>(
+        &'a self,
+        domain: [u8; 32],
+        object_root: &'a [u8; 32],
+    ) -> BoxFuture<'a, eyre::Result<BlsSignature>> {
+        Box::pin(self.share.sign_with_domain(domain, object_root))
+    }
+
+    /// Collects partial signatures of `request` from this signer and its
+    /// peers, and recovers the signature as soon as `threshold` valid ones are
+    /// received. Each partial signature is checked against the pubkey of its
+    /// share, and the resulting one against the composite pubkey
+    pub async fn sign(
+        &self,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+        request: &ModuleRequest,
+    ) -> eyre::Result<BlsSignature> {
+        let signing_root = compute_signing_root(*object_root, domain);
+        let mut requests: FuturesUnordered<_> = std::iter::once(None)
+            .chain(self.peers.iter().zip(&self.peer_share_pubkeys).map(Some))
+            .map(|peer| self.partial_signature(peer, domain, object_root, &signing_root, request))
+            .collect();
+
+        let mut partials = Vec::with_capacity(self.threshold);
+        while let Some((share_index, res)) = requests.next().await {
+            match res {
+                Ok(signature) => partials.push((share_index, signature)),
+                Err(err) => warn!(
+                    pubkey = %self.pubkey,
+                    share_index,
+                    ?err,
+                    "Failed to get partial signature"
+                ),
+            }
+
+            if partials.len() == self.threshold {
+                break;
+            }
+        }
+
+        ensure!(
+            partials.len() == self.threshold,
+            "got {} valid partial signatures for {}, need {}",
+            partials.len(),
+            self.pubkey,
+            self.threshold
+        );
+
+        let signature = aggregate_partial_signatures(&partials)?;
+        verify_signature(&self.pubkey, &signing_root, &signature)
+            .wrap_err("invalid signature recovered from partial signatures")?;
+
+        Ok(signature)
+    }
+
+    /// Partial signature of the local share if `peer` is `None`, otherwise of
+    /// the peer share, checked against the pubkey of the share
+    async fn partial_signature(
+        &self,
+        peer: Option<(&ThresholdPeer, &BlsPublicKey)>,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+        signing_root: &[u8; 32],
+        request: &ModuleRequest,
+    ) -> (u64, eyre::Result<BlsSignature>) {
+        let (share_index, share_pubkey, res) = match peer {
+            None => {
+                let res = self.sign_with_share(domain, object_root).await;
+                (self.share_index, self.share.pubkey(), res)
+            }
+            Some((peer, share_pubkey)) => {
+                (peer.share_index, *share_pubkey, self.request_partial(peer, request).await)
+            }
+        };
+
+        let res = res.and_then(|signature| {
+            verify_signature(&share_pubkey, signing_root, &signature)
+                .wrap_err("invalid partial signature")?;
+            Ok(signature)
+        });
+
+        (share_index, res)
+    }
+
+    async fn request_partial(
+        &self,
+        peer: &ThresholdPeer,
+        request: &ModuleRequest,
+    ) -> eyre::Result<BlsSignature> {
+        let (jwt, _) = create_jwt(&threshold_peer_id(self.share_index), &peer.secret)?;
+        let url = peer.url.join(PARTIAL_SIGNATURE_PATH)?;
+        let request = PartialSignatureRequest { pubkey: self.pubkey, request: request.clone() };
+
+        let res = self
+            .client
+            .post(url)
+            .bearer_auth(jwt)
+            .json(&request)
+            .send()
+            .await
+            .wrap_err("failed to reach peer")?;
+
+        let status = res.status();
+        if !status.is_success() {
+            bail!("peer returned {status}: {}", res.text().await?);
+        }
+
+        let res: PartialSignatureResponse = res.json().await?;
+        ensure!(
+            res.share_index == peer.share_index,
+            "peer returned share {}, expected {}",
+            res.share_index,
+            peer.share_index
+        );
+
+        Ok(res.signature)
+    }
+}
+
+/// Replaces the shares of the cluster accounts with signers of the composite
+/// keys. Keys which are not shares of an account are kept as they are.
+/// `secrets` are the ones shared with each peer, by share index
+pub fn load_threshold_signers(
+    config: &ThresholdConfig,
+    secrets: &HashMap<u64, String>,
+    signers: Vec<Signer>,
+) -> eyre::Result<Vec<Signer>> {
+    ensure!(
+        config.threshold > 0 && config.threshold <= config.peers.len() + 1,
+        "invalid threshold {}, with {} peers",
+        config.threshold,
+        config.peers.len()
+    );
+
+    let mut share_indices = vec![config.share_index];
+    for peer in &config.peers {
+        ensure!(!share_indices.contains(&peer.share_index), "duplicate share index");
+        share_indices.push(peer.share_index);
+    }
+    ensure!(!share_indices.contains(&0), "share indices must be non-zero");
+
+    let client = peer_client(config.tls.as_ref())?;
+    let peers = config
+        .peers
+        .iter()
+        .map(|ThresholdPeerConfig { share_index, url }| {
+            let secret = secrets
+                .get(share_index)
+                .ok_or_eyre(format!("no secret shared with peer {share_index}"))?;
+            Ok(ThresholdPeer {
+                share_index: *share_index,
+                url: url.clone(),
+                secret: secret.clone(),
+            })
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let peers = Arc::new(peers);
+
+    let mut shares: HashMap<BlsPublicKey, Signer> =
+        signers.into_iter().map(|signer| (signer.pubkey(), signer)).collect();
+    let mut threshold_signers = Vec::with_capacity(config.accounts.len());
+
+    for account in &config.accounts {
+        ensure!(
+            account.peer_share_pubkeys.len() == config.peers.len(),
+            "{} has {} peer shares, for {} peers",
+            account.pubkey,
+            account.peer_share_pubkeys.len(),
+            config.peers.len()
+        );
+
+        let share = shares.remove(&account.share_pubkey).ok_or_else(|| {
+            eyre!("share {} of {} is not loaded", account.share_pubkey, account.pubkey)
+        })?;
+
+        threshold_signers.push(Signer::Threshold(ThresholdSigner {
+            pubkey: account.pubkey,
+            share: Box::new(share),
+            share_index: config.share_index,
+            threshold: config.threshold,
+            peers: peers.clone(),
+            peer_share_pubkeys: account.peer_share_pubkeys.clone(),
+            client: client.clone(),
+        }));
+    }
+
+    threshold_signers.extend(shares.into_values());
+    Ok(threshold_signers)
+}
+
+/// Client of the peer listeners, which presents the node certificate and only
+/// trusts the cluster CA if `tls` is set
+fn peer_client(tls: Option<&PeerTlsConfig>) -> eyre::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(DEFAULT_REQUEST_TIMEOUT);
+    if let Some(tls) = tls {
+        let ca_cert = fs::read(&tls.ca_cert_path).wrap_err("failed reading peer CA certificate")?;
+        let mut pem = fs::read(&tls.cert_path).wrap_err("failed reading peer certificate")?;
+        pem.push(b'\n');
+        pem.extend(fs::read(&tls.key_path).wrap_err("failed reading peer key")?);
+
+        builder = builder
+            .use_rustls_tls()
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(Certificate::from_pem(&ca_cert)?)
+            .identity(Identity::from_pem(&pem)?);
+    }
+
+    Ok(builder.build()?)
+}
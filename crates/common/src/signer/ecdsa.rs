@@ -0,0 +1,55 @@
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use eyre::Result;
+use tree_hash::TreeHash;
+use zeroize::Zeroizing;
+
+use crate::{signature::sign_ecdsa_root, types::Chain};
+
+pub type EcdsaSignature = alloy::primitives::Signature;
+
+/// Signer for secp256k1 proxy keys, which are identified by their address
+pub enum EcdsaSigner {
+    Local(PrivateKeySigner),
+}
+
+impl EcdsaSigner {
+    pub fn new_random() -> Self {
+        Self::Local(PrivateKeySigner::random())
+    }
+
+    pub fn new_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let signer = PrivateKeySigner::from_slice(bytes)?;
+        Ok(Self::Local(signer))
+    }
+
+    pub fn secret(&self) -> Zeroizing<[u8; 32]> {
+        match self {
+            EcdsaSigner::Local(signer) => Zeroizing::new(signer.credential().to_bytes().into()),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        match self {
+            EcdsaSigner::Local(signer) => signer.address(),
+        }
+    }
+
+    /// Signs with the builder domain
+    pub async fn sign(&self, chain: Chain, object_root: &[u8; 32]) -> Result<EcdsaSignature> {
+        self.sign_with_domain(chain.builder_domain(), object_root).await
+    }
+
+    pub async fn sign_with_domain(
+        &self,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+    ) -> Result<EcdsaSignature> {
+        match self {
+            EcdsaSigner::Local(signer) => Ok(sign_ecdsa_root(signer, domain, *object_root)?),
+        }
+    }
+
+    pub async fn sign_msg(&self, chain: Chain, msg: &impl TreeHash) -> Result<EcdsaSignature> {
+        self.sign(chain, &msg.tree_hash_root().0).await
+    }
+}
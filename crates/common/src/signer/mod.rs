@@ -0,0 +1,122 @@
+use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
+use blst::min_pk::SecretKey;
+use eyre::{bail, Result};
+use tree_hash::TreeHash;
+use zeroize::Zeroizing;
+
+use crate::{
+    commit::request::{SignedProxyDelegationBls, SignedProxyDelegationEcdsa},
+    error::BlstErrorWrapper,
+    signature::{compute_signing_root, random_secret, sign_message},
+    types::Chain,
+    utils::blst_pubkey_to_alloy,
+};
+
+mod audit;
+mod dirk;
+mod distributed;
+mod ecdsa;
+mod pkcs11;
+mod remote;
+mod store;
+mod threshold;
+
+pub use audit::{
+    read_audit_head, read_audit_key, read_audit_log, AuditEntry, AuditEvent, AuditHead, AuditLog,
+    AuditLogWriter, AuditRecord,
+};
+pub use dirk::{proto as dirk_proto, DirkClient, DirkSigner, DirkTlsConfig};
+pub use distributed::{
+    load_threshold_signers, parse_threshold_peer_id, threshold_peer_id, ModuleRequest,
+    ModuleRequestKind, PartialSignatureRequest, PartialSignatureResponse, ThresholdSigner,
+    THRESHOLD_PEER_ID,
+};
+pub use ecdsa::{EcdsaSignature, EcdsaSigner};
+pub use pkcs11::{load_pkcs11_signers, Pkcs11Keys, Pkcs11Token, WrappedKey};
+pub use remote::{RemoteSigner, Web3SignerClient, Web3SignerRequest, Web3SignerResponse};
+pub use store::ProxyStore;
+pub use threshold::{aggregate_partial_signatures, split_secret};
+
+/// Signers are deliberately not `Clone` or `Debug`, so that local key material
+/// is never copied around or printed
+pub enum Signer {
+    Local(SecretKey),
+    /// Key held by Dirk, possibly as a distributed account
+    Dirk(DirkSigner),
+    /// Share of a distributed validator key, signing together with the other
+    /// nodes of the cluster
+    Threshold(ThresholdSigner),
+    /// Key held by Web3Signer, which only signs validator registrations
+    Remote(RemoteSigner),
+}
+
+impl Signer {
+    pub fn new_random() -> Result<Self> {
+        Ok(Signer::Local(random_secret()?))
+    }
+
+    pub fn new_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let secret_key = SecretKey::from_bytes(bytes).map_err(BlstErrorWrapper::from)?;
+        Ok(Self::Local(secret_key))
+    }
+
+    /// Secret key bytes, only available for local keys
+    pub fn secret(&self) -> Result<Zeroizing<[u8; 32]>> {
+        match self {
+            Signer::Local(secret) => Ok(Zeroizing::new(secret.to_bytes())),
+            Signer::Dirk(_) | Signer::Threshold(_) | Signer::Remote(_) => {
+                bail!("secret key of remote signer is not available")
+            }
+        }
+    }
+
+    pub fn pubkey(&self) -> BlsPublicKey {
+        match self {
+            Signer::Local(secret) => blst_pubkey_to_alloy(&secret.sk_to_pk()),
+            Signer::Dirk(dirk) => dirk.pubkey,
+            Signer::Threshold(threshold) => threshold.pubkey,
+            Signer::Remote(remote) => remote.pubkey,
+        }
+    }
+
+    /// Signs with the builder domain
+    pub async fn sign(&self, chain: Chain, object_root: &[u8; 32]) -> Result<BlsSignature> {
+        self.sign_with_domain(chain.builder_domain(), object_root).await
+    }
+
+    /// Distributed keys only sign module requests, which are forwarded to the
+    /// peers, see [ThresholdSigner::sign]. Web3Signer keys only sign typed
+    /// messages, see [RemoteSigner::sign_validator_registration]
+    pub async fn sign_with_domain(
+        &self,
+        domain: [u8; 32],
+        object_root: &[u8; 32],
+    ) -> Result<BlsSignature> {
+        match self {
+            Signer::Local(sk) => Ok(sign_message(sk, &compute_signing_root(*object_root, domain))),
+            Signer::Dirk(dirk) => dirk.sign(domain, object_root).await,
+            Signer::Threshold(threshold) => {
+                bail!("{} is a distributed key, which only signs module requests", threshold.pubkey)
+            }
+            Signer::Remote(remote) => {
+                bail!("{} is held by Web3Signer, which only signs typed messages", remote.pubkey)
+            }
+        }
+    }
+
+    pub async fn sign_msg(&self, chain: Chain, msg: &impl TreeHash) -> Result<BlsSignature> {
+        self.sign(chain, &msg.tree_hash_root().0).await
+    }
+}
+
+/// A BLS proxy key, together with the delegation from the consensus key
+pub struct ProxySigner {
+    pub signer: Signer,
+    pub delegation: SignedProxyDelegationBls,
+}
+
+/// An ECDSA proxy key, together with the delegation from the consensus key
+pub struct EcdsaProxySigner {
+    pub signer: EcdsaSigner,
+    pub delegation: SignedProxyDelegationEcdsa,
+}
@@ -0,0 +1,191 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use alloy::{primitives::Bytes, rpc::types::beacon::BlsPublicKey};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    error::{Error as CryptokiError, RvError},
+    mechanism::{aead::GcmParams, Mechanism},
+    object::{Attribute, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use eyre::{bail, ensure, eyre, WrapErr};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+use super::Signer;
+use crate::config::PKCS11_DIR_KEYS_ENV;
+
+const GCM_IV_LEN: usize = 12;
+const GCM_TAG_BITS: u64 = 128;
+
+/// Keys of a PKCS#11 token, e.g. an HSM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Pkcs11Keys {
+    /// BLS keys encrypted with an AES key of the token, which is never
+    /// exported. Keys are decrypted in the signer memory when loaded
+    Wrapped {
+        /// Label of the AES key
+        wrapping_key_label: String,
+        /// Directory of the encrypted keys, one [WrappedKey] JSON file each
+        keys_path: PathBuf,
+    },
+}
+
+impl Pkcs11Keys {
+    /// Overrides the path with the one mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        let Self::Wrapped { wrapping_key_label, keys_path } = self;
+        Self::Wrapped {
+            wrapping_key_label,
+            keys_path: std::env::var(PKCS11_DIR_KEYS_ENV).map(PathBuf::from).unwrap_or(keys_path),
+        }
+    }
+}
+
+/// BLS secret key encrypted with AES-GCM by the token, with the pubkey as
+/// additional data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub pubkey: BlsPublicKey,
+    pub iv: Bytes,
+    /// Encrypted key, followed by the GCM tag
+    pub ciphertext: Bytes,
+}
+
+/// Session on a PKCS#11 token, logged in as the user. Sessions can't be used
+/// concurrently, so signatures with the same token are serialized
+#[derive(Clone)]
+pub struct Pkcs11Token {
+    session: Arc<Mutex<Session>>,
+}
+
+impl Pkcs11Token {
+    /// Loads the PKCS#11 module and logs in the token with the given label
+    pub fn open(module_path: &Path, token_label: &str, pin: &str) -> eyre::Result<Self> {
+        let pkcs11 = Pkcs11::new(module_path)
+            .wrap_err_with(|| format!("failed loading PKCS#11 module {}", module_path.display()))?;
+        match pkcs11.initialize(CInitializeArgs::OsThreads) {
+            Ok(()) | Err(CryptokiError::Pkcs11(RvError::CryptokiAlreadyInitialized, _)) => {}
+            Err(err) => return Err(err).wrap_err("failed initializing PKCS#11 module"),
+        }
+
+        let mut slot = None;
+        for candidate in pkcs11.get_slots_with_token()? {
+            if pkcs11.get_token_info(candidate)?.label() == token_label {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| eyre!("token {token_label} not found"))?;
+
+        let session = pkcs11.open_ro_session(slot)?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.to_owned())))
+            .wrap_err("failed logging in token")?;
+
+        Ok(Self { session: Arc::new(Mutex::new(session)) })
+    }
+
+    /// Decrypts the keys in `keys_path` with the AES key of the token. Keys
+    /// which fail to decrypt are skipped
+    pub fn unwrap_keys(
+        &self,
+        wrapping_key_label: &str,
+        keys_path: &Path,
+    ) -> eyre::Result<Vec<Signer>> {
+        let wrapping_key = self.find_key(ObjectClass::SECRET_KEY, wrapping_key_label)?;
+        let mut signers = Vec::new();
+
+        for entry in fs::read_dir(keys_path)
+            .wrap_err_with(|| format!("failed reading {}", keys_path.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            match self.unwrap_key(wrapping_key, &path) {
+                Ok(signer) => signers.push(signer),
+                Err(err) => warn!(path =% path.display(), ?err, "Failed to unwrap key"),
+            }
+        }
+
+        Ok(signers)
+    }
+
+    fn unwrap_key(&self, wrapping_key: ObjectHandle, path: &Path) -> eyre::Result<Signer> {
+        let wrapped: WrappedKey = serde_json::from_reader(fs::File::open(path)?)?;
+
+        let mut iv = wrapped.iv.to_vec();
+        let params = GcmParams::new(&mut iv, wrapped.pubkey.as_slice(), GCM_TAG_BITS.into())?;
+        let secret = Zeroizing::new(
+            self.session()?
+                .decrypt(&Mechanism::AesGcm(params), wrapping_key, &wrapped.ciphertext)
+                .wrap_err("failed decrypting key")?,
+        );
+
+        let signer = Signer::new_from_bytes(&secret)?;
+        ensure!(signer.pubkey() == wrapped.pubkey, "decrypted key doesn't match pubkey");
+
+        Ok(signer)
+    }
+
+    /// Encrypts a local key with the AES key of the token, to be loaded with
+    /// [Pkcs11Keys::Wrapped]
+    pub fn wrap_key(&self, wrapping_key_label: &str, signer: &Signer) -> eyre::Result<WrappedKey> {
+        let wrapping_key = self.find_key(ObjectClass::SECRET_KEY, wrapping_key_label)?;
+        let pubkey = signer.pubkey();
+
+        let mut iv = [0u8; GCM_IV_LEN];
+        rand::thread_rng().fill_bytes(&mut iv);
+        let mut params_iv = iv;
+        let params = GcmParams::new(&mut params_iv, pubkey.as_slice(), GCM_TAG_BITS.into())?;
+
+        let plaintext = signer.secret()?;
+        let ciphertext = self
+            .session()?
+            .encrypt(&Mechanism::AesGcm(params), wrapping_key, plaintext.as_slice())
+            .wrap_err("failed encrypting key")?;
+
+        Ok(WrappedKey { pubkey, iv: iv.to_vec().into(), ciphertext: ciphertext.into() })
+    }
+
+    fn find_key(&self, class: ObjectClass, label: &str) -> eyre::Result<ObjectHandle> {
+        let template = [Attribute::Class(class), Attribute::Label(label.as_bytes().to_vec())];
+        let objects = self.session()?.find_objects(&template)?;
+
+        match objects.as_slice() {
+            [key] => Ok(*key),
+            [] => bail!("no key with label {label}"),
+            _ => bail!("multiple keys with label {label}"),
+        }
+    }
+
+    fn session(&self) -> eyre::Result<std::sync::MutexGuard<'_, Session>> {
+        self.session.lock().map_err(|_| eyre!("PKCS#11 session lock poisoned"))
+    }
+}
+
+/// Opens the token with the PIN and loads its keys
+pub fn load_pkcs11_signers(
+    module_path: &Path,
+    token_label: &str,
+    pin: &str,
+    keys: Pkcs11Keys,
+) -> eyre::Result<Vec<Signer>> {
+    let token = Pkcs11Token::open(module_path, token_label, pin)?;
+
+    let Pkcs11Keys::Wrapped { wrapping_key_label, keys_path } = keys;
+    let signers = token.unwrap_keys(&wrapping_key_label, &keys_path)?;
+    ensure!(!signers.is_empty(), "no key could be unwrapped from {}", keys_path.display());
+
+    Ok(signers)
+}
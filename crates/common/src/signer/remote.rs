@@ -0,0 +1,124 @@
+use alloy::{
+    primitives::B256,
+    rpc::types::beacon::{relay::ValidatorRegistrationMessage, BlsPublicKey, BlsSignature},
+};
+use eyre::{bail, WrapErr};
+use reqwest::header::{HeaderValue, ACCEPT};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    signature::{compute_signing_root, compute_validator_registration_root, verify_signature},
+    types::Chain,
+    DEFAULT_REQUEST_TIMEOUT,
+};
+
+pub const WEB3SIGNER_PUBKEYS_PATH: &str = "api/v1/eth2/publicKeys";
+pub const WEB3SIGNER_SIGN_PATH: &str = "api/v1/eth2/sign";
+
+/// Sign requests of Web3Signer are typed, it computes the signing root itself
+/// from the object of the given type. Only validator registrations are sent,
+/// since they are the only objects modules can request which Web3Signer knows
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Web3SignerRequest {
+    #[serde(rename_all = "camelCase")]
+    ValidatorRegistration {
+        signing_root: B256,
+        #[serde(rename = "validator_registration")]
+        validator_registration: ValidatorRegistrationMessage,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Web3SignerResponse {
+    pub signature: BlsSignature,
+}
+
+/// Client for a Web3Signer instance
+#[derive(Debug, Clone)]
+pub struct Web3SignerClient {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl Web3SignerClient {
+    pub fn new(url: Url) -> eyre::Result<Self> {
+        let client = reqwest::Client::builder().timeout(DEFAULT_REQUEST_TIMEOUT).build()?;
+        Ok(Self { url, client })
+    }
+
+    /// Keys available in Web3Signer
+    pub async fn get_pubkeys(&self) -> eyre::Result<Vec<BlsPublicKey>> {
+        let url = self.url.join(WEB3SIGNER_PUBKEYS_PATH)?;
+        let res = self.client.get(url).send().await.wrap_err("failed to reach Web3Signer")?;
+
+        let status = res.status();
+        if !status.is_success() {
+            bail!("Web3Signer returned {status} for public keys: {}", res.text().await?);
+        }
+
+        Ok(res.json().await?)
+    }
+
+    /// Requests a signature of a typed object. The returned signature is
+    /// checked against `signing_root`, which is the one Web3Signer is
+    /// expected to compute
+    async fn sign(
+        &self,
+        pubkey: &BlsPublicKey,
+        request: &Web3SignerRequest,
+        signing_root: [u8; 32],
+    ) -> eyre::Result<BlsSignature> {
+        let url = self.url.join(&format!("{WEB3SIGNER_SIGN_PATH}/{pubkey}"))?;
+
+        let res = self
+            .client
+            .post(url)
+            .header(ACCEPT, HeaderValue::from_static("application/json"))
+            .json(request)
+            .send()
+            .await
+            .wrap_err("failed to reach Web3Signer")?;
+
+        let status = res.status();
+        if !status.is_success() {
+            bail!("Web3Signer returned {status} for {pubkey}: {}", res.text().await?);
+        }
+
+        let Web3SignerResponse { signature } = res.json().await?;
+        verify_signature(pubkey, &signing_root, &signature)
+            .wrap_err("invalid signature from Web3Signer")?;
+
+        Ok(signature)
+    }
+}
+
+/// A consensus key held by Web3Signer
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    pub pubkey: BlsPublicKey,
+    pub client: Web3SignerClient,
+}
+
+impl RemoteSigner {
+    /// Signs a registration of this key with the builder domain
+    pub async fn sign_validator_registration(
+        &self,
+        chain: Chain,
+        registration: &ValidatorRegistrationMessage,
+    ) -> eyre::Result<BlsSignature> {
+        if registration.pubkey != self.pubkey {
+            bail!("registration of {} can't be signed by {}", registration.pubkey, self.pubkey);
+        }
+
+        let object_root = compute_validator_registration_root(registration);
+        let signing_root = compute_signing_root(object_root, chain.builder_domain());
+        let request = Web3SignerRequest::ValidatorRegistration {
+            signing_root: signing_root.into(),
+            validator_registration: registration.clone(),
+        };
+
+        self.client.sign(&self.pubkey, &request, signing_root).await
+    }
+}
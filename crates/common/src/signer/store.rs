@@ -0,0 +1,445 @@
+use std::{
+    fs::{self, File, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    primitives::{hex::FromHex, Address},
+    rpc::types::beacon::BlsPublicKey,
+    signers::local::PrivateKeySigner,
+};
+use eth2_keystore::{keypair_from_secret, Keystore, KeystoreBuilder};
+use eyre::{eyre, Context};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use super::{EcdsaProxySigner, EcdsaSigner, ProxySigner, Signer};
+use crate::{
+    commit::request::{
+        SignedProxyDelegationBls, SignedProxyDelegationEcdsa, SignedRevokedProxyBls,
+        SignedRevokedProxyEcdsa,
+    },
+    config::{PROXY_DIR_KEYS_ENV, PROXY_DIR_SECRETS_ENV},
+    utils::{create_private_dir_all, create_private_file, write_private_file},
+};
+
+const KEYSTORE_FILE: &str = "keystore.json";
+const DELEGATION_FILE: &str = "delegation.json";
+const REVOCATION_FILE: &str = "revocation.json";
+const BLS_DIR: &str = "bls";
+const ECDSA_DIR: &str = "ecdsa";
+
+/// Persists proxy keys as encrypted keystores, so that delegations are still
+/// valid after a restart. BLS keys are stored as EIP-2335 keystores, ECDSA keys
+/// as Web3 Secret Storage keystores. The layout is:
+/// - `keys_path/<module_id>/<scheme>/<proxy>/keystore.json`
+/// - `keys_path/<module_id>/<scheme>/<proxy>/delegation.json`
+/// - `keys_path/<module_id>/<scheme>/<proxy>/revocation.json`, if revoked
+/// - `secrets_path/<module_id>/<scheme>/<proxy>`, the keystore password
+///
+/// where `scheme` is `bls` or `ecdsa`, and `proxy` is the BLS pubkey or the
+/// ECDSA address. New files are only readable by the owner. Storing a proxy
+/// builds its keystore with scrypt, which is slow and blocking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyStore {
+    pub keys_path: PathBuf,
+    pub secrets_path: PathBuf,
+}
+
+impl ProxyStore {
+    /// Overrides the paths with the ones mounted in the container, if set
+    pub fn with_env_paths(self) -> Self {
+        Self {
+            keys_path: std::env::var(PROXY_DIR_KEYS_ENV)
+                .map(PathBuf::from)
+                .unwrap_or(self.keys_path),
+            secrets_path: std::env::var(PROXY_DIR_SECRETS_ENV)
+                .map(PathBuf::from)
+                .unwrap_or(self.secrets_path),
+        }
+    }
+
+    pub fn store_bls_proxy(&self, module_id: &str, proxy: &ProxySigner) -> eyre::Result<()> {
+        let (keys_dir, password) =
+            self.prepare(module_id, BLS_DIR, &proxy.signer.pubkey().to_string())?;
+
+        let keypair = keypair_from_secret(proxy.signer.secret()?.as_slice())
+            .map_err(|err| eyre!("{err:?}"))?;
+        let keystore = KeystoreBuilder::new(&keypair, password.as_bytes(), String::new())
+            .and_then(|builder| builder.build())
+            .map_err(|err| eyre!("failed building keystore: {err:?}"))?;
+        keystore
+            .to_json_writer(create_private_file(keys_dir.join(KEYSTORE_FILE))?)
+            .map_err(|err| eyre!("failed writing keystore: {err:?}"))?;
+
+        serde_json::to_writer(
+            create_private_file(keys_dir.join(DELEGATION_FILE))?,
+            &proxy.delegation,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn store_ecdsa_proxy(&self, module_id: &str, proxy: &EcdsaProxySigner) -> eyre::Result<()> {
+        let (keys_dir, password) =
+            self.prepare(module_id, ECDSA_DIR, &proxy.signer.address().to_string())?;
+
+        PrivateKeySigner::encrypt_keystore(
+            &keys_dir,
+            &mut rand::thread_rng(),
+            proxy.signer.secret(),
+            password,
+            Some(KEYSTORE_FILE),
+        )
+        .wrap_err("failed writing keystore")?;
+        // the keystore is written with the default mode
+        fs::set_permissions(keys_dir.join(KEYSTORE_FILE), Permissions::from_mode(0o600))?;
+
+        serde_json::to_writer(
+            create_private_file(keys_dir.join(DELEGATION_FILE))?,
+            &proxy.delegation,
+        )?;
+
+        Ok(())
+    }
+
+    /// Moves the BLS proxies stored before ECDSA proxies were supported, from
+    /// `<module_id>/<proxy>` to `<module_id>/bls/<proxy>` in both paths.
+    /// Returns the number of proxies moved
+    pub fn migrate_legacy_layout(&self) -> eyre::Result<usize> {
+        let mut migrated = 0;
+
+        if !self.keys_path.exists() {
+            return Ok(migrated);
+        }
+
+        for module_entry in fs::read_dir(&self.keys_path)? {
+            let module_path = module_entry?.path();
+            let Some(module_id) = dir_name(&module_path) else {
+                continue;
+            };
+
+            for proxy_entry in fs::read_dir(&module_path)? {
+                let proxy_path = proxy_entry?.path();
+                // only BLS proxies were stored, in directories named by pubkey
+                let Some(proxy) =
+                    dir_name(&proxy_path).filter(|proxy| BlsPublicKey::from_hex(proxy).is_ok())
+                else {
+                    continue;
+                };
+
+                let keys_dir = module_path.join(BLS_DIR);
+                let secrets_dir = self.secrets_path.join(&module_id).join(BLS_DIR);
+                create_private_dir_all(&keys_dir)?;
+                create_private_dir_all(&secrets_dir)?;
+
+                let password_path = self.secrets_path.join(&module_id).join(&proxy);
+                if password_path.exists() {
+                    fs::rename(password_path, secrets_dir.join(&proxy))?;
+                }
+                fs::rename(&proxy_path, keys_dir.join(&proxy))?;
+
+                info!(module_id, proxy, "Moved proxy to the current store layout");
+                migrated += 1;
+            }
+        }
+
+        Ok(migrated)
+    }
+
+    /// Loads all the stored BLS proxies, with the id of the module they belong
+    /// to. Proxies which fail to load are skipped
+    pub fn load_bls_proxies(&self) -> eyre::Result<Vec<(String, ProxySigner)>> {
+        self.load_all(BLS_DIR, |proxy, keys_dir, password| {
+            let pubkey = BlsPublicKey::from_hex(proxy)?;
+
+            let keystore = Keystore::from_json_file(keys_dir.join(KEYSTORE_FILE))
+                .map_err(|err| eyre!("failed reading keystore: {err:?}"))?;
+            let keypair = keystore
+                .decrypt_keypair(&password)
+                .map_err(|err| eyre!("failed decrypting keystore: {err:?}"))?;
+            let signer = Signer::new_from_bytes(keypair.sk.serialize().as_bytes())?;
+
+            let delegation: SignedProxyDelegationBls = read_delegation(keys_dir)?;
+
+            if signer.pubkey() != pubkey || delegation.message.proxy != pubkey {
+                return Err(eyre!("proxy pubkey mismatch"));
+            }
+
+            Ok(ProxySigner { signer, delegation })
+        })
+    }
+
+    /// Loads all the stored ECDSA proxies, with the id of the module they
+    /// belong to. Proxies which fail to load are skipped
+    pub fn load_ecdsa_proxies(&self) -> eyre::Result<Vec<(String, EcdsaProxySigner)>> {
+        self.load_all(ECDSA_DIR, |proxy, keys_dir, password| {
+            let address = Address::from_hex(proxy)?;
+
+            let signer = PrivateKeySigner::decrypt_keystore(keys_dir.join(KEYSTORE_FILE), password)
+                .wrap_err("failed decrypting keystore")?;
+            let signer = EcdsaSigner::Local(signer);
+
+            let delegation: SignedProxyDelegationEcdsa = read_delegation(keys_dir)?;
+
+            if signer.address() != address || delegation.message.proxy != address {
+                return Err(eyre!("proxy address mismatch"));
+            }
+
+            Ok(EcdsaProxySigner { signer, delegation })
+        })
+    }
+
+    /// Saves the revocation of a stored BLS proxy, next to its delegation
+    pub fn store_bls_revocation(
+        &self,
+        module_id: &str,
+        revocation: &SignedRevokedProxyBls,
+    ) -> eyre::Result<()> {
+        self.store_revocation(module_id, BLS_DIR, &revocation.message.proxy.to_string(), revocation)
+    }
+
+    /// Saves the revocation of a stored ECDSA proxy, next to its delegation
+    pub fn store_ecdsa_revocation(
+        &self,
+        module_id: &str,
+        revocation: &SignedRevokedProxyEcdsa,
+    ) -> eyre::Result<()> {
+        self.store_revocation(
+            module_id,
+            ECDSA_DIR,
+            &revocation.message.proxy.to_string(),
+            revocation,
+        )
+    }
+
+    /// Revocation of a stored BLS proxy, if it was revoked
+    pub fn load_bls_revocation(
+        &self,
+        module_id: &str,
+        proxy: &BlsPublicKey,
+    ) -> eyre::Result<Option<SignedRevokedProxyBls>> {
+        self.load_revocation(module_id, BLS_DIR, &proxy.to_string())
+    }
+
+    /// Revocation of a stored ECDSA proxy, if it was revoked
+    pub fn load_ecdsa_revocation(
+        &self,
+        module_id: &str,
+        proxy: &Address,
+    ) -> eyre::Result<Option<SignedRevokedProxyEcdsa>> {
+        self.load_revocation(module_id, ECDSA_DIR, &proxy.to_string())
+    }
+
+    fn store_revocation<T: Serialize>(
+        &self,
+        module_id: &str,
+        scheme: &str,
+        proxy: &str,
+        revocation: &T,
+    ) -> eyre::Result<()> {
+        let keys_dir = self.keys_path.join(module_id).join(scheme).join(proxy);
+        serde_json::to_writer(create_private_file(keys_dir.join(REVOCATION_FILE))?, revocation)?;
+        Ok(())
+    }
+
+    fn load_revocation<T: DeserializeOwned>(
+        &self,
+        module_id: &str,
+        scheme: &str,
+        proxy: &str,
+    ) -> eyre::Result<Option<T>> {
+        let path = self.keys_path.join(module_id).join(scheme).join(proxy).join(REVOCATION_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_reader(File::open(path)?)?))
+    }
+
+    /// Creates the directories for a new proxy and writes a random password
+    /// for its keystore. Returns the keys directory and the password
+    fn prepare(
+        &self,
+        module_id: &str,
+        scheme: &str,
+        proxy: &str,
+    ) -> eyre::Result<(PathBuf, Zeroizing<String>)> {
+        let password: Zeroizing<String> = Zeroizing::new(
+            rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect(),
+        );
+
+        let secrets_dir = self.secrets_path.join(module_id).join(scheme);
+        create_private_dir_all(&secrets_dir)?;
+        write_private_file(secrets_dir.join(proxy), password.as_bytes())?;
+
+        let keys_dir = self.keys_path.join(module_id).join(scheme).join(proxy);
+        create_private_dir_all(&keys_dir)?;
+
+        Ok((keys_dir, password))
+    }
+
+    fn load_all<T>(
+        &self,
+        scheme: &str,
+        load_one: impl Fn(&str, &Path, Zeroizing<Vec<u8>>) -> eyre::Result<T>,
+    ) -> eyre::Result<Vec<(String, T)>> {
+        let mut proxies = Vec::new();
+
+        if !self.keys_path.exists() {
+            return Ok(proxies);
+        }
+
+        for module_entry in fs::read_dir(&self.keys_path)? {
+            let module_path = module_entry?.path();
+            let Some(module_id) = dir_name(&module_path) else {
+                continue;
+            };
+
+            let scheme_path = module_path.join(scheme);
+            if !scheme_path.is_dir() {
+                continue;
+            }
+
+            for proxy_entry in fs::read_dir(&scheme_path)? {
+                let proxy_path = proxy_entry?.path();
+                let Some(proxy) = dir_name(&proxy_path) else {
+                    continue;
+                };
+
+                let res = fs::read(self.secrets_path.join(&module_id).join(scheme).join(&proxy))
+                    .wrap_err("failed reading password")
+                    .and_then(|password| load_one(&proxy, &proxy_path, Zeroizing::new(password)));
+
+                match res {
+                    Ok(signer) => proxies.push((module_id.clone(), signer)),
+                    Err(err) => warn!(module_id, proxy, ?err, "Failed to load proxy"),
+                }
+            }
+        }
+
+        Ok(proxies)
+    }
+}
+
+fn read_delegation<T: DeserializeOwned>(keys_dir: &Path) -> eyre::Result<T> {
+    Ok(serde_json::from_reader(File::open(keys_dir.join(DELEGATION_FILE))?)?)
+}
+
+fn dir_name(path: &Path) -> Option<String> {
+    path.is_dir().then(|| path.file_name()?.to_str().map(String::from)).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use tree_hash::TreeHash;
+
+    use super::ProxyStore;
+    use crate::{
+        commit::request::{
+            ProxyDelegation, RevokedProxy, SignedProxyDelegation, SignedRevokedProxy,
+        },
+        signer::{EcdsaProxySigner, EcdsaSigner, ProxySigner, Signer},
+        types::Chain,
+    };
+
+    #[tokio::test]
+    async fn test_store_and_load_proxy() {
+        let dir = std::env::temp_dir().join(format!("cb_proxy_store_{}", std::process::id()));
+        let store = ProxyStore { keys_path: dir.join("keys"), secrets_path: dir.join("secrets") };
+
+        let chain = Chain::Holesky;
+        let consensus = Signer::new_random().unwrap();
+
+        let signer = Signer::new_random().unwrap();
+        let message = ProxyDelegation::new(consensus.pubkey(), signer.pubkey());
+        let signature = consensus.sign(chain, &message.tree_hash_root().0).await.unwrap();
+        let delegation = SignedProxyDelegation { message, signature };
+        store.store_bls_proxy("TEST_MODULE", &ProxySigner { signer, delegation }).unwrap();
+
+        let ecdsa_signer = EcdsaSigner::new_random();
+        let ecdsa_message = ProxyDelegation::new(consensus.pubkey(), ecdsa_signer.address());
+        let signature = consensus.sign(chain, &ecdsa_message.tree_hash_root().0).await.unwrap();
+        let delegation = SignedProxyDelegation { message: ecdsa_message, signature };
+        store
+            .store_ecdsa_proxy(
+                "TEST_MODULE",
+                &EcdsaProxySigner { signer: ecdsa_signer, delegation },
+            )
+            .unwrap();
+
+        // keys and passwords are only readable by the owner
+        let bls_keys = dir.join("keys/TEST_MODULE/bls").join(message.proxy.to_string());
+        let ecdsa_keys = dir.join("keys/TEST_MODULE/ecdsa").join(ecdsa_message.proxy.to_string());
+        for (path, mode) in [
+            (bls_keys.clone(), 0o700),
+            (bls_keys.join("keystore.json"), 0o600),
+            (ecdsa_keys.join("keystore.json"), 0o600),
+            (dir.join("secrets/TEST_MODULE/bls").join(message.proxy.to_string()), 0o600),
+        ] {
+            assert_eq!(std::fs::metadata(path).unwrap().permissions().mode() & 0o777, mode);
+        }
+
+        let proxies = store.load_bls_proxies().unwrap();
+        let ecdsa_proxies = store.load_ecdsa_proxies().unwrap();
+
+        assert!(store.load_bls_revocation("TEST_MODULE", &message.proxy).unwrap().is_none());
+        let revoked =
+            RevokedProxy { delegator: message.delegator, proxy: message.proxy, revoked_epoch: 1 };
+        let signature = consensus.sign(chain, &revoked.tree_hash_root().0).await.unwrap();
+        let revocation = SignedRevokedProxy { message: revoked, signature };
+        store.store_bls_revocation("TEST_MODULE", &revocation).unwrap();
+        let loaded = store.load_bls_revocation("TEST_MODULE", &message.proxy).unwrap().unwrap();
+        assert!(loaded.validate(chain).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(proxies.len(), 1);
+        let (module_id, proxy) = &proxies[0];
+        assert_eq!(module_id, "TEST_MODULE");
+        assert_eq!(proxy.signer.pubkey(), message.proxy);
+        assert_eq!(proxy.delegation.message.delegator, message.delegator);
+        assert!(proxy.delegation.validate(chain).is_ok());
+
+        assert_eq!(ecdsa_proxies.len(), 1);
+        let (module_id, proxy) = &ecdsa_proxies[0];
+        assert_eq!(module_id, "TEST_MODULE");
+        assert_eq!(proxy.signer.address(), ecdsa_message.proxy);
+        assert!(proxy.delegation.validate(chain).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_legacy_layout() {
+        let dir = std::env::temp_dir().join(format!("cb_proxy_legacy_{}", std::process::id()));
+        let store = ProxyStore { keys_path: dir.join("keys"), secrets_path: dir.join("secrets") };
+
+        let consensus = Signer::new_random().unwrap();
+        let signer = Signer::new_random().unwrap();
+        let message = ProxyDelegation::new(consensus.pubkey(), signer.pubkey());
+        let signature = consensus.sign(Chain::Holesky, &message.tree_hash_root().0).await.unwrap();
+        let delegation = SignedProxyDelegation { message, signature };
+        store.store_bls_proxy("TEST_MODULE", &ProxySigner { signer, delegation }).unwrap();
+
+        // move the proxy to `<module_id>/<proxy>`, as stored by older versions
+        let proxy = message.proxy.to_string();
+        for path in [&store.keys_path, &store.secrets_path] {
+            let module_dir = path.join("TEST_MODULE");
+            std::fs::rename(module_dir.join("bls").join(&proxy), module_dir.join(&proxy)).unwrap();
+            std::fs::remove_dir(module_dir.join("bls")).unwrap();
+        }
+        assert!(store.load_bls_proxies().unwrap().is_empty());
+
+        assert_eq!(store.migrate_legacy_layout().unwrap(), 1);
+        assert_eq!(store.migrate_legacy_layout().unwrap(), 0);
+        let proxies = store.load_bls_proxies().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(proxies.len(), 1);
+        assert_eq!(proxies[0].1.signer.pubkey(), message.proxy);
+    }
+}
@@ -0,0 +1,218 @@
+use alloy::rpc::types::beacon::BlsSignature;
+use blst::{
+    blst_bendian_from_scalar, blst_fr, blst_fr_add, blst_fr_from_scalar, blst_fr_from_uint64,
+    blst_fr_inverse, blst_fr_mul, blst_fr_sub, blst_p2, blst_p2_add_or_double, blst_p2_affine,
+    blst_p2_affine_in_g2, blst_p2_compress, blst_p2_from_affine, blst_p2_mult, blst_p2_uncompress,
+    blst_scalar, blst_scalar_from_bendian, blst_scalar_from_fr, min_pk::SecretKey, BLST_ERROR,
+};
+use eyre::{bail, ensure};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{error::BlstErrorWrapper, signature::random_secret};
+
+/// Splits a secret key in shares for the participants with the given non-zero
+/// ids, so that the partial signatures of any `threshold` of them can be
+/// aggregated in a signature of the whole key
+pub fn split_secret(
+    secret: &SecretKey,
+    threshold: usize,
+    ids: &[u64],
+) -> eyre::Result<Vec<SecretKey>> {
+    ensure!(threshold > 0 && threshold <= ids.len(), "invalid threshold {threshold}");
+
+    // f(x) = secret + a_1 * x + ... + a_{t-1} * x^{t-1}
+    let mut coefficients = vec![secret_to_fr(secret)];
+    for _ in 1..threshold {
+        coefficients.push(secret_to_fr(&random_secret()?));
+    }
+
+    let shares = ids
+        .iter()
+        .map(|id| {
+            ensure!(*id != 0, "participant ids must be non-zero");
+
+            let x = fr_from_u64(*id);
+            let mut y = blst_fr::default();
+            for coefficient in coefficients.iter().rev() {
+                // SAFETY: all pointers are to initialized values
+                unsafe {
+                    blst_fr_mul(&mut y, &y, &x);
+                    blst_fr_add(&mut y, &y, coefficient);
+                }
+            }
+
+            let share = fr_to_secret(&y);
+            y.l.zeroize();
+            share
+        })
+        .collect();
+
+    // the coefficients are enough to recover the secret
+    for coefficient in coefficients.iter_mut() {
+        coefficient.l.zeroize();
+    }
+
+    shares
+}
+
+/// Recovers a threshold BLS signature from the partial signatures of the
+/// participants, with Lagrange interpolation at zero. Participants are
+/// identified by their non-zero id, i.e. the point at which their share of the
+/// secret key was evaluated. At least `threshold` partial signatures are needed
+/// for the result to be valid, which is up to the caller to check
+pub fn aggregate_partial_signatures(
+    partials: &[(u64, BlsSignature)],
+) -> eyre::Result<BlsSignature> {
+    ensure!(!partials.is_empty(), "no partial signatures to aggregate");
+
+    let ids: Vec<u64> = partials.iter().map(|(id, _)| *id).collect();
+    for (i, id) in ids.iter().enumerate() {
+        ensure!(*id != 0, "participant ids must be non-zero");
+        ensure!(!ids[..i].contains(id), "duplicate participant id {id}");
+    }
+
+    let mut aggregate = blst_p2::default();
+
+    for (i, (id, signature)) in partials.iter().enumerate() {
+        let point = signature_to_point(signature)?;
+        let coefficient = lagrange_coefficient(*id, &ids);
+
+        let mut scalar = blst_scalar::default();
+        let mut term = blst_p2::default();
+        // SAFETY: all pointers are to initialized values, blst supports the output
+        // aliasing an input
+        unsafe {
+            blst_scalar_from_fr(&mut scalar, &coefficient);
+            blst_p2_mult(&mut term, &point, scalar.b.as_ptr(), 255);
+
+            if i == 0 {
+                aggregate = term;
+            } else {
+                blst_p2_add_or_double(&mut aggregate, &aggregate, &term);
+            }
+        }
+    }
+
+    let mut out = [0u8; 96];
+    // SAFETY: out is 96 bytes
+    unsafe { blst_p2_compress(out.as_mut_ptr(), &aggregate) };
+
+    Ok(BlsSignature::from_slice(&out))
+}
+
+/// Lagrange basis polynomial of `id` over `ids`, evaluated at zero
+fn lagrange_coefficient(id: u64, ids: &[u64]) -> blst_fr {
+    let x_i = fr_from_u64(id);
+    let mut numerator = fr_from_u64(1);
+    let mut denominator = fr_from_u64(1);
+
+    for x_j in ids.iter().filter(|x_j| **x_j != id).map(|x_j| fr_from_u64(*x_j)) {
+        let mut diff = blst_fr::default();
+        // SAFETY: all pointers are to initialized values
+        unsafe {
+            blst_fr_mul(&mut numerator, &numerator, &x_j);
+            blst_fr_sub(&mut diff, &x_j, &x_i);
+            blst_fr_mul(&mut denominator, &denominator, &diff);
+        }
+    }
+
+    let mut coefficient = blst_fr::default();
+    // SAFETY: all pointers are to initialized values, the denominator is non-zero
+    // as ids are distinct
+    unsafe {
+        blst_fr_inverse(&mut denominator, &denominator);
+        blst_fr_mul(&mut coefficient, &numerator, &denominator);
+    }
+
+    coefficient
+}
+
+fn fr_from_u64(value: u64) -> blst_fr {
+    let mut out = blst_fr::default();
+    // SAFETY: blst reads 4 limbs
+    unsafe { blst_fr_from_uint64(&mut out, [value, 0, 0, 0].as_ptr()) };
+    out
+}
+
+fn secret_to_fr(secret: &SecretKey) -> blst_fr {
+    let bytes = Zeroizing::new(secret.to_bytes());
+    let mut scalar = blst_scalar::default();
+    let mut out = blst_fr::default();
+    // SAFETY: secret bytes are 32 bytes
+    unsafe {
+        blst_scalar_from_bendian(&mut scalar, bytes.as_ptr());
+        blst_fr_from_scalar(&mut out, &scalar);
+    }
+    scalar.b.zeroize();
+    out
+}
+
+fn fr_to_secret(value: &blst_fr) -> eyre::Result<SecretKey> {
+    let mut scalar = blst_scalar::default();
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    // SAFETY: bytes is 32 bytes
+    unsafe {
+        blst_scalar_from_fr(&mut scalar, value);
+        blst_bendian_from_scalar(bytes.as_mut_ptr(), &scalar);
+    }
+    scalar.b.zeroize();
+    Ok(SecretKey::from_bytes(bytes.as_slice()).map_err(BlstErrorWrapper::from)?)
+}
+
+fn signature_to_point(signature: &BlsSignature) -> eyre::Result<blst_p2> {
+    let mut affine = blst_p2_affine::default();
+    let mut point = blst_p2::default();
+
+    // SAFETY: signature is 96 bytes, all pointers are to initialized values
+    unsafe {
+        let res = blst_p2_uncompress(&mut affine, signature.0.as_ptr());
+        if res != BLST_ERROR::BLST_SUCCESS {
+            return Err(BlstErrorWrapper::from(res).into());
+        }
+
+        if !blst_p2_affine_in_g2(&affine) {
+            bail!("partial signature not in group");
+        }
+
+        blst_p2_from_affine(&mut point, &affine);
+    }
+
+    Ok(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aggregate_partial_signatures, split_secret};
+    use crate::{
+        signature::{random_secret, sign_message, verify_signature},
+        utils::blst_pubkey_to_alloy,
+    };
+
+    #[test]
+    fn test_aggregate_partial_signatures() {
+        let secret = random_secret().unwrap();
+        let shares = split_secret(&secret, 3, &[1, 2, 3, 4]).unwrap();
+
+        let msg = [1u8; 32];
+        let expected = sign_message(&secret, &msg);
+        let partial = |id: u64| (id, sign_message(&shares[id as usize - 1], &msg));
+
+        let signature =
+            aggregate_partial_signatures(&[partial(1), partial(2), partial(4)]).unwrap();
+        assert_eq!(signature, expected);
+
+        let signature =
+            aggregate_partial_signatures(&[partial(4), partial(3), partial(2)]).unwrap();
+        assert_eq!(signature, expected);
+
+        let pubkey = blst_pubkey_to_alloy(&secret.sk_to_pk());
+        assert!(verify_signature(&pubkey, &msg, &signature).is_ok());
+
+        // below the threshold
+        let signature = aggregate_partial_signatures(&[partial(1), partial(3)]).unwrap();
+        assert_ne!(signature, expected);
+
+        assert!(aggregate_partial_signatures(&[partial(1), partial(1), partial(2)]).is_err());
+        assert!(aggregate_partial_signatures(&[]).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use crate::constants::{
-    HELDER_BUILDER_DOMAIN, HELDER_FORK_VERSION, HELDER_GENESIS_TIME_SECONDS,
-    HOLESKY_BUILDER_DOMAIN, HOLESKY_FORK_VERSION, HOLESKY_GENESIS_TIME_SECONDS,
-    MAINNET_BUILDER_DOMAIN, MAINNET_FORK_VERSION, MAINNET_GENESIS_TIME_SECONDS,
-    RHEA_BUILDER_DOMAIN, RHEA_FORK_VERSION, RHEA_GENESIS_TIME_SECONDS,
+use crate::{
+    constants::{
+        COMMIT_BOOST_DOMAIN, HELDER_BUILDER_DOMAIN, HELDER_FORK_VERSION,
+        HELDER_GENESIS_TIME_SECONDS, HOLESKY_BUILDER_DOMAIN, HOLESKY_FORK_VERSION,
+        HOLESKY_GENESIS_TIME_SECONDS, MAINNET_BUILDER_DOMAIN, MAINNET_FORK_VERSION,
+        MAINNET_GENESIS_TIME_SECONDS, RHEA_BUILDER_DOMAIN, RHEA_FORK_VERSION,
+        RHEA_GENESIS_TIME_SECONDS,
+    },
+    signature::compute_domain,
 };
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,6 +29,12 @@ impl Chain {
         }
     }
 
+    /// Domain of the signatures requested by commit modules, see
+    /// [crate::signature::compute_module_object_root]
+    pub fn commit_boost_domain(&self) -> [u8; 32] {
+        compute_domain(*self, COMMIT_BOOST_DOMAIN)
+    }
+
     pub fn fork_version(&self) -> [u8; 4] {
         match self {
             Chain::Mainnet => MAINNET_FORK_VERSION,
@@ -1,18 +1,58 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    fs::{DirBuilder, File, OpenOptions},
+    io,
+    net::{IpAddr, Ipv4Addr},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use alloy::{
     primitives::U256,
     rpc::types::beacon::{BlsPublicKey, BlsSignature},
 };
 use blst::min_pk::{PublicKey, Signature};
+use opentelemetry::{
+    trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, TracerProvider,
+    },
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    runtime,
+    trace::{Config, Tracer},
+    Resource,
+};
 use rand::{distributions::Alphanumeric, Rng};
 use reqwest::header::HeaderMap;
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use rolling_file::{BasicRollingFileAppender, RollingConditionBasic};
+use tracing::Span;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    fmt::{
+        self,
+        format::{JsonFields, Writer},
+        time::FormatTime,
+        FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter,
+    },
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+use uuid::Uuid;
 
-use crate::types::Chain;
+use crate::{
+    config::{LogFormat, LogRotation, LogsSettings, OtlpConfig},
+    types::Chain,
+};
 
 const SECONDS_PER_SLOT: u64 = 12;
 const MILLIS_PER_SECOND: u64 = 1_000;
+pub const SLOTS_PER_EPOCH: u64 = 32;
 
 pub fn timestamp_of_slot_start_millis(slot: u64, chain: Chain) -> u64 {
     let seconds_since_genesis = chain.genesis_time_sec() + slot * SECONDS_PER_SLOT;
@@ -22,6 +62,9 @@ pub fn ms_into_slot(slot: u64, chain: Chain) -> u64 {
     let slot_start_ms = timestamp_of_slot_start_millis(slot, chain);
     utcnow_ms().saturating_sub(slot_start_ms)
 }
+pub fn current_slot(chain: Chain) -> u64 {
+    utcnow_sec().saturating_sub(chain.genesis_time_sec()) / SECONDS_PER_SLOT
+}
 
 /// Seconds
 pub fn utcnow_sec() -> u64 {
@@ -109,24 +152,202 @@ pub const fn default_u256() -> U256 {
     U256::ZERO
 }
 
+/// Listen on all the IPv4 interfaces
+pub const fn default_host() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+}
+
 // LOGGING
-// TODO: more customized logging + logging guard
-pub fn initialize_tracing_log() {
-    let level_env = std::env::var("RUST_LOG").unwrap_or("info".to_owned());
+/// Initializes logging to stdout and optionally to rolling files, as
+/// configured in the `[logs]` section of the config file, and trace export if
+/// `[otlp]` is set. The returned guard flushes the file logs when dropped, so
+/// it should be kept alive until the service exits
+pub fn initialize_tracing_log(module_id: &str) -> eyre::Result<Option<WorkerGuard>> {
+    let settings = LogsSettings::load_from_env()?;
 
-    let filter = match level_env.parse::<EnvFilter>() {
-        Ok(f) => f,
+    let level = std::env::var("RUST_LOG").unwrap_or(settings.level_for(module_id).to_owned());
+    let level = match level.parse::<EnvFilter>() {
+        Ok(_) => level,
         Err(_) => {
-            eprintln!("Invalid RUST_LOG value {}, defaulting to info", level_env);
-            EnvFilter::new("info")
+            eprintln!("Invalid log level {}, defaulting to info", level);
+            "info".to_owned()
+        }
+    };
+    // the log level only applies to the log outputs, spans are exported at
+    // OTLP_LEVEL regardless
+    let filter = || EnvFilter::new(&level);
+
+    let mut layers = vec![fmt_layer(settings.format, module_id, std::io::stdout, true)
+        .with_filter(filter())
+        .boxed()];
+
+    let mut guard = None;
+    if let Some(file) = &settings.file {
+        let dir = file.dir();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut condition = match file.rotation {
+            LogRotation::Daily => RollingConditionBasic::new().daily(),
+            LogRotation::Hourly => RollingConditionBasic::new().hourly(),
+            LogRotation::Never => RollingConditionBasic::new(),
+        };
+        if let Some(max_size_mb) = file.max_size_mb {
+            condition = condition.max_size(max_size_mb * 1024 * 1024);
+        }
+
+        let appender = BasicRollingFileAppender::new(
+            dir.join(format!("{module_id}.log")),
+            condition,
+            file.max_files as usize,
+        )?;
+        let (writer, file_guard) = tracing_appender::non_blocking(appender);
+
+        layers.push(
+            fmt_layer(settings.format, module_id, writer, false).with_filter(filter()).boxed(),
+        );
+        guard = Some(file_guard);
+    }
+
+    match OtlpConfig::load_from_env().and_then(|config| {
+        config.map(|config| otlp_layer::<Registry>(&config, module_id)).transpose()
+    }) {
+        Ok(layer) => layers.extend(layer.map(|layer| layer.with_filter(OTLP_LEVEL).boxed())),
+        Err(err) => {
+            eprintln!("Failed to setup OTLP exporter, traces will not be exported: {err}")
         }
     };
 
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(fmt::layer().with_target(false))
-        .try_init()
-        .unwrap();
+    tracing_subscriber::registry().with(layers).try_init()?;
+
+    Ok(guard)
+}
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Most verbose level of the spans exported to the OTLP collector
+const OTLP_LEVEL: LevelFilter = LevelFilter::INFO;
+
+fn fmt_layer<W>(format: LogFormat, module_id: &str, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Pretty => {
+            fmt::layer().with_target(false).with_ansi(ansi).with_writer(writer).boxed()
+        }
+        LogFormat::Json => fmt::layer()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonFormat { module_id: module_id.to_owned() })
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+/// Formats each event as a single line JSON object, with the fields of the
+/// event and of all the spans in scope at the top level. This keeps the field
+/// names stable across services, e.g. `module_id`, `slot`, `relay_id` and
+/// `req_id`. If the same field is set more than once, the innermost wins
+struct JsonFormat {
+    module_id: String,
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    N: for<'w> FormatFields<'w> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> std::fmt::Result {
+        let mut timestamp = String::new();
+        fmt::time::SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+
+        let mut log = serde_json::Map::new();
+        log.insert("timestamp".into(), timestamp.into());
+        log.insert("level".into(), event.metadata().level().as_str().into());
+        log.insert("module_id".into(), self.module_id.clone().into());
+
+        // span fields are already formatted as JSON objects by `JsonFields`
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>() {
+                    extend_json_fields(&mut log, fields)?;
+                }
+            }
+        }
+
+        let mut fields = String::new();
+        ctx.format_fields(Writer::new(&mut fields), event)?;
+        extend_json_fields(&mut log, &fields)?;
+
+        let line = serde_json::to_string(&log).map_err(|_| std::fmt::Error)?;
+        writeln!(writer, "{line}")
+    }
+}
+
+fn extend_json_fields(
+    log: &mut serde_json::Map<String, serde_json::Value>,
+    fields: &str,
+) -> std::fmt::Result {
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(fields).map_err(|_| std::fmt::Error)?;
+    log.extend(fields);
+    Ok(())
+}
+
+/// Creates a layer exporting spans to an OTLP collector, in batches. The
+/// tracer provider is also set as the global one, so that pending spans can be
+/// flushed with [`opentelemetry::global::shutdown_tracer_provider`]
+pub fn otlp_layer<S>(
+    config: &OtlpConfig,
+    service_name: &str,
+) -> eyre::Result<OpenTelemetryLayer<S, Tracer>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter =
+        opentelemetry_otlp::new_exporter().http().with_endpoint(config.traces_url()?.as_str());
+
+    let provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(Config::default().with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            service_name.to_owned(),
+        )])))
+        .install_batch(runtime::Tokio)?;
+
+    let tracer = provider.tracer("commit-boost");
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Sets the parent of the span to a remote span with the slot uuid as trace
+/// id. This groups the `get_header` and `submit_block` calls of the same slot,
+/// and the relay requests they make, in a single trace
+pub fn set_slot_trace_parent(span: &Span, slot_uuid: Uuid) {
+    let bytes = slot_uuid.into_bytes();
+    let mut span_id = [0; 8];
+    span_id.copy_from_slice(&bytes[8..]);
+
+    let span_context = SpanContext::new(
+        TraceId::from_bytes(bytes),
+        SpanId::from_bytes(span_id),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+
+    span.set_parent(opentelemetry::Context::new().with_remote_span_context(span_context));
 }
 
 pub fn print_logo() {
@@ -165,3 +386,77 @@ pub fn get_user_agent(req_headers: &HeaderMap) -> Option<String> {
         .get(reqwest::header::USER_AGENT)
         .and_then(|ua| ua.to_str().ok().map(|s| s.to_string()))
 }
+
+// Files
+/// Creates or truncates a file only readable by the owner, for keys and
+/// secrets. The mode of an existing file is not changed
+pub fn create_private_file(path: impl AsRef<Path>) -> io::Result<File> {
+    OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)
+}
+
+/// Writes a file only readable by the owner, see [`create_private_file`]
+pub fn write_private_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    io::Write::write_all(&mut create_private_file(path)?, contents.as_ref())
+}
+
+/// Creates a directory and its missing parents, only accessible by the owner
+pub fn create_private_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::fmt_layer;
+    use crate::config::LogFormat;
+
+    #[derive(Clone, Default)]
+    struct TestWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_log_fields() {
+        let writer = TestWriter::default();
+        let make_writer = writer.clone();
+        let subscriber = tracing_subscriber::registry().with(fmt_layer(
+            LogFormat::Json,
+            "pbs",
+            move || make_writer.clone(),
+            false,
+        ));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("get_header", req_id = "abc", slot = 1);
+            let _enter = span.enter();
+            let relay_span = tracing::info_span!("handler", relay_id = "relay");
+            let _enter = relay_span.enter();
+            tracing::info!(latency_ms = 10, "received header");
+        });
+
+        let output = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        let log: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+
+        assert_eq!(log["level"], "INFO");
+        assert_eq!(log["module_id"], "pbs");
+        assert_eq!(log["req_id"], "abc");
+        assert_eq!(log["slot"], 1);
+        assert_eq!(log["relay_id"], "relay");
+        assert_eq!(log["latency_ms"], 10);
+        assert_eq!(log["message"], "received header");
+    }
+}
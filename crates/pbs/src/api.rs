@@ -32,11 +32,12 @@ pub trait BuilderApi<S: BuilderApiState>: 'static {
     }
 
     /// https://ethereum.github.io/builder-specs/#/Builder/submitBlindedBlock
+    /// Returns the payload and the id of the relay which delivered it
     async fn submit_block(
         signed_blinded_block: SignedBlindedBeaconBlock,
         req_headers: HeaderMap,
         state: PbsState<S>,
-    ) -> eyre::Result<SubmitBlindedBlockResponse> {
+    ) -> eyre::Result<(SubmitBlindedBlockResponse, String)> {
         mev_boost::submit_block(signed_blinded_block, req_headers, state).await
     }
 
@@ -91,3 +91,20 @@ pub enum ValidationError {
     #[error("failed signature verification: {0:?}")]
     Sigverify(#[from] BlstErrorWrapper),
 }
+
+impl ValidationError {
+    /// Short identifier of the error, used as a metric label
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ValidationError::EmptyBlockhash => "empty_blockhash",
+            ValidationError::PubkeyMismatch { .. } => "pubkey_mismatch",
+            ValidationError::ParentHashMismatch { .. } => "parent_hash_mismatch",
+            ValidationError::BlockHashMismatch { .. } => "block_hash_mismatch",
+            ValidationError::KzgCommitments { .. } => "kzg_commitments",
+            ValidationError::KzgMismatch { .. } => "kzg_mismatch",
+            ValidationError::BidTooLow { .. } => "bid_too_low",
+            ValidationError::EmptyTxRoot => "empty_tx_root",
+            ValidationError::Sigverify(_) => "sigverify",
+        }
+    }
+}
@@ -3,12 +3,17 @@
 //! - what PBS receives from relays
 //! - what PBS returns to the beacon node
 
+use alloy::primitives::U256;
+use cb_common::utils::wei_to_eth;
 use lazy_static::lazy_static;
 use prometheus::{
-    register_histogram_vec_with_registry, register_int_counter_vec_with_registry, HistogramVec,
-    IntCounterVec, Registry,
+    register_counter_vec_with_registry, register_histogram_vec_with_registry,
+    register_histogram_with_registry, register_int_counter_vec_with_registry, CounterVec,
+    Histogram, HistogramVec, IntCounterVec, Registry,
 };
 
+use crate::error::ValidationError;
+
 lazy_static! {
     pub static ref PBS_METRICS_REGISTRY: Registry =
         Registry::new_custom(Some("cb_pbs".to_string()), None).unwrap();
@@ -32,6 +37,82 @@ lazy_static! {
     )
     .unwrap();
 
+    /// Responses from relays that failed validation, by error type
+    pub static ref RELAY_VALIDATION_ERRORS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "relay_validation_error_total",
+        "Relay responses that failed validation",
+        &["error", "endpoint", "relay_id"],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    // BIDS
+    /// Value of the valid bids received, in ETH
+    pub static ref RELAY_BID_VALUE: HistogramVec = register_histogram_vec_with_registry!(
+        "relay_bid_value",
+        "Value of valid bids received by relay, in ETH",
+        &["relay_id"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Number of valid bids received for each get_header
+    pub static ref BIDS_PER_SLOT: Histogram = register_histogram_with_registry!(
+        "bids_per_slot",
+        "Number of valid bids received from relays in a slot",
+        vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 15.0, 20.0],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Relay which sent the best bid for each get_header
+    pub static ref RELAY_AUCTION_WINS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "relay_auction_win_total",
+        "Number of times a relay sent the best bid",
+        &["relay_id"],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Difference between the best and the second best bid, in ETH
+    pub static ref BID_MARGIN: Histogram = register_histogram_with_registry!(
+        "bid_margin",
+        "Difference between the best and second best bid, in ETH",
+        vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    // PAYLOADS
+    /// Payloads delivered, by the relay whose payload was returned to the
+    /// beacon node
+    pub static ref RELAY_PAYLOAD_DELIVERED: IntCounterVec = register_int_counter_vec_with_registry!(
+        "relay_payload_delivered_total",
+        "Payloads delivered by relay",
+        &["relay_id"],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Value of the delivered payloads, in ETH
+    pub static ref RELAY_PAYLOAD_DELIVERED_VALUE: CounterVec = register_counter_vec_with_registry!(
+        "relay_payload_delivered_value_total",
+        "Total value of the payloads delivered by relay, in ETH",
+        &["relay_id"],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Payloads missed, by relays which sent the corresponding header
+    pub static ref RELAY_PAYLOAD_MISSED: IntCounterVec = register_int_counter_vec_with_registry!(
+        "relay_payload_missed_total",
+        "Payloads missed by relay",
+        &["relay_id"],
+        PBS_METRICS_REGISTRY
+    )
+    .unwrap();
+
     // TO BEACON NODE
     /// Status code returned to beacon node by endpoint
     pub static ref BEACON_NODE_STATUS: IntCounterVec = register_int_counter_vec_with_registry!(
@@ -41,3 +122,78 @@ lazy_static! {
         PBS_METRICS_REGISTRY
     ).unwrap();
 }
+
+/// Records how many bids were received, which relay sent the best one and by
+/// how much it beat the second best
+pub fn record_auction(relay_bids: &[(&str, U256)]) {
+    BIDS_PER_SLOT.observe(relay_bids.len() as f64);
+
+    let mut values = relay_bids.to_vec();
+    values.sort_by(|a, b| b.1.cmp(&a.1));
+
+    if let Some((winner, best)) = values.first() {
+        RELAY_AUCTION_WINS.with_label_values(&[winner]).inc();
+
+        if let Some((_, second)) = values.get(1) {
+            BID_MARGIN.observe(wei_to_eth(&(*best - *second)));
+        }
+    }
+}
+
+/// Records a relay response which failed validation
+pub fn record_validation_error(err: &ValidationError, endpoint: &str, relay_id: &str) {
+    RELAY_VALIDATION_ERRORS.with_label_values(&[err.tag(), endpoint, relay_id]).inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use cb_common::utils::eth_to_wei;
+
+    use super::*;
+
+    #[test]
+    fn test_record_auction() {
+        let count = BIDS_PER_SLOT.get_sample_count();
+        let margins = BID_MARGIN.get_sample_count();
+        let margin_sum = BID_MARGIN.get_sample_sum();
+
+        record_auction(&[
+            ("auction_a", eth_to_wei(0.1)),
+            ("auction_b", eth_to_wei(0.3)),
+            ("auction_c", eth_to_wei(0.2)),
+        ]);
+
+        assert_eq!(BIDS_PER_SLOT.get_sample_count(), count + 1);
+        assert_eq!(RELAY_AUCTION_WINS.with_label_values(&["auction_a"]).get(), 0);
+        assert_eq!(RELAY_AUCTION_WINS.with_label_values(&["auction_b"]).get(), 1);
+        assert_eq!(RELAY_AUCTION_WINS.with_label_values(&["auction_c"]).get(), 0);
+        assert_eq!(BID_MARGIN.get_sample_count(), margins + 1);
+        assert!((BID_MARGIN.get_sample_sum() - margin_sum - 0.1).abs() < 1e-9);
+
+        // a single bid wins without a margin
+        record_auction(&[("auction_a", U256::from(1))]);
+
+        assert_eq!(BIDS_PER_SLOT.get_sample_count(), count + 2);
+        assert_eq!(RELAY_AUCTION_WINS.with_label_values(&["auction_a"]).get(), 1);
+        assert_eq!(BID_MARGIN.get_sample_count(), margins + 1);
+
+        // no bids still counts the slot
+        record_auction(&[]);
+
+        assert_eq!(BIDS_PER_SLOT.get_sample_count(), count + 3);
+    }
+
+    #[test]
+    fn test_record_validation_error() {
+        record_validation_error(&ValidationError::EmptyBlockhash, "get_header", "validation_a");
+        record_validation_error(&ValidationError::EmptyBlockhash, "get_header", "validation_a");
+        record_validation_error(&ValidationError::EmptyTxRoot, "submit_block", "validation_a");
+
+        let count = |tag, endpoint| {
+            RELAY_VALIDATION_ERRORS.with_label_values(&[tag, endpoint, "validation_a"]).get()
+        };
+        assert_eq!(count("empty_blockhash", "get_header"), 2);
+        assert_eq!(count("empty_tx_root", "submit_block"), 1);
+        assert_eq!(count("empty_blockhash", "submit_block"), 0);
+    }
+}
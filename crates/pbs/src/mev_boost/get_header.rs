@@ -13,17 +13,19 @@ use cb_common::{
     },
     signature::verify_signed_builder_message,
     types::Chain,
-    utils::{get_user_agent, ms_into_slot, utcnow_ms},
+    utils::{get_user_agent, ms_into_slot, utcnow_ms, wei_to_eth},
 };
 use futures::future::join_all;
 use reqwest::{header::USER_AGENT, StatusCode};
 use tokio::time::sleep;
-use tracing::{debug, error, warn, Instrument};
+use tracing::{debug, error, field::Empty, warn, Instrument, Span};
 
 use crate::{
     constants::{GET_HEADER_ENDPOINT_TAG, TIMEOUT_ERROR_CODE, TIMEOUT_ERROR_CODE_STR},
     error::{PbsError, ValidationError},
-    metrics::{RELAY_LATENCY, RELAY_STATUS_CODE},
+    metrics::{
+        record_auction, record_validation_error, RELAY_BID_VALUE, RELAY_LATENCY, RELAY_STATUS_CODE,
+    },
     state::{BuilderApiState, PbsState},
 };
 
@@ -79,17 +81,22 @@ pub async fn get_header<S: BuilderApiState>(
         let relay_id = relays[i].id.as_ref();
 
         match res {
-            Ok(Some(res)) => relay_bids.push(res),
+            Ok(Some(res)) => {
+                RELAY_BID_VALUE.with_label_values(&[relay_id]).observe(wei_to_eth(&res.value()));
+                relay_bids.push((relay_id, res))
+            }
             Ok(_) => {}
             Err(err) if err.is_timeout() => error!(err = "Timed Out", relay_id),
             Err(err) => error!(?err, relay_id),
         }
     }
 
-    Ok(state.add_bids(params.slot, relay_bids))
+    record_auction(&relay_bids.iter().map(|(id, bid)| (*id, bid.value())).collect::<Vec<_>>());
+
+    Ok(state.add_bids(params.slot, relay_bids.into_iter().map(|(_, bid)| bid).collect()))
 }
 
-#[tracing::instrument(skip_all, name = "handler", fields(relay_id = relay.id.as_ref()))]
+#[tracing::instrument(skip_all, name = "handler", fields(relay_id = relay.id.as_ref(), status_code = Empty, latency_ms = Empty))]
 async fn send_timed_get_header(
     params: GetHeaderParams,
     relay: RelayClient,
@@ -238,6 +245,9 @@ async fn send_one_get_header(
         .observe(request_latency.as_secs_f64());
 
     let code = res.status();
+    Span::current()
+        .record("status_code", code.as_u16())
+        .record("latency_ms", request_latency.as_millis() as u64);
     RELAY_STATUS_CODE.with_label_values(&[code.as_str(), GET_HEADER_ENDPOINT_TAG, &relay.id]).inc();
 
     let response_bytes = res.bytes().await?;
@@ -274,7 +284,8 @@ async fn send_one_get_header(
         params.parent_hash,
         skip_sigverify,
         min_bid_wei,
-    )?;
+    )
+    .inspect_err(|err| record_validation_error(err, GET_HEADER_ENDPOINT_TAG, &relay.id))?;
 
     Ok((start_request_time, Some(get_header_response)))
 }
@@ -10,21 +10,22 @@ use cb_common::{
 };
 use futures::future::select_ok;
 use reqwest::header::USER_AGENT;
-use tracing::{debug, warn};
+use tracing::{debug, field::Empty, warn, Span};
 
 use crate::{
     constants::{SUBMIT_BLINDED_BLOCK_ENDPOINT_TAG, TIMEOUT_ERROR_CODE_STR},
     error::{PbsError, ValidationError},
-    metrics::{RELAY_LATENCY, RELAY_STATUS_CODE},
+    metrics::{record_validation_error, RELAY_LATENCY, RELAY_STATUS_CODE},
     state::{BuilderApiState, PbsState},
 };
 
 /// Implements https://ethereum.github.io/builder-specs/#/Builder/submitBlindedBlock
+/// Returns the payload together with the id of the relay which delivered it
 pub async fn submit_block<S: BuilderApiState>(
     signed_blinded_block: SignedBlindedBeaconBlock,
     req_headers: HeaderMap,
     state: PbsState<S>,
-) -> eyre::Result<SubmitBlindedBlockResponse> {
+) -> eyre::Result<(SubmitBlindedBlockResponse, String)> {
     let (_, slot_uuid) = state.get_slot_and_uuid();
 
     // prepare headers
@@ -35,27 +36,23 @@ pub async fn submit_block<S: BuilderApiState>(
         send_headers.insert(USER_AGENT, HeaderValue::from_str(&ua)?);
     }
 
+    let timeout_ms = state.config.pbs_config.timeout_get_payload_ms;
+
     let relays = state.relays();
     let mut handles = Vec::with_capacity(relays.len());
     for relay in relays.iter() {
-        handles.push(Box::pin(send_submit_block(
-            &signed_blinded_block,
-            relay,
-            send_headers.clone(),
-            state.config.pbs_config.timeout_get_payload_ms,
-        )));
+        let send =
+            send_submit_block(&signed_blinded_block, relay, send_headers.clone(), timeout_ms);
+        handles.push(Box::pin(async move { send.await.map(|res| (res, relay)) }));
     }
 
-    let results = select_ok(handles).await;
-    match results {
-        Ok((res, _)) => Ok(res),
-        Err(err) => Err(err.into()),
-    }
+    let ((res, relay), _) = select_ok(handles).await?;
+    Ok((res, relay.id.to_string()))
 }
 
 // submits blinded signed block and expects the execution payload + blobs bundle
 // back
-#[tracing::instrument(skip_all, name = "handler", fields(relay_id = relay.id.as_ref()))]
+#[tracing::instrument(skip_all, name = "handler", fields(relay_id = relay.id.as_ref(), status_code = Empty, latency_ms = Empty))]
 async fn send_submit_block(
     signed_blinded_block: &SignedBlindedBeaconBlock,
     relay: &RelayClient,
@@ -92,6 +89,9 @@ async fn send_submit_block(
         .observe(request_latency.as_secs_f64());
 
     let code = res.status();
+    Span::current()
+        .record("status_code", code.as_u16())
+        .record("latency_ms", request_latency.as_millis() as u64);
     RELAY_STATUS_CODE
         .with_label_values(&[code.as_str(), SUBMIT_BLINDED_BLOCK_ENDPOINT_TAG, &relay.id])
         .inc();
@@ -116,11 +116,24 @@ async fn send_submit_block(
         "received unblinded block"
     );
 
+    validate_unblinded_block(signed_blinded_block, &block_response).inspect_err(|err| {
+        record_validation_error(err, SUBMIT_BLINDED_BLOCK_ENDPOINT_TAG, &relay.id)
+    })?;
+
+    Ok(block_response)
+}
+
+/// Checks that the payload received from the relay matches the blinded block
+/// that was signed
+fn validate_unblinded_block(
+    signed_blinded_block: &SignedBlindedBeaconBlock,
+    block_response: &SubmitBlindedBlockResponse,
+) -> Result<(), ValidationError> {
     if signed_blinded_block.block_hash() != block_response.block_hash() {
-        return Err(PbsError::Validation(ValidationError::BlockHashMismatch {
+        return Err(ValidationError::BlockHashMismatch {
             expected: signed_blinded_block.block_hash(),
             got: block_response.block_hash(),
-        }))
+        })
     }
 
     if let Some(blobs) = &block_response.data.blobs_bundle {
@@ -129,25 +142,25 @@ async fn send_submit_block(
             expected_committments.len() != blobs.commitments.len() ||
             expected_committments.len() != blobs.proofs.len()
         {
-            return Err(PbsError::Validation(ValidationError::KzgCommitments {
+            return Err(ValidationError::KzgCommitments {
                 expected_blobs: expected_committments.len(),
                 got_blobs: blobs.blobs.len(),
                 got_commitments: blobs.commitments.len(),
                 got_proofs: blobs.proofs.len(),
-            }))
+            })
         }
 
         for (i, comm) in expected_committments.iter().enumerate() {
             // this is safe since we already know they are the same length
             if *comm != blobs.commitments[i] {
-                return Err(PbsError::Validation(ValidationError::KzgMismatch {
+                return Err(ValidationError::KzgMismatch {
                     expected: format!("{comm}"),
                     got: format!("{}", blobs.commitments[i]),
                     index: i,
-                }))
+                })
             }
         }
     }
 
-    Ok(block_response)
+    Ok(())
 }
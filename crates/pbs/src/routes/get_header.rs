@@ -6,10 +6,10 @@ use axum::{
 };
 use cb_common::{
     pbs::{BuilderEvent, GetHeaderParams},
-    utils::{get_user_agent, ms_into_slot},
+    utils::{get_user_agent, ms_into_slot, set_slot_trace_parent},
 };
 use reqwest::StatusCode;
-use tracing::{error, info};
+use tracing::{error, info, Span};
 use uuid::Uuid;
 
 use crate::{
@@ -27,7 +27,8 @@ pub async fn handle_get_header<S: BuilderApiState, T: BuilderApi<S>>(
     Path(params): Path<GetHeaderParams>,
 ) -> Result<impl IntoResponse, PbsClientError> {
     state.publish_event(BuilderEvent::GetHeaderRequest(params));
-    state.get_or_update_slot_uuid(params.slot);
+    let slot_uuid = state.get_or_update_slot_uuid(params.slot);
+    set_slot_trace_parent(&Span::current(), slot_uuid);
 
     let ua = get_user_agent(&req_headers);
     let ms_into_slot = ms_into_slot(params.slot, state.config.chain);
@@ -1,17 +1,24 @@
+use alloy::primitives::B256;
 use axum::{extract::State, http::HeaderMap, response::IntoResponse, Json};
 use cb_common::{
     pbs::{BuilderEvent, SignedBlindedBeaconBlock},
-    utils::{get_user_agent, timestamp_of_slot_start_millis, utcnow_ms},
+    utils::{
+        get_user_agent, set_slot_trace_parent, timestamp_of_slot_start_millis, utcnow_ms,
+        wei_to_eth,
+    },
 };
 use reqwest::StatusCode;
-use tracing::{error, info, trace, warn};
+use tracing::{error, info, trace, warn, Span};
 use uuid::Uuid;
 
 use crate::{
     api::BuilderApi,
     constants::SUBMIT_BLINDED_BLOCK_ENDPOINT_TAG,
     error::PbsClientError,
-    metrics::BEACON_NODE_STATUS,
+    metrics::{
+        BEACON_NODE_STATUS, RELAY_PAYLOAD_DELIVERED, RELAY_PAYLOAD_DELIVERED_VALUE,
+        RELAY_PAYLOAD_MISSED,
+    },
     state::{BuilderApiState, PbsState},
 };
 
@@ -30,6 +37,7 @@ pub async fn handle_submit_block<S: BuilderApiState, T: BuilderApi<S>>(
     let slot_start_ms = timestamp_of_slot_start_millis(slot, state.config.chain);
     let ua = get_user_agent(&req_headers);
     let (curr_slot, slot_uuid) = state.get_slot_and_uuid();
+    set_slot_trace_parent(&Span::current(), slot_uuid);
 
     info!(?ua, %slot_uuid, ms_into_slot=now.saturating_sub(slot_start_ms), %block_hash);
 
@@ -38,24 +46,29 @@ pub async fn handle_submit_block<S: BuilderApiState, T: BuilderApi<S>>(
     }
 
     match T::submit_block(signed_blinded_block, req_headers, state.clone()).await {
-        Ok(res) => {
+        Ok((res, relay_id)) => {
             trace!(?res);
             state.publish_event(BuilderEvent::SubmitBlockResponse(Box::new(res.clone())));
-            info!("received unblinded block");
+            info!(%relay_id, "received unblinded block");
+
+            // only the relay whose payload is returned is credited
+            RELAY_PAYLOAD_DELIVERED.with_label_values(&[&relay_id]).inc();
+            if let Some(value) = state.get_bid_value_by_block_hash(slot, block_hash) {
+                RELAY_PAYLOAD_DELIVERED_VALUE
+                    .with_label_values(&[&relay_id])
+                    .inc_by(wei_to_eth(&value));
+            }
 
             BEACON_NODE_STATUS.with_label_values(&["200", SUBMIT_BLINDED_BLOCK_ENDPOINT_TAG]).inc();
             Ok((StatusCode::OK, Json(res).into_response()))
         }
 
         Err(err) => {
-            if let Some(fault_pubkeys) = state.get_relays_by_block_hash(slot, block_hash) {
-                let fault_relays = state
-                    .relays()
-                    .iter()
-                    .filter(|relay| fault_pubkeys.contains(&relay.pubkey()))
-                    .map(|relay| &**relay.id)
-                    .collect::<Vec<_>>()
-                    .join(",");
+            if let Some(fault_relay_ids) = relay_ids_by_block_hash(&state, slot, block_hash) {
+                for relay_id in fault_relay_ids.iter() {
+                    RELAY_PAYLOAD_MISSED.with_label_values(&[relay_id]).inc();
+                }
+                let fault_relays = fault_relay_ids.join(",");
 
                 error!(?err, %block_hash, fault_relays, "CRITICAL: no payload received from relays");
                 state.publish_event(BuilderEvent::MissedPayload {
@@ -78,3 +91,19 @@ pub async fn handle_submit_block<S: BuilderApiState, T: BuilderApi<S>>(
         }
     }
 }
+
+/// Ids of the relays which sent a header for the given block hash
+fn relay_ids_by_block_hash<S: BuilderApiState>(
+    state: &PbsState<S>,
+    slot: u64,
+    block_hash: B256,
+) -> Option<Vec<&str>> {
+    state.get_relays_by_block_hash(slot, block_hash).map(|pubkeys| {
+        state
+            .relays()
+            .iter()
+            .filter(|relay| pubkeys.contains(&relay.pubkey()))
+            .map(|relay| &**relay.id)
+            .collect()
+    })
+}
@@ -1,5 +1,4 @@
-use std::net::SocketAddr;
-
+use cb_common::{config::TlsConfig, server::serve};
 use cb_metrics::provider::MetricsProvider;
 use eyre::Result;
 use prometheus::core::Collector;
@@ -23,16 +22,24 @@ impl PbsService {
         //     PbsService::relay_check(state.relays()).await;
         // }
 
-        let address = SocketAddr::from(([0, 0, 0, 0], state.config.pbs_config.port));
+        let address = state.config.pbs_config.listen_address().expect("invalid listen address");
+        let tls = state
+            .config
+            .pbs_config
+            .tls
+            .as_ref()
+            .map(TlsConfig::server_config)
+            .transpose()
+            .expect("failed loading TLS certificates");
         let events_subs =
             state.config.event_publiher.as_ref().map(|e| e.n_subscribers()).unwrap_or_default();
         let app = create_app_router::<S, T>(state);
 
-        info!(?address, events_subs, "Starting PBS service");
+        info!(?address, events_subs, tls = tls.is_some(), "Starting PBS service");
 
         let listener = TcpListener::bind(address).await.expect("failed tcp binding");
 
-        if let Err(err) = axum::serve(listener, app).await {
+        if let Err(err) = serve(listener, tls, app).await {
             error!(?err, "Pbs server exited")
         }
     }
@@ -4,7 +4,10 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use alloy::{primitives::B256, rpc::types::beacon::BlsPublicKey};
+use alloy::{
+    primitives::{B256, U256},
+    rpc::types::beacon::BlsPublicKey,
+};
 use cb_common::{
     config::{PbsConfig, PbsModuleConfig},
     pbs::{BuilderEvent, GetHeaderReponse, RelayClient},
@@ -103,6 +106,14 @@ where
         })
     }
 
+    /// Retrieves the value of the bid for a given block hash
+    /// Returns None if we dont have bids for the slot or for the block hash
+    pub fn get_bid_value_by_block_hash(&self, slot: u64, block_hash: B256) -> Option<U256> {
+        self.bid_cache.get(&slot).and_then(|bids| {
+            bids.iter().find(|&bid| bid.block_hash() == block_hash).map(|bid| bid.value())
+        })
+    }
+
     /// Clear bids which are more than ~3 minutes old
     fn clear(&self, last_slot: u64) {
         self.bid_cache.retain(|slot, _| last_slot.saturating_sub(*slot) < 15)
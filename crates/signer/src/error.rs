@@ -1,4 +1,4 @@
-use alloy::rpc::types::beacon::BlsPublicKey;
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -18,17 +18,69 @@ pub enum SignerModuleError {
 
     #[error("unknown proxy signer: {0}")]
     UnknownProxySigner(BlsPublicKey),
+
+    #[error("unknown ecdsa proxy signer: {0}")]
+    UnknownEcdsaProxySigner(Address),
+
+    #[error("module {0} is not allowed to sign with the builder domain")]
+    BuilderDomainNotAllowed(String),
+
+    #[error("consensus key not allowed by the module policy: {0}")]
+    KeyNotAllowed(BlsPublicKey),
+
+    #[error("module {0} can only sign with proxy keys")]
+    ProxyOnly(String),
+
+    #[error("outside of signing window: {0}")]
+    OutsideSigningWindow(String),
+
+    #[error("message not allowed by the module policy: {0}")]
+    MessageNotAllowed(String),
+
+    #[error("proxy {0} was revoked")]
+    ProxyRevoked(String),
+
+    #[error("proxy {0} expired")]
+    ProxyExpired(String),
+
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("too many requests in batch: {0}, max {1}")]
+    BatchTooLarge(usize, usize),
+
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
 }
 
-impl IntoResponse for SignerModuleError {
-    fn into_response(self) -> Response {
-        let status = match self {
+impl SignerModuleError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
             SignerModuleError::Unauthorized => StatusCode::UNAUTHORIZED,
             SignerModuleError::UnknownModuleId(_) => StatusCode::NOT_FOUND,
             SignerModuleError::UnknownConsensusSigner(_) => StatusCode::NOT_FOUND,
             SignerModuleError::UnknownProxySigner(_) => StatusCode::NOT_FOUND,
-        };
+            SignerModuleError::UnknownEcdsaProxySigner(_) => StatusCode::NOT_FOUND,
+            SignerModuleError::BuilderDomainNotAllowed(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::KeyNotAllowed(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::ProxyOnly(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::OutsideSigningWindow(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::MessageNotAllowed(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::ProxyRevoked(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::ProxyExpired(_) => StatusCode::FORBIDDEN,
+            SignerModuleError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            SignerModuleError::BatchTooLarge(..) => StatusCode::BAD_REQUEST,
+            SignerModuleError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            SignerModuleError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
-        (status, self.to_string()).into_response()
+impl IntoResponse for SignerModuleError {
+    fn into_response(self) -> Response {
+        (self.status_code(), self.to_string()).into_response()
     }
 }
@@ -0,0 +1,67 @@
+use cb_common::config::SignerHardeningConfig;
+use eyre::bail;
+use tracing::info;
+
+/// Applies the process level protections of the key material. Must run before
+/// any key is loaded, so that no secret is ever dumped or swapped
+pub fn harden_process(config: SignerHardeningConfig) -> eyre::Result<()> {
+    if config.disable_core_dumps {
+        disable_core_dumps()?;
+        info!("Disabled core dumps");
+    }
+
+    if config.lock_memory {
+        lock_memory()?;
+        info!("Locked signer memory");
+    }
+
+    Ok(())
+}
+
+fn disable_core_dumps() -> eyre::Result<()> {
+    let limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: limit is a valid rlimit
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        bail!("failed disabling core dumps: {}", std::io::Error::last_os_error());
+    }
+
+    // also prevents ptrace and reading /proc/<pid>/mem from processes of the
+    // same user
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: PR_SET_DUMPABLE takes a single integer argument
+        if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) } != 0 {
+            bail!("failed marking process as non-dumpable: {}", std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+fn lock_memory() -> eyre::Result<()> {
+    // SAFETY: mlockall has no memory safety requirements
+    if unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) } != 0 {
+        bail!(
+            "failed locking memory, the signer needs the IPC_LOCK capability or a high enough \
+             memlock limit: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disable_core_dumps;
+
+    #[test]
+    fn test_disable_core_dumps() {
+        disable_core_dumps().unwrap();
+
+        let mut limit = libc::rlimit { rlim_cur: 1, rlim_max: 1 };
+        assert_eq!(unsafe { libc::getrlimit(libc::RLIMIT_CORE, &mut limit) }, 0);
+        assert_eq!(limit.rlim_cur, 0);
+        assert_eq!(limit.rlim_max, 0);
+    }
+}
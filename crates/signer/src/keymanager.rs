@@ -0,0 +1,269 @@
+//! Keymanager API, to list, import and remove consensus keys at runtime. See
+//! https://ethereum.github.io/keymanager-APIs/#/Local%20Key%20Manager
+
+use std::sync::Arc;
+
+use alloy::rpc::types::beacon::BlsPublicKey;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json};
+use axum_extra::TypedHeader;
+use cb_common::{
+    config::KeymanagerConfig,
+    loader::{decrypt_keystore_json, has_keystore, remove_keystore, save_keystore},
+};
+use headers::{authorization::Bearer, Authorization};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::{net::TcpListener, sync::RwLock};
+use tracing::{error, info, warn};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{error::SignerModuleError, manager::SigningManager, metrics::AUTH_FAILURES};
+
+pub const KEYSTORES_PATH: &str = "/eth/v1/keystores";
+
+#[derive(Clone)]
+struct KeymanagerState {
+    manager: Arc<RwLock<SigningManager>>,
+    config: Arc<KeymanagerConfig>,
+    token: Arc<String>,
+}
+
+impl KeymanagerState {
+    /// Compares the token in constant time, so that it can't be guessed byte
+    /// by byte from the response times
+    fn authenticate(&self, auth: &Authorization<Bearer>) -> Result<(), SignerModuleError> {
+        if !bool::from(auth.token().as_bytes().ct_eq(self.token.as_bytes())) {
+            warn!("Unauthorized keymanager request");
+            AUTH_FAILURES.with_label_values(&["invalid_keymanager_token"]).inc();
+            return Err(SignerModuleError::Unauthorized);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListKeystoresResponse {
+    pub data: Vec<KeystoreEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreEntry {
+    pub validating_pubkey: BlsPublicKey,
+    pub derivation_path: Option<String>,
+    /// Keys loaded from the signer config can't be removed with the API
+    pub readonly: bool,
+}
+
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct ImportKeystoresRequest {
+    /// EIP-2335 keystores, as JSON strings
+    pub keystores: Vec<String>,
+    /// Password of each keystore
+    pub passwords: Vec<String>,
+    /// Ignored, the signer doesn't sign slashable messages
+    pub slashing_protection: Option<String>,
+}
+
+impl std::fmt::Debug for ImportKeystoresRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImportKeystoresRequest")
+            .field("keystores", &self.keystores.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteKeystoresRequest {
+    pub pubkeys: Vec<BlsPublicKey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoresResponse {
+    pub data: Vec<KeystoreStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreStatus {
+    pub status: KeystoreStatusKind,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystoreStatusKind {
+    Imported,
+    Duplicate,
+    Deleted,
+    NotFound,
+    Error,
+}
+
+impl KeystoreStatus {
+    fn ok(status: KeystoreStatusKind) -> Self {
+        Self { status, message: String::new() }
+    }
+
+    fn error(message: impl ToString) -> Self {
+        Self { status: KeystoreStatusKind::Error, message: message.to_string() }
+    }
+}
+
+/// Serves the keymanager API until the listener fails. Keys are imported and
+/// removed in place, so proxies of the other keys are kept
+pub async fn run_keymanager(
+    listener: TcpListener,
+    manager: Arc<RwLock<SigningManager>>,
+    config: KeymanagerConfig,
+    token: String,
+) {
+    let state = KeymanagerState { manager, config: config.into(), token: token.into() };
+    let app = axum::Router::new()
+        .route(
+            KEYSTORES_PATH,
+            get(handle_list_keystores)
+                .post(handle_import_keystores)
+                .delete(handle_delete_keystores),
+        )
+        .with_state(state);
+
+    if let Err(err) = axum::serve(listener, app).await {
+        error!(?err, "Keymanager server exited")
+    }
+}
+
+/// Lists the local consensus keys, remote keys are not included
+async fn handle_list_keystores(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<KeymanagerState>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    state.authenticate(&auth)?;
+
+    let data = state
+        .manager
+        .read()
+        .await
+        .local_pubkeys()
+        .into_iter()
+        .map(|pubkey| KeystoreEntry {
+            validating_pubkey: pubkey,
+            derivation_path: None,
+            readonly: !has_keystore(&state.config.keys_path, &pubkey),
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(ListKeystoresResponse { data })).into_response())
+}
+
+/// Keystores are decrypted on a blocking thread before taking the write lock,
+/// as the key derivation is slow
+async fn handle_import_keystores(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<KeymanagerState>,
+    Json(mut request): Json<ImportKeystoresRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    state.authenticate(&auth)?;
+
+    if request.keystores.len() != request.passwords.len() {
+        return Err(SignerModuleError::InvalidRequest(
+            "keystores and passwords have different lengths".to_owned(),
+        ));
+    }
+
+    let keystores = std::mem::take(&mut request.keystores);
+    let passwords = Zeroizing::new(std::mem::take(&mut request.passwords));
+    let (keystores, passwords, decrypted) = tokio::task::spawn_blocking(move || {
+        let decrypted: Vec<_> = keystores
+            .iter()
+            .zip(passwords.iter())
+            .map(|(keystore, password)| decrypt_keystore_json(keystore, password.as_bytes()))
+            .collect();
+        (keystores, passwords, decrypted)
+    })
+    .await
+    .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+    let mut manager = state.manager.write().await;
+    let mut data = Vec::with_capacity(keystores.len());
+
+    for ((keystore, password), signer) in keystores.iter().zip(passwords.iter()).zip(decrypted) {
+        let signer = match signer {
+            Ok(signer) => signer,
+            Err(err) => {
+                data.push(KeystoreStatus::error(err));
+                continue;
+            }
+        };
+
+        let pubkey = signer.pubkey();
+        if manager.has_consensus(&pubkey) {
+            data.push(KeystoreStatus::ok(KeystoreStatusKind::Duplicate));
+            continue;
+        }
+
+        let config = &state.config;
+        if let Err(err) =
+            save_keystore(&config.keys_path, &config.secrets_path, &pubkey, keystore, password)
+        {
+            error!(%pubkey, ?err, "Failed to save imported keystore");
+            data.push(KeystoreStatus::error(err));
+            continue;
+        }
+
+        manager.add_consensus_signer(signer);
+        info!(%pubkey, "Imported consensus key");
+        data.push(KeystoreStatus::ok(KeystoreStatusKind::Imported));
+    }
+
+    Ok((StatusCode::OK, Json(KeystoresResponse { data })).into_response())
+}
+
+/// Removes imported keys, and revokes the proxies they delegated to
+async fn handle_delete_keystores(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<KeymanagerState>,
+    Json(request): Json<DeleteKeystoresRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    state.authenticate(&auth)?;
+
+    let mut manager = state.manager.write().await;
+    let mut data = Vec::with_capacity(request.pubkeys.len());
+
+    for pubkey in request.pubkeys {
+        if !manager.has_consensus(&pubkey) {
+            data.push(KeystoreStatus::ok(KeystoreStatusKind::NotFound));
+            continue;
+        }
+
+        let config = &state.config;
+        if !has_keystore(&config.keys_path, &pubkey) {
+            data.push(KeystoreStatus::error("key loaded from the signer config, it's read only"));
+            continue;
+        }
+
+        // the key is removed from the manager before its files, and restored if
+        // they can't be removed, so that the loaded keys match the files
+        let res = manager.remove_consensus_signer(&pubkey).await.and_then(|signer| {
+            remove_keystore(&config.keys_path, &config.secrets_path, &pubkey).map_err(|err| {
+                if let Some(signer) = signer {
+                    manager.add_consensus_signer(signer);
+                }
+                SignerModuleError::Internal(err.to_string())
+            })
+        });
+
+        match res {
+            Ok(_) => {
+                info!(%pubkey, "Removed consensus key");
+                data.push(KeystoreStatus::ok(KeystoreStatusKind::Deleted));
+            }
+            Err(err) => {
+                error!(%pubkey, ?err, "Failed to remove consensus key");
+                data.push(KeystoreStatus::error(err));
+            }
+        }
+    }
+
+    Ok((StatusCode::OK, Json(KeystoresResponse { data })).into_response())
+}
@@ -1,3 +1,7 @@
 pub mod error;
+pub mod hardening;
+pub mod keymanager;
 pub mod manager;
+pub mod metrics;
+pub mod policy;
 pub mod service;
@@ -1,14 +1,33 @@
 use std::collections::HashMap;
 
-use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
+use alloy::{
+    primitives::Address,
+    rpc::types::beacon::{relay::ValidatorRegistrationMessage, BlsPublicKey, BlsSignature},
+};
 use cb_common::{
-    commit::request::{ProxyDelegation, SignedProxyDelegation},
-    signer::Signer,
+    commit::{
+        client::{DelegationResponse, DelegationResponseBls, DelegationResponseEcdsa},
+        request::{
+            ProxyDelegation, ProxyId, ProxyInfo, ProxyKey, ProxyStatus, RevokedProxy,
+            SignedProxyDelegation, SignedProxyDelegationBls, SignedProxyDelegationEcdsa,
+            SignedRevokedProxy, SignedRevokedProxyBls, SignedRevokedProxyEcdsa,
+        },
+    },
+    signature::compute_validator_registration_root,
+    signer::{
+        EcdsaProxySigner, EcdsaSignature, EcdsaSigner, ModuleRequest, ModuleRequestKind,
+        ProxySigner, ProxyStore, Signer,
+    },
     types::Chain,
+    utils::{current_slot, SLOTS_PER_EPOCH},
 };
+use tracing::{info, warn};
 use tree_hash::TreeHash;
 
-use crate::error::SignerModuleError;
+use crate::{
+    error::SignerModuleError,
+    metrics::{CONSENSUS_KEY_TAG, LOADED_KEYS, PROXY_BLS_KEY_TAG, PROXY_ECDSA_KEY_TAG},
+};
 
 // For extra safety and to avoid risking signing malicious messages, use a proxy
 // setup: proposer creates a new ephemeral keypair which will be used to sign
@@ -18,70 +37,580 @@ use crate::error::SignerModuleError;
 // key for slashing the faulty message + proxy delegation can be used
 // Signed using builder domain
 
-pub struct ProxySigner {
-    signer: Signer,
-    delegation: SignedProxyDelegation,
-}
-
 pub struct SigningManager {
     chain: Chain,
     consensus_signers: HashMap<BlsPublicKey, Signer>,
     proxy_signers: HashMap<BlsPublicKey, ProxySigner>,
+    ecdsa_proxy_signers: HashMap<Address, EcdsaProxySigner>,
+    /// Map of module ids to the proxies they created. Proxies can only be used
+    /// by the module which created them
+    proxy_pubkeys: HashMap<String, Vec<BlsPublicKey>>,
+    proxy_addresses: HashMap<String, Vec<Address>>,
+    /// Revoked proxies stay loaded, so that their status can be reported, but
+    /// can't sign anymore
+    revoked_proxies: HashMap<BlsPublicKey, SignedRevokedProxyBls>,
+    revoked_ecdsa_proxies: HashMap<Address, SignedRevokedProxyEcdsa>,
+    /// Where to persist new proxies, if at all
+    proxy_store: Option<ProxyStore>,
 }
 
 impl SigningManager {
-    pub fn new(chain: Chain) -> Self {
-        Self { chain, consensus_signers: HashMap::new(), proxy_signers: HashMap::new() }
+    pub fn new(chain: Chain, proxy_store: Option<ProxyStore>) -> Self {
+        Self {
+            chain,
+            consensus_signers: HashMap::new(),
+            proxy_signers: HashMap::new(),
+            ecdsa_proxy_signers: HashMap::new(),
+            proxy_pubkeys: HashMap::new(),
+            proxy_addresses: HashMap::new(),
+            revoked_proxies: HashMap::new(),
+            revoked_ecdsa_proxies: HashMap::new(),
+            proxy_store,
+        }
     }
 
     pub fn add_consensus_signer(&mut self, signer: Signer) {
         self.consensus_signers.insert(signer.pubkey(), signer);
+        self.update_key_metrics();
+    }
+
+    /// Removes a consensus signer, and revokes all the proxies it delegated to.
+    /// The revocations are signed and persisted first, so that the signer is
+    /// kept if that fails. Revoked proxies stay loaded, so that their
+    /// delegations are still reported. Returns the signer, if found
+    pub async fn remove_consensus_signer(
+        &mut self,
+        pubkey: &BlsPublicKey,
+    ) -> Result<Option<Signer>, SignerModuleError> {
+        if !self.consensus_signers.contains_key(pubkey) {
+            return Ok(None);
+        }
+
+        let mut revocations = Vec::new();
+        for (module_id, proxies) in &self.proxy_pubkeys {
+            for proxy in proxies {
+                if self.revoked_proxies.contains_key(proxy) ||
+                    self.get_delegation(proxy)?.message.delegator != *pubkey
+                {
+                    continue;
+                }
+                let revocation =
+                    self.sign_revocation(module_id, *pubkey, *proxy, ProxyId::Bls(*proxy), None);
+                revocations.push((module_id.clone(), revocation.await?));
+            }
+        }
+
+        let mut ecdsa_revocations = Vec::new();
+        for (module_id, proxies) in &self.proxy_addresses {
+            for proxy in proxies {
+                if self.revoked_ecdsa_proxies.contains_key(proxy) ||
+                    self.get_ecdsa_delegation(proxy)?.message.delegator != *pubkey
+                {
+                    continue;
+                }
+                let revocation =
+                    self.sign_revocation(module_id, *pubkey, *proxy, ProxyId::Ecdsa(*proxy), None);
+                ecdsa_revocations.push((module_id.clone(), revocation.await?));
+            }
+        }
+
+        if let Some(store) = &self.proxy_store {
+            for (module_id, revocation) in &revocations {
+                store
+                    .store_bls_revocation(module_id, revocation)
+                    .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            }
+            for (module_id, revocation) in &ecdsa_revocations {
+                store
+                    .store_ecdsa_revocation(module_id, revocation)
+                    .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            }
+        }
+
+        let signer = self.consensus_signers.remove(pubkey);
+        info!(%pubkey, bls = revocations.len(), ecdsa = ecdsa_revocations.len(), "Revoked proxies");
+        for (_, revocation) in revocations {
+            self.revoked_proxies.insert(revocation.message.proxy, revocation);
+        }
+        for (_, revocation) in ecdsa_revocations {
+            self.revoked_ecdsa_proxies.insert(revocation.message.proxy, revocation);
+        }
+        self.update_key_metrics();
+
+        Ok(signer)
+    }
+
+    pub fn add_proxy_signer(&mut self, proxy: ProxySigner, module_id: String) {
+        let proxy_pubkey = proxy.signer.pubkey();
+        self.proxy_signers.insert(proxy_pubkey, proxy);
+        self.proxy_pubkeys.entry(module_id).or_default().push(proxy_pubkey);
+        self.update_key_metrics();
+    }
+
+    pub fn add_ecdsa_proxy_signer(&mut self, proxy: EcdsaProxySigner, module_id: String) {
+        let proxy_address = proxy.signer.address();
+        self.ecdsa_proxy_signers.insert(proxy_address, proxy);
+        self.proxy_addresses.entry(module_id).or_default().push(proxy_address);
+        self.update_key_metrics();
+    }
+
+    fn update_key_metrics(&self) {
+        LOADED_KEYS
+            .with_label_values(&[CONSENSUS_KEY_TAG])
+            .set(self.consensus_signers.len() as i64);
+        LOADED_KEYS.with_label_values(&[PROXY_BLS_KEY_TAG]).set(self.proxy_signers.len() as i64);
+        LOADED_KEYS
+            .with_label_values(&[PROXY_ECDSA_KEY_TAG])
+            .set(self.ecdsa_proxy_signers.len() as i64);
+    }
+
+    /// Loads the proxies persisted in the store, with their revocations.
+    /// Consensus signers should be added first, as proxies delegated by unknown
+    /// keys are skipped
+    pub fn load_proxies(&mut self) -> eyre::Result<()> {
+        let Some(store) = &self.proxy_store else {
+            return Ok(());
+        };
+
+        let migrated = store.migrate_legacy_layout()?;
+        if migrated > 0 {
+            info!(migrated, "Moved stored proxies to the current layout");
+        }
+
+        let bls_proxies = store.load_bls_proxies()?;
+        let ecdsa_proxies = store.load_ecdsa_proxies()?;
+
+        let mut bls_revocations = Vec::new();
+        for (module_id, proxy) in &bls_proxies {
+            let pubkey = proxy.signer.pubkey();
+            if let Some(revocation) = store.load_bls_revocation(module_id, &pubkey)? {
+                bls_revocations.push((module_id.clone(), revocation));
+            }
+        }
+
+        let mut ecdsa_revocations = Vec::new();
+        for (module_id, proxy) in &ecdsa_proxies {
+            let address = proxy.signer.address();
+            if let Some(revocation) = store.load_ecdsa_revocation(module_id, &address)? {
+                ecdsa_revocations.push((module_id.clone(), revocation));
+            }
+        }
+
+        for (module_id, proxy) in bls_proxies {
+            let pubkey = proxy.signer.pubkey();
+            let revoked = bls_revocations.iter().any(|(_, r)| r.message.proxy == pubkey);
+            if self.check_stored_delegation(&module_id, &proxy.delegation, revoked) {
+                self.add_proxy_signer(proxy, module_id);
+            }
+        }
+
+        for (module_id, proxy) in ecdsa_proxies {
+            let address = proxy.signer.address();
+            let revoked = ecdsa_revocations.iter().any(|(_, r)| r.message.proxy == address);
+            if self.check_stored_delegation(&module_id, &proxy.delegation, revoked) {
+                self.add_ecdsa_proxy_signer(proxy, module_id);
+            }
+        }
+
+        for (module_id, revocation) in bls_revocations {
+            let delegation =
+                self.proxy_signers.get(&revocation.message.proxy).map(|p| p.delegation);
+            if check_stored_revocation(self.chain, &module_id, delegation, &revocation) {
+                self.revoked_proxies.insert(revocation.message.proxy, revocation);
+            }
+        }
+
+        for (module_id, revocation) in ecdsa_revocations {
+            let delegation =
+                self.ecdsa_proxy_signers.get(&revocation.message.proxy).map(|p| p.delegation);
+            if check_stored_revocation(self.chain, &module_id, delegation, &revocation) {
+                self.revoked_ecdsa_proxies.insert(revocation.message.proxy, revocation);
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn add_proxy_signer(&mut self, proxy: ProxySigner) {
-        self.proxy_signers.insert(proxy.signer.pubkey(), proxy);
+    /// Proxies of unknown consensus keys are only loaded if revoked, e.g. after
+    /// the key was removed, so that the revocation is still reported
+    fn check_stored_delegation<T: ProxyKey + std::fmt::Display>(
+        &self,
+        module_id: &str,
+        delegation: &SignedProxyDelegation<T>,
+        revoked: bool,
+    ) -> bool {
+        if !revoked && !self.has_consensus(&delegation.message.delegator) {
+            warn!(
+                module_id,
+                proxy = %delegation.message.proxy,
+                "Skipping proxy of unknown consensus key"
+            );
+            return false;
+        }
+
+        if let Err(err) = delegation.validate(self.chain) {
+            warn!(
+                module_id,
+                proxy = %delegation.message.proxy,
+                ?err,
+                "Skipping proxy with invalid delegation"
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// Current epoch, according to the wall clock
+    pub fn current_epoch(&self) -> u64 {
+        current_slot(self.chain) / SLOTS_PER_EPOCH
     }
 
+    /// Proxies can only be created with an expiry epoch in the future
+    pub fn check_expiry(&self, expiry_epoch: Option<u64>) -> Result<(), SignerModuleError> {
+        match expiry_epoch {
+            Some(expiry_epoch) if expiry_epoch <= self.current_epoch() => {
+                Err(SignerModuleError::InvalidRequest(format!(
+                    "expiry epoch {expiry_epoch} is not in the future"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a new BLS proxy delegated to by `delegator`. If `expiry_epoch`
+    /// is set, the proxy can't sign from that epoch on. `jwt` is the one the
+    /// module authenticated with, needed if `delegator` is a distributed key.
+    /// The proxy is only added with [`SigningManager::add_new_proxy`], so that
+    /// the delegation, which may be signed remotely, doesn't need an exclusive
+    /// borrow
     pub async fn create_proxy(
-        &mut self,
+        &self,
+        module_id: &str,
+        delegator: BlsPublicKey,
+        expiry_epoch: Option<u64>,
+        jwt: Option<&str>,
+    ) -> Result<ProxySigner, SignerModuleError> {
+        self.check_expiry(expiry_epoch)?;
+        let signer =
+            Signer::new_random().map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+        let message = ProxyDelegation { delegator, proxy: signer.pubkey(), expiry_epoch };
+        let kind =
+            ModuleRequestKind::Delegation { proxy: ProxyId::Bls(message.proxy), expiry_epoch };
+        let signature = self
+            .sign_consensus(
+                &delegator,
+                delegation_domain(self.chain),
+                &message.tree_hash_root().0,
+                module_request(module_id, jwt, kind).as_ref(),
+            )
+            .await?;
+
+        Ok(ProxySigner { signer, delegation: SignedProxyDelegation { signature, message } })
+    }
+
+    /// Creates a new ECDSA proxy delegated to by `delegator`, see
+    /// [`SigningManager::create_proxy`]
+    pub async fn create_ecdsa_proxy(
+        &self,
+        module_id: &str,
         delegator: BlsPublicKey,
-    ) -> eyre::Result<SignedProxyDelegation> {
-        let signer = Signer::new_random()?;
+        expiry_epoch: Option<u64>,
+        jwt: Option<&str>,
+    ) -> Result<EcdsaProxySigner, SignerModuleError> {
+        self.check_expiry(expiry_epoch)?;
+        let signer = EcdsaSigner::new_random();
+
+        let message = ProxyDelegation { delegator, proxy: signer.address(), expiry_epoch };
+        let kind =
+            ModuleRequestKind::Delegation { proxy: ProxyId::Ecdsa(message.proxy), expiry_epoch };
+        let signature = self
+            .sign_consensus(
+                &delegator,
+                delegation_domain(self.chain),
+                &message.tree_hash_root().0,
+                module_request(module_id, jwt, kind).as_ref(),
+            )
+            .await?;
+
+        Ok(EcdsaProxySigner { signer, delegation: SignedProxyDelegation { signature, message } })
+    }
+
+    /// Adds a proxy created with [`SigningManager::create_proxy`], once it's
+    /// persisted in the [`SigningManager::proxy_store`]. Fails if its consensus
+    /// key was removed in the meantime
+    pub fn add_new_proxy(
+        &mut self,
+        module_id: String,
+        proxy: ProxySigner,
+    ) -> Result<SignedProxyDelegationBls, SignerModuleError> {
+        let delegation = proxy.delegation;
+        self.check_delegator(&delegation.message.delegator)?;
+        self.add_proxy_signer(proxy, module_id);
+
+        Ok(delegation)
+    }
+
+    /// Adds a proxy created with [`SigningManager::create_ecdsa_proxy`], see
+    /// [`SigningManager::add_new_proxy`]
+    pub fn add_new_ecdsa_proxy(
+        &mut self,
+        module_id: String,
+        proxy: EcdsaProxySigner,
+    ) -> Result<SignedProxyDelegationEcdsa, SignerModuleError> {
+        let delegation = proxy.delegation;
+        self.check_delegator(&delegation.message.delegator)?;
+        self.add_ecdsa_proxy_signer(proxy, module_id);
+
+        Ok(delegation)
+    }
+
+    /// Where new proxies should be persisted, if at all
+    pub fn proxy_store(&self) -> Option<&ProxyStore> {
+        self.proxy_store.as_ref()
+    }
+
+    fn check_delegator(&self, delegator: &BlsPublicKey) -> Result<(), SignerModuleError> {
+        if !self.has_consensus(delegator) {
+            return Err(SignerModuleError::UnknownConsensusSigner(*delegator));
+        }
+        Ok(())
+    }
+
+    /// Signs the revocation of a BLS proxy of the module as of the current
+    /// epoch. Revoking a proxy again returns the existing revocation. The
+    /// revocation is only applied with [`SigningManager::add_revocation`],
+    /// see [`SigningManager::create_proxy`]
+    pub async fn revoke_proxy(
+        &self,
+        module_id: &str,
+        proxy: &BlsPublicKey,
+        jwt: Option<&str>,
+    ) -> Result<SignedRevokedProxyBls, SignerModuleError> {
+        // proxies of other modules are treated as unknown
+        if !self.has_proxy(module_id, proxy) {
+            return Err(SignerModuleError::UnknownProxySigner(*proxy));
+        }
+        if let Some(revocation) = self.revoked_proxies.get(proxy) {
+            return Ok(*revocation);
+        }
+
+        let delegator = self.get_delegation(proxy)?.message.delegator;
+        self.sign_revocation(module_id, delegator, *proxy, ProxyId::Bls(*proxy), jwt).await
+    }
+
+    /// Signs the revocation of an ECDSA proxy of the module, see
+    /// [`SigningManager::revoke_proxy`]
+    pub async fn revoke_ecdsa_proxy(
+        &self,
+        module_id: &str,
+        proxy: &Address,
+        jwt: Option<&str>,
+    ) -> Result<SignedRevokedProxyEcdsa, SignerModuleError> {
+        // proxies of other modules are treated as unknown
+        if !self.has_ecdsa_proxy(module_id, proxy) {
+            return Err(SignerModuleError::UnknownEcdsaProxySigner(*proxy));
+        }
+        if let Some(revocation) = self.revoked_ecdsa_proxies.get(proxy) {
+            return Ok(*revocation);
+        }
+
+        let delegator = self.get_ecdsa_delegation(proxy)?.message.delegator;
+        self.sign_revocation(module_id, delegator, *proxy, ProxyId::Ecdsa(*proxy), jwt).await
+    }
+
+    /// Applies a revocation signed with [`SigningManager::revoke_proxy`], once
+    /// it's persisted. If the proxy was revoked in the meantime, the existing
+    /// revocation is kept and returned. Fails if the proxy was removed
+    pub fn add_revocation(
+        &mut self,
+        module_id: &str,
+        revocation: SignedRevokedProxyBls,
+    ) -> Result<SignedRevokedProxyBls, SignerModuleError> {
+        let proxy = revocation.message.proxy;
+        if !self.has_proxy(module_id, &proxy) {
+            return Err(SignerModuleError::UnknownProxySigner(proxy));
+        }
+
+        Ok(*self.revoked_proxies.entry(proxy).or_insert_with(|| {
+            info!(module_id, %proxy, epoch = revocation.message.revoked_epoch, "Revoked proxy");
+            revocation
+        }))
+    }
 
-        let message = ProxyDelegation { delegator, proxy: signer.pubkey() };
-        let signature = self.sign_consensus(&delegator, &message.tree_hash_root().0).await?;
-        let signed_delegation: SignedProxyDelegation = SignedProxyDelegation { signature, message };
-        let proxy_signer = ProxySigner { signer, delegation: signed_delegation };
+    /// Applies a revocation signed with [`SigningManager::revoke_ecdsa_proxy`],
+    /// see [`SigningManager::add_revocation`]
+    pub fn add_ecdsa_revocation(
+        &mut self,
+        module_id: &str,
+        revocation: SignedRevokedProxyEcdsa,
+    ) -> Result<SignedRevokedProxyEcdsa, SignerModuleError> {
+        let proxy = revocation.message.proxy;
+        if !self.has_ecdsa_proxy(module_id, &proxy) {
+            return Err(SignerModuleError::UnknownEcdsaProxySigner(proxy));
+        }
 
-        self.add_proxy_signer(proxy_signer);
+        Ok(*self.revoked_ecdsa_proxies.entry(proxy).or_insert_with(|| {
+            info!(module_id, %proxy, epoch = revocation.message.revoked_epoch, "Revoked proxy");
+            revocation
+        }))
+    }
 
-        Ok(signed_delegation)
+    async fn sign_revocation<T: TreeHash>(
+        &self,
+        module_id: &str,
+        delegator: BlsPublicKey,
+        proxy: T,
+        proxy_id: ProxyId,
+        jwt: Option<&str>,
+    ) -> Result<SignedRevokedProxy<T>, SignerModuleError> {
+        let message = RevokedProxy { delegator, proxy, revoked_epoch: self.current_epoch() };
+        let kind =
+            ModuleRequestKind::Revocation { proxy: proxy_id, revoked_epoch: message.revoked_epoch };
+        let signature = self
+            .sign_consensus(
+                &delegator,
+                delegation_domain(self.chain),
+                &message.tree_hash_root().0,
+                module_request(module_id, jwt, kind).as_ref(),
+            )
+            .await?;
+
+        Ok(SignedRevokedProxy { message, signature })
     }
 
-    // TODO: double check what we can actually sign here with different providers eg
-    // web3 signer
+    /// Signs with a consensus key, with any domain. Distributed keys need the
+    /// module `request` being signed, which the peers check again before
+    /// signing with their share. Web3Signer keys can't sign here, see
+    /// [SigningManager::sign_validator_registration]
     pub async fn sign_consensus(
         &self,
         pubkey: &BlsPublicKey,
+        domain: [u8; 32],
         msg: &[u8; 32],
+        request: Option<&ModuleRequest>,
     ) -> Result<BlsSignature, SignerModuleError> {
         let signer = self
             .consensus_signers
             .get(pubkey)
             .ok_or(SignerModuleError::UnknownConsensusSigner(*pubkey))?;
-        let signature = signer.sign(self.chain, msg).await;
+        let res = match (signer, request) {
+            (Signer::Threshold(signer), Some(request)) => signer.sign(domain, msg, request).await,
+            _ => signer.sign_with_domain(domain, msg).await,
+        };
+        let signature = res.map_err(|err| SignerModuleError::Internal(err.to_string()))?;
 
         Ok(signature)
     }
 
+    /// Signs a validator registration with the builder domain. Web3Signer keys
+    /// are sent the registration itself, since they only sign typed messages
+    pub async fn sign_validator_registration(
+        &self,
+        registration: &ValidatorRegistrationMessage,
+        request: Option<&ModuleRequest>,
+    ) -> Result<BlsSignature, SignerModuleError> {
+        let pubkey = &registration.pubkey;
+        let signer = self
+            .consensus_signers
+            .get(pubkey)
+            .ok_or(SignerModuleError::UnknownConsensusSigner(*pubkey))?;
+        let Signer::Remote(signer) = signer else {
+            let object_root = compute_validator_registration_root(registration);
+            let domain = self.chain.builder_domain();
+            return self.sign_consensus(pubkey, domain, &object_root, request).await;
+        };
+
+        signer
+            .sign_validator_registration(self.chain, registration)
+            .await
+            .map_err(|err| SignerModuleError::Internal(err.to_string()))
+    }
+
+    /// Signs with the local share of a distributed validator key, for the other
+    /// nodes of the cluster. Returns the share index with the partial signature
+    pub async fn sign_share(
+        &self,
+        pubkey: &BlsPublicKey,
+        domain: [u8; 32],
+        msg: &[u8; 32],
+    ) -> Result<(u64, BlsSignature), SignerModuleError> {
+        let Some(Signer::Threshold(signer)) = self.consensus_signers.get(pubkey) else {
+            return Err(SignerModuleError::UnknownConsensusSigner(*pubkey));
+        };
+
+        signer
+            .sign_share(domain, msg)
+            .await
+            .map_err(|err| SignerModuleError::Internal(err.to_string()))
+    }
+
     pub async fn sign_proxy(
         &self,
+        module_id: &str,
         pubkey: &BlsPublicKey,
+        domain: [u8; 32],
         msg: &[u8; 32],
     ) -> Result<BlsSignature, SignerModuleError> {
+        // proxies of other modules are treated as unknown
+        if !self.has_proxy(module_id, pubkey) {
+            return Err(SignerModuleError::UnknownProxySigner(*pubkey));
+        }
+
         let proxy =
             self.proxy_signers.get(pubkey).ok_or(SignerModuleError::UnknownProxySigner(*pubkey))?;
-        let signature = proxy.signer.sign(self.chain, msg).await;
+        match self.status(&proxy.delegation.message, self.revoked_proxies.contains_key(pubkey)) {
+            ProxyStatus::Active => {}
+            ProxyStatus::Expired => {
+                return Err(SignerModuleError::ProxyExpired(pubkey.to_string()));
+            }
+            ProxyStatus::Revoked => {
+                return Err(SignerModuleError::ProxyRevoked(pubkey.to_string()));
+            }
+        }
+
+        let signature = proxy
+            .signer
+            .sign_with_domain(domain, msg)
+            .await
+            .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+
+        Ok(signature)
+    }
+
+    pub async fn sign_ecdsa_proxy(
+        &self,
+        module_id: &str,
+        address: &Address,
+        domain: [u8; 32],
+        msg: &[u8; 32],
+    ) -> Result<EcdsaSignature, SignerModuleError> {
+        // proxies of other modules are treated as unknown
+        if !self.has_ecdsa_proxy(module_id, address) {
+            return Err(SignerModuleError::UnknownEcdsaProxySigner(*address));
+        }
+
+        let proxy = self
+            .ecdsa_proxy_signers
+            .get(address)
+            .ok_or(SignerModuleError::UnknownEcdsaProxySigner(*address))?;
+        let revoked = self.revoked_ecdsa_proxies.contains_key(address);
+        match self.status(&proxy.delegation.message, revoked) {
+            ProxyStatus::Active => {}
+            ProxyStatus::Expired => {
+                return Err(SignerModuleError::ProxyExpired(address.to_string()));
+            }
+            ProxyStatus::Revoked => {
+                return Err(SignerModuleError::ProxyRevoked(address.to_string()));
+            }
+        }
+
+        let signature = proxy
+            .signer
+            .sign_with_domain(domain, msg)
+            .await
+            .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
 
         Ok(signature)
     }
@@ -90,30 +619,187 @@ impl SigningManager {
         self.consensus_signers.keys().cloned().collect()
     }
 
-    pub fn proxy_pubkeys(&self) -> Vec<BlsPublicKey> {
-        self.proxy_signers.keys().cloned().collect()
+    /// Consensus pubkeys of the local keys, i.e. not held by a remote signer
+    pub fn local_pubkeys(&self) -> Vec<BlsPublicKey> {
+        self.consensus_signers
+            .iter()
+            .filter(|(_, signer)| matches!(signer, Signer::Local(_)))
+            .map(|(pubkey, _)| *pubkey)
+            .collect()
     }
 
-    pub fn delegations(&self) -> Vec<SignedProxyDelegation> {
+    /// Proxy pubkeys created by the given module, which can still sign
+    pub fn proxy_pubkeys(&self, module_id: &str) -> Vec<BlsPublicKey> {
+        self.proxy_status(module_id)
+            .into_iter()
+            .filter_map(|info| match (info.proxy, info.status) {
+                (ProxyId::Bls(pubkey), ProxyStatus::Active) => Some(pubkey),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Addresses of the ECDSA proxies created by the given module, which can
+    /// still sign
+    pub fn proxy_addresses(&self, module_id: &str) -> Vec<Address> {
+        self.proxy_status(module_id)
+            .into_iter()
+            .filter_map(|info| match (info.proxy, info.status) {
+                (ProxyId::Ecdsa(address), ProxyStatus::Active) => Some(address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All the proxies created by the given module, including the revoked and
+    /// expired ones
+    pub fn proxy_status(&self, module_id: &str) -> Vec<ProxyInfo> {
+        let bls = self.proxy_pubkeys.get(module_id).into_iter().flatten().filter_map(|pubkey| {
+            let delegation = self.proxy_signers.get(pubkey)?.delegation.message;
+            Some(ProxyInfo {
+                proxy: ProxyId::Bls(*pubkey),
+                delegator: delegation.delegator,
+                status: self.status(&delegation, self.revoked_proxies.contains_key(pubkey)),
+                expiry_epoch: delegation.expiry_epoch,
+            })
+        });
+
+        let ecdsa =
+            self.proxy_addresses.get(module_id).into_iter().flatten().filter_map(|address| {
+                let delegation = self.ecdsa_proxy_signers.get(address)?.delegation.message;
+                let revoked = self.revoked_ecdsa_proxies.contains_key(address);
+                Some(ProxyInfo {
+                    proxy: ProxyId::Ecdsa(*address),
+                    delegator: delegation.delegator,
+                    status: self.status(&delegation, revoked),
+                    expiry_epoch: delegation.expiry_epoch,
+                })
+            });
+
+        bls.chain(ecdsa).collect()
+    }
+
+    /// Revocations take precedence over expiries
+    fn status<T: ProxyKey>(&self, delegation: &ProxyDelegation<T>, revoked: bool) -> ProxyStatus {
+        if revoked {
+            ProxyStatus::Revoked
+        } else if delegation.is_expired(self.current_epoch()) {
+            ProxyStatus::Expired
+        } else {
+            ProxyStatus::Active
+        }
+    }
+
+    pub fn delegations(&self) -> Vec<SignedProxyDelegationBls> {
         self.proxy_signers.values().map(|s| s.delegation).collect()
     }
 
+    pub fn ecdsa_delegations(&self) -> Vec<SignedProxyDelegationEcdsa> {
+        self.ecdsa_proxy_signers.values().map(|s| s.delegation).collect()
+    }
+
     pub fn has_consensus(&self, pubkey: &BlsPublicKey) -> bool {
         self.consensus_signers.contains_key(pubkey)
     }
 
-    pub fn has_proxy(&self, pubkey: &BlsPublicKey) -> bool {
-        self.proxy_signers.contains_key(pubkey)
+    pub fn has_proxy(&self, module_id: &str, pubkey: &BlsPublicKey) -> bool {
+        self.proxy_pubkeys.get(module_id).is_some_and(|pubkeys| pubkeys.contains(pubkey))
+    }
+
+    pub fn has_ecdsa_proxy(&self, module_id: &str, address: &Address) -> bool {
+        self.proxy_addresses.get(module_id).is_some_and(|addresses| addresses.contains(address))
     }
 
     pub fn get_delegation(
         &self,
         proxy_pubkey: &BlsPublicKey,
-    ) -> Result<SignedProxyDelegation, SignerModuleError> {
+    ) -> Result<SignedProxyDelegationBls, SignerModuleError> {
         let signer = self
             .proxy_signers
             .get(proxy_pubkey)
             .ok_or(SignerModuleError::UnknownProxySigner(*proxy_pubkey))?;
         Ok(signer.delegation)
     }
+
+    pub fn get_ecdsa_delegation(
+        &self,
+        proxy_address: &Address,
+    ) -> Result<SignedProxyDelegationEcdsa, SignerModuleError> {
+        let signer = self
+            .ecdsa_proxy_signers
+            .get(proxy_address)
+            .ok_or(SignerModuleError::UnknownEcdsaProxySigner(*proxy_address))?;
+        Ok(signer.delegation)
+    }
+
+    /// Delegation of a proxy of any module, with its status and revocation
+    pub fn delegation_info(
+        &self,
+        proxy_pubkey: &BlsPublicKey,
+    ) -> Result<DelegationResponseBls, SignerModuleError> {
+        let delegation = self.get_delegation(proxy_pubkey)?;
+        let revocation = self.revoked_proxies.get(proxy_pubkey).copied();
+        let status = self.status(&delegation.message, revocation.is_some());
+        Ok(DelegationResponse { delegation, status, revocation })
+    }
+
+    pub fn ecdsa_delegation_info(
+        &self,
+        proxy_address: &Address,
+    ) -> Result<DelegationResponseEcdsa, SignerModuleError> {
+        let delegation = self.get_ecdsa_delegation(proxy_address)?;
+        let revocation = self.revoked_ecdsa_proxies.get(proxy_address).copied();
+        let status = self.status(&delegation.message, revocation.is_some());
+        Ok(DelegationResponse { delegation, status, revocation })
+    }
+
+    pub fn is_revoked(&self, proxy: &ProxyId) -> bool {
+        match proxy {
+            ProxyId::Bls(pubkey) => self.revoked_proxies.contains_key(pubkey),
+            ProxyId::Ecdsa(address) => self.revoked_ecdsa_proxies.contains_key(address),
+        }
+    }
+}
+
+/// Request to forward to the peers, if the key signing for the module is a
+/// distributed one. Without the module JWT, e.g. when revoking from the CLI,
+/// distributed keys can't sign
+fn module_request(
+    module_id: &str,
+    jwt: Option<&str>,
+    kind: ModuleRequestKind,
+) -> Option<ModuleRequest> {
+    jwt.map(|jwt| ModuleRequest { module_id: module_id.to_owned(), jwt: jwt.to_owned(), kind })
+}
+
+/// Domain the proxy delegations and revocations are signed with
+pub fn delegation_domain(chain: Chain) -> [u8; 32] {
+    chain.builder_domain()
+}
+
+/// Checks that a stored revocation is for a loaded proxy, and signed by its
+/// delegator
+fn check_stored_revocation<T: ProxyKey + std::fmt::Display>(
+    chain: Chain,
+    module_id: &str,
+    delegation: Option<SignedProxyDelegation<T>>,
+    revocation: &SignedRevokedProxy<T>,
+) -> bool {
+    let proxy = &revocation.message.proxy;
+    let Some(delegation) = delegation else {
+        warn!(module_id, %proxy, "Skipping revocation of unknown proxy");
+        return false;
+    };
+
+    if delegation.message.delegator != revocation.message.delegator {
+        warn!(module_id, %proxy, "Skipping revocation by another consensus key");
+        return false;
+    }
+
+    if let Err(err) = revocation.validate(chain) {
+        warn!(module_id, %proxy, ?err, "Skipping invalid revocation");
+        return false;
+    }
+
+    true
 }
@@ -0,0 +1,78 @@
+//! Metrics for the Signer module:
+//! - signature requests and their latency
+//! - failed authentications
+//! - keys loaded
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
+    register_int_gauge_vec_with_registry, HistogramVec, IntCounterVec, IntGaugeVec, Registry,
+};
+
+use crate::error::SignerModuleError;
+
+pub const CONSENSUS_KEY_TAG: &str = "consensus";
+pub const PROXY_BLS_KEY_TAG: &str = "proxy_bls";
+pub const PROXY_ECDSA_KEY_TAG: &str = "proxy_ecdsa";
+
+lazy_static! {
+    pub static ref SIGNER_METRICS_REGISTRY: Registry =
+        Registry::new_custom(Some("cb_signer".to_string()), None).unwrap();
+
+    // SIGNATURES
+    /// Signature requests by module, key type and outcome
+    pub static ref SIGNATURE_REQUESTS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "signature_requests_total",
+        "Signature requests by module, key type and outcome",
+        &["module_id", "key_type", "outcome"],
+        SIGNER_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    /// Latency of the signature requests, including the policy checks
+    pub static ref SIGNING_LATENCY: HistogramVec = register_histogram_vec_with_registry!(
+        "signing_latency",
+        "Latency of the signature requests by key type",
+        &["key_type"],
+        SIGNER_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    // AUTH
+    /// Requests with a missing, invalid or expired JWT, or for an unknown module
+    pub static ref AUTH_FAILURES: IntCounterVec = register_int_counter_vec_with_registry!(
+        "auth_failures_total",
+        "Requests which failed authentication, by reason",
+        &["reason"],
+        SIGNER_METRICS_REGISTRY
+    )
+    .unwrap();
+
+    // KEYS
+    /// Keys currently loaded, by key type
+    pub static ref LOADED_KEYS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "loaded_keys",
+        "Keys loaded in the signer, by key type",
+        &["key_type"],
+        SIGNER_METRICS_REGISTRY
+    )
+    .unwrap();
+}
+
+/// Outcome label of a signature request: requests denied by the module
+/// policy are counted separately from the failed ones
+pub fn outcome_tag<T>(res: &Result<T, SignerModuleError>) -> &'static str {
+    match res {
+        Ok(_) => "success",
+        Err(
+            SignerModuleError::BuilderDomainNotAllowed(_) |
+            SignerModuleError::KeyNotAllowed(_) |
+            SignerModuleError::ProxyOnly(_) |
+            SignerModuleError::OutsideSigningWindow(_) |
+            SignerModuleError::ProxyRevoked(_) |
+            SignerModuleError::ProxyExpired(_) |
+            SignerModuleError::RateLimited(_),
+        ) => "denied",
+        Err(_) => "error",
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use alloy::rpc::types::beacon::BlsPublicKey;
+use cb_common::{
+    commit::schema::{find_schema, DecodedMessage},
+    config::SigningPolicy,
+    types::Chain,
+    utils::{as_str, current_slot, utcnow_sec, SLOTS_PER_EPOCH},
+    DEFAULT_REQUEST_TIMEOUT,
+};
+use eyre::{bail, ensure, WrapErr};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::error::SignerModuleError;
+
+/// Enforces the signing policies of the modules. Modules without a policy are
+/// unrestricted
+pub struct PolicyEngine {
+    chain: Chain,
+    policies: HashMap<String, ModulePolicy>,
+    /// Signatures requested by each module in the current slot and epoch
+    counters: Mutex<HashMap<String, SignatureCounter>>,
+    duties: Option<ProposerDuties>,
+}
+
+struct ModulePolicy {
+    /// Resolved from pubkeys and key groups, None if all keys are allowed
+    keys: Option<HashSet<BlsPublicKey>>,
+    proxy_only: bool,
+    max_signatures_per_slot: Option<u64>,
+    max_signatures_per_epoch: Option<u64>,
+    allowed_hours: Vec<(u8, u8)>,
+    proposal_window_slots: Option<u64>,
+    /// None if raw object roots can be signed
+    message_schemas: Option<HashSet<String>>,
+    max_slot_lookahead: Option<u64>,
+}
+
+/// How a consensus key is used by a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyUse {
+    /// Signing with the consensus key
+    Consensus,
+    /// Signing with a proxy of the consensus key
+    Proxy,
+    /// Delegating to a new proxy, allowed for `proxy_only` modules
+    Delegation,
+}
+
+#[derive(Default)]
+struct SignatureCounter {
+    slot: u64,
+    in_slot: u64,
+    epoch: u64,
+    in_epoch: u64,
+}
+
+impl PolicyEngine {
+    pub fn new(
+        chain: Chain,
+        policies: HashMap<String, SigningPolicy>,
+        key_groups: &HashMap<String, Vec<BlsPublicKey>>,
+        beacon_url: Option<Url>,
+    ) -> eyre::Result<Self> {
+        let mut needs_duties = false;
+        let mut resolved = HashMap::with_capacity(policies.len());
+
+        for (module_id, policy) in policies {
+            let keys = if policy.pubkeys.is_empty() && policy.key_groups.is_empty() {
+                None
+            } else {
+                let mut keys: HashSet<BlsPublicKey> = policy.pubkeys.into_iter().collect();
+                for group in &policy.key_groups {
+                    let Some(group_keys) = key_groups.get(group) else {
+                        bail!("unknown key group {group} in policy of {module_id}");
+                    };
+                    keys.extend(group_keys);
+                }
+                Some(keys)
+            };
+
+            for (start, end) in &policy.allowed_hours {
+                ensure!(
+                    *start < 24 && *end <= 24 && start != end,
+                    "invalid hours [{start}, {end}] in policy of {module_id}"
+                );
+            }
+
+            if let Some(window) = policy.proposal_window_slots {
+                ensure!(
+                    window < SLOTS_PER_EPOCH,
+                    "proposal window of {module_id} must be less than an epoch"
+                );
+                needs_duties = true;
+            }
+
+            for schema in &policy.message_schemas {
+                ensure!(
+                    find_schema(schema).is_some(),
+                    "unknown message schema {schema} in policy of {module_id}"
+                );
+            }
+            let message_schemas = (!policy.message_schemas.is_empty())
+                .then(|| policy.message_schemas.into_iter().collect());
+
+            resolved.insert(module_id, ModulePolicy {
+                keys,
+                proxy_only: policy.proxy_only,
+                max_signatures_per_slot: policy.max_signatures_per_slot,
+                max_signatures_per_epoch: policy.max_signatures_per_epoch,
+                allowed_hours: policy.allowed_hours,
+                proposal_window_slots: policy.proposal_window_slots,
+                message_schemas,
+                max_slot_lookahead: policy.max_slot_lookahead,
+            });
+        }
+
+        let duties = match beacon_url {
+            Some(url) => Some(ProposerDuties::new(url)?),
+            None if needs_duties => bail!("proposal windows require signer.beacon_url"),
+            None => None,
+        };
+
+        Ok(Self { chain, policies: resolved, counters: Default::default(), duties })
+    }
+
+    /// Whether the module can use the consensus key, directly or through a
+    /// proxy
+    pub fn is_key_allowed(&self, module_id: &str, pubkey: &BlsPublicKey) -> bool {
+        self.policies
+            .get(module_id)
+            .and_then(|policy| policy.keys.as_ref())
+            .map_or(true, |keys| keys.contains(pubkey))
+    }
+
+    pub fn check_key(
+        &self,
+        module_id: &str,
+        pubkey: &BlsPublicKey,
+    ) -> Result<(), SignerModuleError> {
+        if !self.is_key_allowed(module_id, pubkey) {
+            return Err(SignerModuleError::KeyNotAllowed(*pubkey));
+        }
+
+        Ok(())
+    }
+
+    /// Checks a request using a consensus key. Allowed requests reserve a
+    /// signature in the rate limits, which is given back if the returned
+    /// [`Reservation`] is dropped without being committed
+    pub async fn check_signature(
+        &self,
+        module_id: &str,
+        consensus: &BlsPublicKey,
+        key_use: KeyUse,
+    ) -> Result<Reservation<'_>, SignerModuleError> {
+        let Some(policy) = self.policies.get(module_id) else {
+            return Ok(Reservation { counted: None });
+        };
+
+        self.check_key(module_id, consensus)?;
+
+        if policy.proxy_only && key_use == KeyUse::Consensus {
+            return Err(SignerModuleError::ProxyOnly(module_id.to_owned()));
+        }
+
+        if !policy.allowed_hours.is_empty() {
+            let hour = ((utcnow_sec() % 86400) / 3600) as u8;
+            if !policy.allowed_hours.iter().any(|(start, end)| in_hours(hour, *start, *end)) {
+                return Err(SignerModuleError::OutsideSigningWindow(format!(
+                    "signing not allowed at {hour}:00 UTC"
+                )));
+            }
+        }
+
+        let slot = current_slot(self.chain);
+
+        if let Some(window) = policy.proposal_window_slots {
+            let Some(duties) = &self.duties else {
+                return Err(SignerModuleError::Internal("missing beacon node".to_owned()));
+            };
+
+            let proposing = duties
+                .has_proposal(consensus, slot, slot + window)
+                .await
+                .map_err(|err| SignerModuleError::Internal(err.to_string()))?;
+            if !proposing {
+                return Err(SignerModuleError::OutsideSigningWindow(format!(
+                    "no proposal of {consensus} in the next {window} slots"
+                )));
+            }
+        }
+
+        self.count_signature(module_id, policy, slot)?;
+
+        Ok(Reservation { counted: Some((self, module_id.to_owned(), slot)) })
+    }
+
+    /// Checks the decoded message of a structured request, or that the module
+    /// can sign raw object roots if `message` is None. Doesn't count towards
+    /// the rate limits, see [`PolicyEngine::check_signature`]
+    pub fn check_message(
+        &self,
+        module_id: &str,
+        message: Option<&DecodedMessage>,
+    ) -> Result<(), SignerModuleError> {
+        let Some(policy) = self.policies.get(module_id) else {
+            return Ok(());
+        };
+
+        let Some(message) = message else {
+            if policy.message_schemas.is_some() {
+                return Err(SignerModuleError::MessageNotAllowed(
+                    "only structured messages can be signed".to_owned(),
+                ));
+            }
+            return Ok(());
+        };
+
+        if policy.message_schemas.as_ref().is_some_and(|schemas| !schemas.contains(message.schema))
+        {
+            return Err(SignerModuleError::MessageNotAllowed(format!(
+                "schema {} not allowed",
+                message.schema
+            )));
+        }
+
+        if let (Some(lookahead), Some(slot)) = (policy.max_slot_lookahead, message.slot) {
+            let current = current_slot(self.chain);
+            if slot < current || slot > current.saturating_add(lookahead) {
+                return Err(SignerModuleError::MessageNotAllowed(format!(
+                    "slot {slot} not in the next {lookahead} slots"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn count_signature(
+        &self,
+        module_id: &str,
+        policy: &ModulePolicy,
+        slot: u64,
+    ) -> Result<(), SignerModuleError> {
+        let mut counters = self.counters.lock().expect("poisoned counters");
+        let counter = counters.entry(module_id.to_owned()).or_default();
+
+        if counter.slot != slot {
+            counter.slot = slot;
+            counter.in_slot = 0;
+        }
+
+        let epoch = slot / SLOTS_PER_EPOCH;
+        if counter.epoch != epoch {
+            counter.epoch = epoch;
+            counter.in_epoch = 0;
+        }
+
+        if policy.max_signatures_per_slot.is_some_and(|max| counter.in_slot >= max) {
+            return Err(SignerModuleError::RateLimited(format!(
+                "max signatures in slot {slot} reached"
+            )));
+        }
+
+        if policy.max_signatures_per_epoch.is_some_and(|max| counter.in_epoch >= max) {
+            return Err(SignerModuleError::RateLimited(format!(
+                "max signatures in epoch {epoch} reached"
+            )));
+        }
+
+        counter.in_slot += 1;
+        counter.in_epoch += 1;
+
+        Ok(())
+    }
+
+    /// Gives back a signature counted in `slot`, unless the counters moved on
+    fn release_signature(&self, module_id: &str, slot: u64) {
+        let mut counters = self.counters.lock().expect("poisoned counters");
+        let Some(counter) = counters.get_mut(module_id) else {
+            return;
+        };
+
+        if counter.slot == slot {
+            counter.in_slot = counter.in_slot.saturating_sub(1);
+        }
+        if counter.epoch == slot / SLOTS_PER_EPOCH {
+            counter.in_epoch = counter.in_epoch.saturating_sub(1);
+        }
+    }
+}
+
+/// Signature counted towards the rate limits of a module, released on drop
+/// unless the request succeeded
+#[must_use]
+pub struct Reservation<'a> {
+    counted: Option<(&'a PolicyEngine, String, u64)>,
+}
+
+impl Reservation<'_> {
+    /// Keeps the signature counted, once the request succeeded
+    pub fn commit(mut self) {
+        self.counted = None;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some((engine, module_id, slot)) = self.counted.take() {
+            engine.release_signature(&module_id, slot);
+        }
+    }
+}
+
+/// Whether the hour is in `[start, end)`, wrapping around midnight if `start`
+/// is after `end`
+fn in_hours(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end {
+        start <= hour && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Proposer duties fetched from the beacon node, cached by epoch
+struct ProposerDuties {
+    client: reqwest::Client,
+    url: Url,
+    by_epoch: RwLock<HashMap<u64, Vec<ProposerDuty>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProposerDutiesResponse {
+    data: Vec<ProposerDuty>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProposerDuty {
+    pubkey: BlsPublicKey,
+    #[serde(with = "as_str")]
+    slot: u64,
+}
+
+impl ProposerDuties {
+    fn new(url: Url) -> eyre::Result<Self> {
+        let client = reqwest::Client::builder().timeout(DEFAULT_REQUEST_TIMEOUT).build()?;
+        Ok(Self { client, url, by_epoch: Default::default() })
+    }
+
+    /// Whether the key proposes in any slot of `[from, to]`
+    async fn has_proposal(&self, pubkey: &BlsPublicKey, from: u64, to: u64) -> eyre::Result<bool> {
+        for epoch in from / SLOTS_PER_EPOCH..=to / SLOTS_PER_EPOCH {
+            let duties = self.get_duties(epoch).await?;
+            if duties.iter().any(|duty| duty.pubkey == *pubkey && (from..=to).contains(&duty.slot))
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn get_duties(&self, epoch: u64) -> eyre::Result<Vec<ProposerDuty>> {
+        if let Some(duties) = self.by_epoch.read().await.get(&epoch) {
+            return Ok(duties.clone());
+        }
+
+        let url = self.url.join(&format!("eth/v1/validator/duties/proposer/{epoch}"))?;
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .wrap_err("failed to reach beacon node")?
+            .error_for_status()?
+            .json::<ProposerDutiesResponse>()
+            .await?;
+
+        let mut by_epoch = self.by_epoch.write().await;
+        // only the current and next epoch are ever needed
+        by_epoch.retain(|cached, _| *cached + 1 >= epoch);
+        by_epoch.insert(epoch, res.data.clone());
+
+        Ok(res.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use cb_common::{config::SigningPolicy, types::Chain};
+
+    use super::{in_hours, PolicyEngine};
+
+    #[test]
+    fn test_in_hours() {
+        assert!(in_hours(8, 8, 20));
+        assert!(in_hours(19, 8, 20));
+        assert!(!in_hours(20, 8, 20));
+        assert!(!in_hours(7, 8, 20));
+
+        assert!(in_hours(23, 22, 6));
+        assert!(in_hours(0, 22, 6));
+        assert!(in_hours(5, 22, 6));
+        assert!(!in_hours(6, 22, 6));
+        assert!(!in_hours(12, 22, 6));
+    }
+
+    #[test]
+    fn test_empty_hours_rejected() {
+        let policy = SigningPolicy { allowed_hours: vec![(8, 8)], ..Default::default() };
+        let policies = HashMap::from([("module".to_owned(), policy)]);
+        assert!(PolicyEngine::new(Chain::Holesky, policies, &HashMap::new(), None).is_err());
+    }
+}
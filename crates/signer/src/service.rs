@@ -1,28 +1,85 @@
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, DirBuilder, Permissions},
+    net::SocketAddr,
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock as StdRwLock},
+    time::Instant,
+};
 
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::beacon::{relay::ValidatorRegistrationMessage, BlsPublicKey, BlsSignature},
+};
 use axum::{
     extract::State,
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post},
-    Json,
+    Json, Router,
 };
 use axum_extra::TypedHeader;
 use cb_common::{
     commit::{
-        client::GetPubkeysResponse,
-        constants::{GET_PUBKEYS_PATH, REQUEST_SIGNATURE_PATH},
-        request::SignRequest,
+        client::{BatchSignatureResult, GetPubkeysResponse, VerificationResponse},
+        constants::{
+            GENERATE_PROXY_KEY_PATH, GET_DELEGATION_PATH, GET_PUBKEYS_PATH, HEALTH_PATH,
+            MAX_BATCH_SIGNATURES, PARTIAL_SIGNATURE_PATH, READY_PATH,
+            REQUEST_ECDSA_MESSAGE_SIGNATURE_PATH, REQUEST_ECDSA_SIGNATURE_PATH,
+            REQUEST_MESSAGE_SIGNATURE_PATH, REQUEST_SIGNATURES_PATH, REQUEST_SIGNATURE_PATH,
+            REQUEST_VALIDATOR_REGISTRATION_PATH, REVOKE_PROXY_KEY_PATH, VERIFY_DELEGATION_PATH,
+            VERIFY_ECDSA_SIGNATURE_PATH, VERIFY_SIGNATURE_PATH,
+        },
+        request::{
+            EncryptionScheme, GenerateProxyRequest, GetDelegationRequest, ProxyDelegation, ProxyId,
+            ProxyKey, RevokeProxyRequest, RevokedProxy, SignEcdsaMessageRequest, SignEcdsaRequest,
+            SignMessageRequest, SignRequest, SignValidatorRegistrationRequest, SignatureDomain,
+            SignedProxyDelegation, VerifyDelegationRequestBls, VerifyDelegationRequestEcdsa,
+            VerifyEcdsaSignatureRequest, VerifySignatureRequest,
+        },
+        schema::{DecodedMessage, SchemaMessage},
+    },
+    config::{
+        listen_address, load_jwts_file, SignerSocketConfig, StartSignerConfig, TlsConfig,
+        SIGNER_SOCKET_FILE,
     },
-    config::StartSignerConfig,
+    jwt::{decode_jwt_module, validate_jwt},
+    loader::{load_validators_dir, ValidatorKeysFormat},
+    server::{serve, serve_connection, AcceptBackoff},
+    signature::{compute_module_object_root, verify_signature},
+    signer::{
+        load_threshold_signers, parse_threshold_peer_id, threshold_peer_id, AuditEvent,
+        AuditLogWriter, AuditRecord, EcdsaSignature, ModuleRequest, ModuleRequestKind,
+        PartialSignatureRequest, PartialSignatureResponse, ProxyStore,
+    },
+    types::Chain,
 };
-use eyre::WrapErr;
+use cb_metrics::provider::MetricsProvider;
+use eyre::{ensure, eyre, OptionExt, WrapErr};
 use headers::{authorization::Bearer, Authorization};
-use tokio::net::TcpListener;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, UnixListener},
+    signal::unix::{signal, SignalKind},
+    sync::RwLock,
+};
+use tokio_rustls::rustls::ServerConfig;
 use tracing::{debug, error, info, warn};
+use tree_hash::TreeHash;
 use uuid::Uuid;
 
-use crate::{error::SignerModuleError, manager::SigningManager};
+use crate::{
+    error::SignerModuleError,
+    hardening::harden_process,
+    keymanager::run_keymanager,
+    manager::{delegation_domain, SigningManager},
+    metrics::{
+        outcome_tag, AUTH_FAILURES, CONSENSUS_KEY_TAG, PROXY_BLS_KEY_TAG, PROXY_ECDSA_KEY_TAG,
+        SIGNATURE_REQUESTS, SIGNER_METRICS_REGISTRY, SIGNING_LATENCY,
+    },
+    policy::{KeyUse, PolicyEngine, Reservation},
+};
 
 /// Implements the Signer API and provides a service for signing requests
 pub struct SigningService;
@@ -30,61 +87,418 @@ pub struct SigningService;
 #[derive(Clone)]
 struct SigningState {
     /// Mananger handling different signing methods
-    manager: Arc<SigningManager>,
-    /// Map of module ids to JWTs. This also acts as registry of all modules
-    /// running
-    jwts: HashMap<String, String>,
+    manager: Arc<RwLock<SigningManager>>,
+    /// Map of module ids to their JWT secrets. This also acts as registry of
+    /// all modules running
+    jwts: Arc<StdRwLock<HashMap<String, Vec<String>>>>,
+    chain: Chain,
+    /// Modules which can sign with the builder domain, all others get
+    /// signatures scoped to their id
+    builder_domain_modules: Arc<HashSet<String>>,
+    /// Per-module restrictions on keys, rates and signing times
+    policy: Arc<PolicyEngine>,
+    audit: Option<Arc<Mutex<AuditLogWriter>>>,
+    /// Secrets shared with the other nodes of the distributed validator
+    /// cluster, by share index, to authenticate them
+    threshold_secrets: Arc<HashMap<u64, String>>,
+}
+
+/// Returned by the readiness endpoint. The signer is ready once at least one
+/// consensus key is loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub consensus: usize,
+    pub proxy_bls: usize,
+    pub proxy_ecdsa: usize,
 }
 
 impl SigningService {
     pub async fn run(config: StartSignerConfig) -> eyre::Result<()> {
+        harden_process(config.hardening)?;
+
         if config.jwts.is_empty() {
             warn!("Signing service was started but no module is registered. Exiting");
             return Ok(());
         } else {
-            info!(modules =? config.jwts.keys(), port =? config.server_port, "Starting signing service");
+            info!(
+                modules =? config.jwts.keys(),
+                host =% config.server_host,
+                port = config.server_port,
+                tls = config.tls.is_some(),
+                "Starting signing service"
+            );
         }
 
-        let mut manager = SigningManager::new(config.chain);
+        let policy = PolicyEngine::new(
+            config.chain,
+            config.policies,
+            &config.key_groups,
+            config.beacon_url,
+        )?;
 
-        // TODO: load proxy keys, or pass already loaded?
-        for signer in config.loader.load_keys()? {
+        let audit = match &config.audit {
+            Some(audit) => {
+                let writer = audit.open()?;
+                info!(path =? audit.file_path(), "Writing audit log");
+                Some(Arc::new(Mutex::new(writer)))
+            }
+            None => None,
+        };
+
+        let mut manager = SigningManager::new(config.chain, config.store);
+
+        let mut signers = config.loader.load_keys().await?;
+        if let Some(threshold) = &config.threshold {
+            signers = load_threshold_signers(threshold, &config.threshold_secrets, signers)?;
+            info!(
+                share_index = threshold.share_index,
+                threshold = threshold.threshold,
+                accounts = threshold.accounts.len(),
+                "Signing as part of a distributed validator cluster"
+            );
+        }
+
+        for signer in signers {
             manager.add_consensus_signer(signer);
         }
 
-        let state = SigningState { manager: manager.into(), jwts: config.jwts };
+        // keys imported via the keymanager API in a previous run
+        if let Some(keymanager) = &config.keymanager {
+            fs::create_dir_all(&keymanager.keys_path)?;
+            fs::create_dir_all(&keymanager.secrets_path)?;
+            let imported = load_validators_dir(
+                &keymanager.keys_path,
+                &keymanager.secrets_path,
+                ValidatorKeysFormat::Lighthouse,
+            )?;
+            for signer in imported {
+                manager.add_consensus_signer(signer);
+            }
+        }
+
+        manager.load_proxies()?;
+        info!(
+            bls = manager.delegations().len(),
+            ecdsa = manager.ecdsa_delegations().len(),
+            "Loaded proxy keys"
+        );
+
+        if !config.builder_domain_modules.is_empty() {
+            let modules = &config.builder_domain_modules;
+            warn!(?modules, "Modules allowed to sign with the builder domain");
+        }
+
+        let manager = Arc::new(RwLock::new(manager));
+
+        // bound here, so that the signer doesn't start without it
+        if let Some(keymanager) = config.keymanager {
+            let token = config.keymanager_token.ok_or_eyre("keymanager token is not set")?;
+            let keymanager_address = listen_address(keymanager.host, keymanager.port)?;
+            let listener = TcpListener::bind(keymanager_address)
+                .await
+                .wrap_err("failed keymanager tcp binding")?;
+            info!(address = %keymanager_address, "Starting keymanager API");
+
+            tokio::spawn(run_keymanager(listener, manager.clone(), keymanager, token));
+        }
+
+        let state = SigningState {
+            manager,
+            jwts: Arc::new(StdRwLock::new(config.jwts)),
+            chain: config.chain,
+            builder_domain_modules: config.builder_domain_modules.into(),
+            policy: policy.into(),
+            audit,
+            threshold_secrets: config.threshold_secrets.into(),
+        };
 
         let app = axum::Router::new()
             .route(REQUEST_SIGNATURE_PATH, post(handle_request_signature))
+            .route(REQUEST_SIGNATURES_PATH, post(handle_request_signatures))
+            .route(REQUEST_ECDSA_SIGNATURE_PATH, post(handle_request_ecdsa_signature))
+            .route(REQUEST_MESSAGE_SIGNATURE_PATH, post(handle_request_message_signature))
+            .route(REQUEST_VALIDATOR_REGISTRATION_PATH, post(handle_request_validator_registration))
+            .route(
+                REQUEST_ECDSA_MESSAGE_SIGNATURE_PATH,
+                post(handle_request_ecdsa_message_signature),
+            )
+            .route(GENERATE_PROXY_KEY_PATH, post(handle_generate_proxy))
+            .route(REVOKE_PROXY_KEY_PATH, post(handle_revoke_proxy))
+            .route(GET_DELEGATION_PATH, post(handle_get_delegation))
+            .route(VERIFY_SIGNATURE_PATH, post(handle_verify_signature))
+            .route(VERIFY_ECDSA_SIGNATURE_PATH, post(handle_verify_ecdsa_signature))
+            .route(VERIFY_DELEGATION_PATH, post(handle_verify_delegation))
             .route(GET_PUBKEYS_PATH, get(handle_get_pubkeys))
-            .with_state(state);
+            .route(HEALTH_PATH, get(handle_health))
+            .route(READY_PATH, get(handle_ready))
+            .with_state(state.clone());
+
+        if let Some(path) = config.jwts_path {
+            tokio::spawn(reload_jwts(path, state.jwts.clone()));
+        }
+
+        let address = SocketAddr::new(config.server_host, config.server_port);
+        let tls = config.tls.as_ref().map(TlsConfig::server_config).transpose()?;
+
+        // the other nodes of the cluster request partial signatures on their own port,
+        // which doesn't serve the Signer API
+        if let Some(threshold) = &config.threshold {
+            let peer_app = axum::Router::new()
+                .route(PARTIAL_SIGNATURE_PATH, post(handle_partial_signature))
+                .with_state(state.clone());
+            // peers are verified with the cluster CA, if set, instead of the client CA of modules
+            let peer_tls = match (&config.tls, &threshold.tls) {
+                (Some(tls), peer_tls) => Some(
+                    TlsConfig {
+                        client_ca_cert_path: peer_tls.as_ref().map(|tls| tls.ca_cert_path.clone()),
+                        ..tls.clone()
+                    }
+                    .server_config()?,
+                ),
+                (None, Some(_)) => {
+                    eyre::bail!("peer certificates need the signer to be served over TLS")
+                }
+                (None, None) => None,
+            };
+            let peer_address = SocketAddr::new(config.server_host, threshold.port);
+            let listener =
+                TcpListener::bind(peer_address).await.wrap_err("failed peer tcp binding")?;
+            info!(address = %peer_address, "Serving partial signatures to the cluster");
+
+            tokio::spawn(async move {
+                if let Err(err) = serve(listener, peer_tls, peer_app).await {
+                    error!(?err, "Peer server exited")
+                }
+            });
+        }
+
+        match config.socket {
+            Some(socket) => serve_unix(&socket, app).await,
+            None => serve_tcp(address, tls, app).await,
+        }
+    }
+
+    pub fn init_metrics() -> eyre::Result<()> {
+        MetricsProvider::load_and_run(SIGNER_METRICS_REGISTRY.clone())
+    }
+}
+
+impl SigningState {
+    /// Validates the bearer token, and returns the id of the module it was
+    /// issued for
+    fn authenticate_module(
+        &self,
+        auth: &Authorization<Bearer>,
+        req_id: Uuid,
+    ) -> Result<String, SignerModuleError> {
+        let module_id = decode_jwt_module(auth.token()).map_err(|err| {
+            warn!(%req_id, %err, "Unauthorized request. Was the module started correctly?");
+            AUTH_FAILURES.with_label_values(&["invalid_jwt"]).inc();
+            SignerModuleError::Unauthorized
+        })?;
+
+        let jwts = self.jwts.read().expect("poisoned jwts");
+        let Some(secrets) = jwts.get(&module_id) else {
+            warn!(%module_id, %req_id, "Unknown module id. Was the module started correctly?");
+            AUTH_FAILURES.with_label_values(&["unknown_module"]).inc();
+            return Err(SignerModuleError::UnknownModuleId(module_id));
+        };
+
+        if let Err(err) = validate_jwt(auth.token(), &module_id, secrets) {
+            warn!(
+                %module_id,
+                %req_id,
+                %err,
+                "Unauthorized request. Was the module started correctly?"
+            );
+            AUTH_FAILURES.with_label_values(&["invalid_jwt"]).inc();
+            return Err(SignerModuleError::Unauthorized);
+        }
+
+        Ok(module_id)
+    }
+
+    /// Checks that the bearer token is a valid JWT of the given module.
+    /// Rejected requests are written to the audit log
+    async fn authenticate(
+        &self,
+        auth: &Authorization<Bearer>,
+        module_id: &str,
+        event: AuditEvent,
+        req_id: Uuid,
+    ) -> Result<(), SignerModuleError> {
+        let res = self.validate_module_jwt(auth, module_id, req_id);
+        if let Err(err) = &res {
+            let record =
+                AuditRecord::rejected(req_id, module_id.to_owned(), event, err.to_string());
+            self.audit(record).await?;
+        }
+
+        res
+    }
+
+    fn validate_module_jwt(
+        &self,
+        auth: &Authorization<Bearer>,
+        module_id: &str,
+        req_id: Uuid,
+    ) -> Result<(), SignerModuleError> {
+        let jwt_module_id = self.authenticate_module(auth, req_id)?;
+        if jwt_module_id != module_id {
+            warn!(module_id, %jwt_module_id, %req_id, "JWT issued for a different module");
+            AUTH_FAILURES.with_label_values(&["wrong_module"]).inc();
+            return Err(SignerModuleError::Unauthorized);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the bearer token is a JWT of a peer of the cluster, signed
+    /// with the secret shared with it, and returns the share index of the peer
+    fn authenticate_peer(
+        &self,
+        auth: &Authorization<Bearer>,
+        req_id: Uuid,
+    ) -> Result<u64, SignerModuleError> {
+        let peer = decode_jwt_module(auth.token()).ok().and_then(|id| parse_threshold_peer_id(&id));
+        let Some((share_index, secret)) =
+            peer.and_then(|peer| self.threshold_secrets.get_key_value(&peer))
+        else {
+            warn!(%req_id, "Partial signature request from an unknown peer");
+            AUTH_FAILURES.with_label_values(&["unknown_peer"]).inc();
+            return Err(SignerModuleError::Unauthorized);
+        };
+
+        let peer_id = threshold_peer_id(*share_index);
+        if let Err(err) = validate_jwt(auth.token(), &peer_id, &[secret.clone()]) {
+            warn!(%req_id, peer = share_index, %err, "Unauthorized partial signature request");
+            AUTH_FAILURES.with_label_values(&["invalid_peer_jwt"]).inc();
+            return Err(SignerModuleError::Unauthorized);
+        }
 
-        let address = SocketAddr::from(([0, 0, 0, 0], config.server_port));
-        let listener = TcpListener::bind(address).await.wrap_err("failed tcp binding")?;
+        Ok(*share_index)
+    }
+
+    /// Domain and object root to sign for a module request. Unless the module
+    /// is privileged and asks for the builder domain, the object root is
+    /// scoped to the module and signed with the commit boost domain
+    fn signing_target(
+        &self,
+        module_id: &str,
+        builder_domain: bool,
+        object_root: [u8; 32],
+        req_id: Uuid,
+    ) -> Result<([u8; 32], [u8; 32]), SignerModuleError> {
+        if !builder_domain {
+            let object_root = compute_module_object_root(module_id, object_root);
+            return Ok((self.chain.commit_boost_domain(), object_root));
+        }
+
+        if !self.builder_domain_modules.contains(module_id) {
+            warn!(module_id, %req_id, "Module not allowed to sign with the builder domain");
+            return Err(SignerModuleError::BuilderDomainNotAllowed(module_id.to_owned()));
+        }
 
-        if let Err(err) = axum::serve(listener, app).await {
-            error!(?err, "Signing server exited")
+        Ok((self.chain.builder_domain(), object_root))
+    }
+
+    /// Checks a signature request against the module policy, `consensus` is the
+    /// key signing or delegating to the proxy. The reservation must be
+    /// committed once the signature is made
+    async fn check_policy(
+        &self,
+        module_id: &str,
+        consensus: &BlsPublicKey,
+        key_use: KeyUse,
+        req_id: Uuid,
+    ) -> Result<Reservation<'_>, SignerModuleError> {
+        self.policy.check_signature(module_id, consensus, key_use).await.inspect_err(|err| {
+            warn!(module_id, %req_id, %err, "Signature request denied by policy");
+        })
+    }
+
+    /// Checks the decoded message of a structured request against the module
+    /// policy, or that the module can sign raw object roots
+    fn check_message(
+        &self,
+        module_id: &str,
+        message: Option<&DecodedMessage>,
+        req_id: Uuid,
+    ) -> Result<(), SignerModuleError> {
+        if let Err(err) = self.policy.check_message(module_id, message) {
+            warn!(module_id, %req_id, %err, "Signature request denied by policy");
+            return Err(err);
         }
+
         Ok(())
     }
+
+    /// Writes the request to the audit log, if enabled. Failing to do so fails
+    /// the request, so that no signature is returned without being logged.
+    /// Entries are synced to disk, so this runs on a blocking thread
+    async fn audit(&self, record: AuditRecord) -> Result<(), SignerModuleError> {
+        let Some(audit) = self.audit.clone() else {
+            return Ok(());
+        };
+
+        tokio::task::spawn_blocking(move || {
+            audit.lock().expect("poisoned audit log").append(record)
+        })
+        .await
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?
+        .map_err(|err| {
+            error!(?err, "Failed to write audit log");
+            SignerModuleError::Internal("failed to write audit log".to_owned())
+        })
+    }
 }
 
-/// Implements get_pubkeys from the Signer API
+/// Implements get_pubkeys from the Signer API. Proxy pubkeys are only returned
+/// to the module that generated them, and consensus pubkeys are filtered by the
+/// module policy. Revoked and expired proxies are only listed with their status
 async fn handle_get_pubkeys(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
     State(state): State<SigningState>,
 ) -> Result<impl IntoResponse, SignerModuleError> {
     let req_id = Uuid::new_v4();
+    let module_id = state.authenticate_module(&auth, req_id)?;
 
-    debug!(event = "get_pubkeys", ?req_id, "New request");
+    debug!(event = "get_pubkeys", %module_id, %req_id, "New request");
 
-    let consensus = state.manager.consensus_pubkeys();
-    let proxy = state.manager.proxy_pubkeys();
+    let manager = state.manager.read().await;
+    let mut consensus = manager.consensus_pubkeys();
+    consensus.retain(|pubkey| state.policy.is_key_allowed(&module_id, pubkey));
+    let proxy = manager.proxy_pubkeys(&module_id);
+    let proxy_ecdsa = manager.proxy_addresses(&module_id);
+    let proxy_status = manager.proxy_status(&module_id);
 
-    let res = GetPubkeysResponse { consensus, proxy };
+    let res = GetPubkeysResponse { consensus, proxy, proxy_ecdsa, proxy_status };
 
     Ok((StatusCode::OK, Json(res)).into_response())
 }
 
+/// Liveness check, doesn't require authentication
+async fn handle_health() -> impl IntoResponse {
+    StatusCode::OK
+}
+
+/// Readiness check, doesn't require authentication. Returns 503 if no
+/// consensus key is loaded
+async fn handle_ready(State(state): State<SigningState>) -> impl IntoResponse {
+    let manager = state.manager.read().await;
+    let consensus = manager.consensus_pubkeys().len();
+    let res = ReadinessResponse {
+        ready: consensus > 0,
+        consensus,
+        proxy_bls: manager.delegations().len(),
+        proxy_ecdsa: manager.ecdsa_delegations().len(),
+    };
+
+    let status = if res.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(res))
+}
+
 /// Implements request_signature from the Signer API
 async fn handle_request_signature(
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
@@ -93,23 +507,967 @@ async fn handle_request_signature(
 ) -> Result<impl IntoResponse, SignerModuleError> {
     let req_id = Uuid::new_v4();
 
-    if let Some(jwt) = state.jwts.get(&request.id) {
-        if !auth.token().contains(jwt) {
-            warn!(module_id=?request.id, ?req_id, "Unauthorized request. Was the module started correctly?");
+    state.authenticate(&auth, &request.id, AuditEvent::RequestSignature, req_id).await?;
+
+    debug!(event = "request_signature", module_id = %request.id, %req_id, "New request");
+
+    let forwarded = forwarded_request(&request, auth.token(), None);
+    let sig = sign_and_audit(&state, request, None, None, forwarded, req_id).await?;
+
+    Ok((StatusCode::OK, Json(sig)).into_response())
+}
+
+/// Implements request_message_signature from the Signer API. The message is
+/// decoded and hashed here, and checked against the module policy
+async fn handle_request_message_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<SignMessageRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::RequestSignature, req_id).await?;
+
+    debug!(
+        event = "request_message_signature",
+        module_id = %request.id,
+        schema = %request.message.schema,
+        %req_id,
+        "New request"
+    );
+
+    let message = decode_message(&request.id, &request.message, req_id)?;
+    let raw_request = request.to_raw_request(message.object_root);
+    let forwarded = forwarded_request(&raw_request, auth.token(), Some(&request.message));
+    let sig = sign_and_audit(&state, raw_request, Some(message), None, forwarded, req_id).await?;
+
+    Ok((StatusCode::OK, Json(sig)).into_response())
+}
+
+/// Implements request_validator_registration from the Signer API. The
+/// registration is signed with the builder domain, so this is only allowed
+/// for privileged modules
+async fn handle_request_validator_registration(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<SignValidatorRegistrationRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::RequestSignature, req_id).await?;
+
+    debug!(
+        event = "request_validator_registration",
+        module_id = %request.id,
+        pubkey = %request.registration.pubkey,
+        %req_id,
+        "New request"
+    );
+
+    let raw_request = request.to_raw_request();
+    let forwarded = forwarded_request(&raw_request, auth.token(), None);
+    let registration = Some(&request.registration);
+    let sig = sign_and_audit(&state, raw_request, None, registration, forwarded, req_id).await?;
+
+    Ok((StatusCode::OK, Json(sig)).into_response())
+}
+
+/// Decodes the message of a structured request, and logs its summary
+fn decode_message(
+    module_id: &str,
+    message: &SchemaMessage,
+    req_id: Uuid,
+) -> Result<DecodedMessage, SignerModuleError> {
+    let decoded = message.decode().map_err(|err| {
+        warn!(module_id, %req_id, %err, "Invalid structured message");
+        SignerModuleError::InvalidRequest(err.to_string())
+    })?;
+    info!(
+        module_id,
+        %req_id,
+        schema = decoded.schema,
+        summary = decoded.summary,
+        "Structured message request"
+    );
+
+    Ok(decoded)
+}
+
+/// Implements request_signatures from the Signer API. Requests are signed in
+/// order, and each one has its own result
+async fn handle_request_signatures(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(requests): Json<Vec<SignRequest>>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    let res = state.authenticate_module(&auth, req_id).and_then(|module_id| {
+        if let Some(request) = requests.iter().find(|request| request.id != module_id) {
+            warn!(%module_id, other = %request.id, %req_id, "Batch with requests of another module");
+            AUTH_FAILURES.with_label_values(&["wrong_module"]).inc();
             return Err(SignerModuleError::Unauthorized);
         }
-    } else {
-        warn!(module_id=?request.id, ?req_id, "Unknown module id. Was the module started correctly?");
-        return Err(SignerModuleError::UnknownModuleId(request.id));
+        Ok(module_id)
+    });
+    let module_id = match res {
+        Ok(module_id) => module_id,
+        Err(err) => {
+            let module_id = requests.first().map(|request| request.id.clone()).unwrap_or_default();
+            let event = AuditEvent::RequestSignature;
+            state.audit(AuditRecord::rejected(req_id, module_id, event, err.to_string())).await?;
+            return Err(err);
+        }
+    };
+
+    debug!(event = "request_signatures", %module_id, %req_id, len = requests.len(), "New request");
+
+    if requests.len() > MAX_BATCH_SIGNATURES {
+        return Err(SignerModuleError::BatchTooLarge(requests.len(), MAX_BATCH_SIGNATURES));
+    }
+
+    let mut results = Vec::with_capacity(requests.len());
+    for request in requests {
+        let forwarded = forwarded_request(&request, auth.token(), None);
+        let res = match sign_and_audit(&state, request, None, None, forwarded, req_id).await {
+            Ok(sig) => BatchSignatureResult::Signature(sig),
+            Err(err) => BatchSignatureResult::Error {
+                code: err.status_code().as_u16(),
+                message: err.to_string(),
+            },
+        };
+        results.push(res);
     }
 
-    debug!(event = "request_signature", module_id=?request.id, ?req_id, "New request");
+    Ok((StatusCode::OK, Json(results)).into_response())
+}
+
+/// Module request the peers of the cluster check again, if the request is
+/// signed with a distributed key
+fn forwarded_request(
+    request: &SignRequest,
+    jwt: &str,
+    message: Option<&SchemaMessage>,
+) -> ModuleRequest {
+    ModuleRequest {
+        module_id: request.id.clone(),
+        jwt: jwt.to_owned(),
+        kind: ModuleRequestKind::Signature {
+            builder_domain: request.builder_domain,
+            object_root: request.object_root.into(),
+            message: message.cloned(),
+        },
+    }
+}
+
+/// Signs the request, and writes it to the audit log. `message` is the decoded
+/// message of structured requests, and `registration` the validator
+/// registration of registration requests, whose root is the one of the request
+async fn sign_and_audit(
+    state: &SigningState,
+    request: SignRequest,
+    message: Option<DecodedMessage>,
+    registration: Option<&ValidatorRegistrationMessage>,
+    forwarded: ModuleRequest,
+    req_id: Uuid,
+) -> Result<BlsSignature, SignerModuleError> {
+    let start_request = Instant::now();
+    let res =
+        sign_request(state, &request, message.as_ref(), registration, &forwarded, req_id).await;
+
+    let key_type = if request.is_proxy { PROXY_BLS_KEY_TAG } else { CONSENSUS_KEY_TAG };
+    SIGNING_LATENCY.with_label_values(&[key_type]).observe(start_request.elapsed().as_secs_f64());
+    SIGNATURE_REQUESTS.with_label_values(&[&request.id, key_type, outcome_tag(&res)]).inc();
+
+    state
+        .audit(AuditRecord {
+            req_id,
+            module_id: request.id,
+            event: AuditEvent::RequestSignature,
+            pubkey: Some(request.pubkey),
+            address: None,
+            is_proxy: request.is_proxy,
+            object_root: Some(request.object_root.into()),
+            builder_domain: request.builder_domain,
+            message: message.map(|message| message.summary),
+            signature: res.as_ref().ok().map(|sig| sig.0.to_vec().into()),
+            error: res.as_ref().err().map(ToString::to_string),
+        })
+        .await?;
+
+    res
+}
+
+async fn sign_request(
+    state: &SigningState,
+    request: &SignRequest,
+    message: Option<&DecodedMessage>,
+    registration: Option<&ValidatorRegistrationMessage>,
+    forwarded: &ModuleRequest,
+    req_id: Uuid,
+) -> Result<BlsSignature, SignerModuleError> {
+    let (domain, object_root) =
+        state.signing_target(&request.id, request.builder_domain, request.object_root, req_id)?;
+    state.check_message(&request.id, message, req_id)?;
+
+    let (consensus, key_use) = if request.is_proxy {
+        let manager = state.manager.read().await;
+        // proxies of other modules are treated as unknown
+        if !manager.has_proxy(&request.id, &request.pubkey) {
+            return Err(SignerModuleError::UnknownProxySigner(request.pubkey));
+        }
+        (manager.get_delegation(&request.pubkey)?.message.delegator, KeyUse::Proxy)
+    } else {
+        (request.pubkey, KeyUse::Consensus)
+    };
+
+    // the lock is not held while the policy may fetch the proposer duties. A
+    // proxy is only ever delegated by one consensus key, and its owner and
+    // status are checked again when signing
+    let reservation = state.check_policy(&request.id, &consensus, key_use, req_id).await?;
 
-    let sig = if request.is_proxy {
-        state.manager.sign_proxy(&request.pubkey, &request.object_root).await
+    let manager = state.manager.read().await;
+    let signature = if request.is_proxy {
+        manager.sign_proxy(&request.id, &request.pubkey, domain, &object_root).await?
+    } else if let Some(registration) = registration {
+        manager.sign_validator_registration(registration, Some(forwarded)).await?
     } else {
-        state.manager.sign_consensus(&request.pubkey, &request.object_root).await
-    }?;
+        manager.sign_consensus(&request.pubkey, domain, &object_root, Some(forwarded)).await?
+    };
+    reservation.commit();
+
+    Ok(signature)
+}
+
+/// Signs with the local share of a distributed validator key, for another node
+/// of the cluster. The module request the node forwards is authenticated and
+/// checked against the config of this node, as if the module sent it here
+async fn handle_partial_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<PartialSignatureRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+    let module_id = request.request.module_id.clone();
+    let event = AuditEvent::PartialSignature;
+
+    let peer = match state.authenticate_peer(&auth, req_id) {
+        Ok(peer) => peer,
+        Err(err) => {
+            let record = AuditRecord::rejected(req_id, module_id, event, err.to_string());
+            state.audit(AuditRecord { pubkey: Some(request.pubkey), ..record }).await?;
+            return Err(err);
+        }
+    };
+    let module_auth = Authorization::bearer(&request.request.jwt)
+        .map_err(|_| SignerModuleError::InvalidRequest("invalid module jwt".to_owned()))?;
+    state.authenticate(&module_auth, &module_id, event, req_id).await?;
+
+    debug!(
+        event = "partial_signature",
+        peer,
+        %module_id,
+        pubkey = %request.pubkey,
+        %req_id,
+        "New request"
+    );
+
+    let res = sign_partial(&state, &request, req_id).await;
+
+    let (object_root, builder_domain) = match &request.request.kind {
+        ModuleRequestKind::Signature { builder_domain, object_root, .. } => {
+            (Some(*object_root), *builder_domain)
+        }
+        ModuleRequestKind::Delegation { .. } | ModuleRequestKind::Revocation { .. } => {
+            (None, delegation_domain(state.chain) == state.chain.builder_domain())
+        }
+    };
+    state
+        .audit(AuditRecord {
+            req_id,
+            module_id,
+            event,
+            pubkey: Some(request.pubkey),
+            address: None,
+            is_proxy: false,
+            object_root,
+            builder_domain,
+            message: None,
+            signature: res.as_ref().ok().map(|(_, sig)| sig.0.to_vec().into()),
+            error: res.as_ref().err().map(ToString::to_string),
+        })
+        .await?;
+
+    let (share_index, signature) = res?;
+    Ok((StatusCode::OK, Json(PartialSignatureResponse { share_index, signature })).into_response())
+}
+
+/// Checks the forwarded module request as [sign_request] and [generate_proxy]
+/// do, and signs it with the local share. Revocations are not checked against
+/// the proxies, which only the node that generated them holds, but the module
+/// must still be allowed to use the consensus key
+async fn sign_partial(
+    state: &SigningState,
+    request: &PartialSignatureRequest,
+    req_id: Uuid,
+) -> Result<(u64, BlsSignature), SignerModuleError> {
+    let module_id = &request.request.module_id;
+    let delegator = request.pubkey;
+
+    let manager = state.manager.read().await;
+    let (domain, object_root, key_use) = match &request.request.kind {
+        ModuleRequestKind::Signature { builder_domain, object_root, message } => {
+            let message = message
+                .as_ref()
+                .map(|message| decode_message(module_id, message, req_id))
+                .transpose()?;
+            if message.as_ref().is_some_and(|message| message.object_root != object_root.0) {
+                return Err(SignerModuleError::InvalidRequest(
+                    "object root doesn't match the message".to_owned(),
+                ));
+            }
+            state.check_message(module_id, message.as_ref(), req_id)?;
+
+            let (domain, object_root) =
+                state.signing_target(module_id, *builder_domain, object_root.0, req_id)?;
+            (domain, object_root, Some(KeyUse::Consensus))
+        }
+        ModuleRequestKind::Delegation { proxy, expiry_epoch } => {
+            manager.check_expiry(*expiry_epoch)?;
+            let expiry_epoch = *expiry_epoch;
+            let object_root = match *proxy {
+                ProxyId::Bls(proxy) => {
+                    ProxyDelegation { delegator, proxy, expiry_epoch }.tree_hash_root()
+                }
+                ProxyId::Ecdsa(proxy) => {
+                    ProxyDelegation { delegator, proxy, expiry_epoch }.tree_hash_root()
+                }
+            };
+            (delegation_domain(state.chain), object_root.0, Some(KeyUse::Delegation))
+        }
+        ModuleRequestKind::Revocation { proxy, revoked_epoch } => {
+            state.policy.check_key(module_id, &delegator).inspect_err(|err| {
+                warn!(module_id, %req_id, %err, "Revocation request denied by policy");
+            })?;
+            let revoked_epoch = *revoked_epoch;
+            if revoked_epoch > manager.current_epoch() {
+                return Err(SignerModuleError::InvalidRequest(format!(
+                    "revocation epoch {revoked_epoch} is in the future"
+                )));
+            }
+            let object_root = match *proxy {
+                ProxyId::Bls(proxy) => {
+                    RevokedProxy { delegator, proxy, revoked_epoch }.tree_hash_root()
+                }
+                ProxyId::Ecdsa(proxy) => {
+                    RevokedProxy { delegator, proxy, revoked_epoch }.tree_hash_root()
+                }
+            };
+            (delegation_domain(state.chain), object_root.0, None)
+        }
+    };
+
+    let reservation = match key_use {
+        Some(key_use) => Some(state.check_policy(module_id, &delegator, key_use, req_id).await?),
+        None => None,
+    };
+    let res = manager.sign_share(&delegator, domain, &object_root).await?;
+    if let Some(reservation) = reservation {
+        reservation.commit();
+    }
+
+    Ok(res)
+}
+
+/// Implements request_ecdsa_signature from the Signer API
+async fn handle_request_ecdsa_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<SignEcdsaRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::RequestEcdsaSignature, req_id).await?;
+
+    debug!(event = "request_ecdsa_signature", module_id = %request.id, %req_id, "New request");
+
+    let sig = sign_ecdsa_and_audit(&state, request, None, req_id).await?;
+
+    Ok((StatusCode::OK, Json(sig)).into_response())
+}
+
+/// Implements request_ecdsa_message_signature from the Signer API, see
+/// [handle_request_message_signature]
+async fn handle_request_ecdsa_message_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<SignEcdsaMessageRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::RequestEcdsaSignature, req_id).await?;
+
+    debug!(
+        event = "request_ecdsa_message_signature",
+        module_id = %request.id,
+        schema = %request.message.schema,
+        %req_id,
+        "New request"
+    );
+
+    let message = decode_message(&request.id, &request.message, req_id)?;
+    let request = request.to_raw_request(message.object_root);
+    let sig = sign_ecdsa_and_audit(&state, request, Some(message), req_id).await?;
 
     Ok((StatusCode::OK, Json(sig)).into_response())
 }
+
+/// Signs the request with an ECDSA proxy, and writes it to the audit log, see
+/// [sign_and_audit]
+async fn sign_ecdsa_and_audit(
+    state: &SigningState,
+    request: SignEcdsaRequest,
+    message: Option<DecodedMessage>,
+    req_id: Uuid,
+) -> Result<EcdsaSignature, SignerModuleError> {
+    let start_request = Instant::now();
+    let res = sign_ecdsa_request(state, &request, message.as_ref(), req_id).await;
+
+    SIGNING_LATENCY
+        .with_label_values(&[PROXY_ECDSA_KEY_TAG])
+        .observe(start_request.elapsed().as_secs_f64());
+    SIGNATURE_REQUESTS
+        .with_label_values(&[&request.id, PROXY_ECDSA_KEY_TAG, outcome_tag(&res)])
+        .inc();
+
+    state
+        .audit(AuditRecord {
+            req_id,
+            module_id: request.id,
+            event: AuditEvent::RequestEcdsaSignature,
+            pubkey: None,
+            address: Some(request.address),
+            is_proxy: true,
+            object_root: Some(request.object_root.into()),
+            builder_domain: request.builder_domain,
+            message: message.map(|message| message.summary),
+            signature: res.as_ref().ok().map(|sig| sig.as_bytes().to_vec().into()),
+            error: res.as_ref().err().map(ToString::to_string),
+        })
+        .await?;
+
+    res
+}
+
+async fn sign_ecdsa_request(
+    state: &SigningState,
+    request: &SignEcdsaRequest,
+    message: Option<&DecodedMessage>,
+    req_id: Uuid,
+) -> Result<EcdsaSignature, SignerModuleError> {
+    let (domain, object_root) =
+        state.signing_target(&request.id, request.builder_domain, request.object_root, req_id)?;
+    state.check_message(&request.id, message, req_id)?;
+
+    // see sign_request for holding the lock until signing
+    let manager = state.manager.read().await;
+    // proxies of other modules are treated as unknown
+    if !manager.has_ecdsa_proxy(&request.id, &request.address) {
+        return Err(SignerModuleError::UnknownEcdsaProxySigner(request.address));
+    }
+    let consensus = manager.get_ecdsa_delegation(&request.address)?.message.delegator;
+    let reservation = state.check_policy(&request.id, &consensus, KeyUse::Proxy, req_id).await?;
+
+    let signature =
+        manager.sign_ecdsa_proxy(&request.id, &request.address, domain, &object_root).await?;
+    reservation.commit();
+
+    Ok(signature)
+}
+
+/// Implements generate_proxy_key from the Signer API
+async fn handle_generate_proxy(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<GenerateProxyRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::GenerateProxyKey, req_id).await?;
+
+    debug!(
+        event = "generate_proxy_key",
+        module_id = %request.id,
+        scheme = ?request.scheme,
+        %req_id,
+        "New request"
+    );
+
+    let res = generate_proxy(&state, &request, auth.token(), req_id).await;
+
+    state
+        .audit(AuditRecord {
+            req_id,
+            module_id: request.id,
+            event: AuditEvent::GenerateProxyKey,
+            pubkey: Some(request.pubkey),
+            address: None,
+            is_proxy: false,
+            object_root: res.as_ref().ok().map(|op| op.object_root),
+            builder_domain: delegation_domain(state.chain) == state.chain.builder_domain(),
+            message: None,
+            signature: res.as_ref().ok().map(|op| op.signature.0.to_vec().into()),
+            error: res.as_ref().err().map(ToString::to_string),
+        })
+        .await?;
+
+    Ok(res?.response)
+}
+
+/// Signed proxy delegation or revocation, with what the audit log records of
+/// it
+struct ProxyOperation {
+    response: Response,
+    object_root: B256,
+    signature: BlsSignature,
+}
+
+impl ProxyOperation {
+    fn new<T: Serialize + TreeHash>(
+        message: &T,
+        signed: impl Serialize,
+        signature: BlsSignature,
+    ) -> Self {
+        Self {
+            response: Json(signed).into_response(),
+            object_root: message.tree_hash_root().0.into(),
+            signature,
+        }
+    }
+}
+
+/// `jwt` is the one the module authenticated with, which is forwarded to the
+/// peers if the consensus key is a distributed one
+async fn generate_proxy(
+    state: &SigningState,
+    request: &GenerateProxyRequest,
+    jwt: &str,
+    req_id: Uuid,
+) -> Result<ProxyOperation, SignerModuleError> {
+    // the delegation is a signature with the consensus key, so it counts
+    // towards the rate limits
+    let reservation =
+        state.check_policy(&request.id, &request.pubkey, KeyUse::Delegation, req_id).await?;
+
+    // the delegation is signed under the read lock, as remote signers can be
+    // slow, and the write lock is only taken to add the proxy once stored
+    let res = match request.scheme {
+        EncryptionScheme::Bls => {
+            let (proxy, store) = {
+                let manager = state.manager.read().await;
+                let proxy = manager
+                    .create_proxy(&request.id, request.pubkey, request.expiry_epoch, Some(jwt))
+                    .await?;
+                (proxy, manager.proxy_store().cloned())
+            };
+            let proxy =
+                store_proxy(store, request.id.clone(), proxy, ProxyStore::store_bls_proxy).await?;
+            let delegation =
+                state.manager.write().await.add_new_proxy(request.id.clone(), proxy)?;
+            ProxyOperation::new(&delegation.message, delegation, delegation.signature)
+        }
+        EncryptionScheme::Ecdsa => {
+            let (proxy, store) = {
+                let manager = state.manager.read().await;
+                let proxy = manager
+                    .create_ecdsa_proxy(
+                        &request.id,
+                        request.pubkey,
+                        request.expiry_epoch,
+                        Some(jwt),
+                    )
+                    .await?;
+                (proxy, manager.proxy_store().cloned())
+            };
+            let proxy =
+                store_proxy(store, request.id.clone(), proxy, ProxyStore::store_ecdsa_proxy)
+                    .await?;
+            let delegation =
+                state.manager.write().await.add_new_ecdsa_proxy(request.id.clone(), proxy)?;
+            ProxyOperation::new(&delegation.message, delegation, delegation.signature)
+        }
+    };
+    reservation.commit();
+
+    Ok(res)
+}
+
+/// Persists a new proxy, or the revocation of one, if a store is set. Building
+/// the keystore and syncing the files is slow, so this runs on a blocking thread
+async fn store_proxy<P: Send + 'static>(
+    store: Option<ProxyStore>,
+    module_id: String,
+    proxy: P,
+    store_fn: fn(&ProxyStore, &str, &P) -> eyre::Result<()>,
+) -> Result<P, SignerModuleError> {
+    let Some(store) = store else {
+        return Ok(proxy);
+    };
+
+    tokio::task::spawn_blocking(move || store_fn(&store, &module_id, &proxy).map(|_| proxy))
+        .await
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))?
+        .map_err(|err| SignerModuleError::Internal(err.to_string()))
+}
+
+/// Implements revoke_proxy_key from the Signer API
+async fn handle_revoke_proxy(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<RevokeProxyRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+
+    state.authenticate(&auth, &request.id, AuditEvent::RevokeProxyKey, req_id).await?;
+
+    debug!(
+        event = "revoke_proxy_key",
+        module_id = %request.id,
+        proxy = ?request.proxy,
+        %req_id,
+        "New request"
+    );
+
+    let res = revoke_proxy(&state, &request, auth.token()).await;
+
+    let (pubkey, address) = match request.proxy {
+        ProxyId::Bls(pubkey) => (Some(pubkey), None),
+        ProxyId::Ecdsa(address) => (None, Some(address)),
+    };
+    state
+        .audit(AuditRecord {
+            req_id,
+            module_id: request.id,
+            event: AuditEvent::RevokeProxyKey,
+            pubkey,
+            address,
+            is_proxy: true,
+            object_root: res.as_ref().ok().map(|op| op.object_root),
+            builder_domain: delegation_domain(state.chain) == state.chain.builder_domain(),
+            message: None,
+            signature: res.as_ref().ok().map(|op| op.signature.0.to_vec().into()),
+            error: res.as_ref().err().map(ToString::to_string),
+        })
+        .await?;
+
+    Ok(res?.response)
+}
+
+/// See [generate_proxy] for `jwt`
+async fn revoke_proxy(
+    state: &SigningState,
+    request: &RevokeProxyRequest,
+    jwt: &str,
+) -> Result<ProxyOperation, SignerModuleError> {
+    // the revocation is signed under the read lock, as remote signers can be
+    // slow, and the write lock is only taken to apply it once stored
+    let res = match request.proxy {
+        ProxyId::Bls(pubkey) => {
+            let (revocation, store) = {
+                let manager = state.manager.read().await;
+                let revocation = manager.revoke_proxy(&request.id, &pubkey, Some(jwt)).await?;
+                (revocation, manager.proxy_store().cloned())
+            };
+            let revocation = store_proxy(
+                store,
+                request.id.clone(),
+                revocation,
+                ProxyStore::store_bls_revocation,
+            )
+            .await?;
+            let revocation = state.manager.write().await.add_revocation(&request.id, revocation)?;
+            ProxyOperation::new(&revocation.message, revocation, revocation.signature)
+        }
+        ProxyId::Ecdsa(address) => {
+            let (revocation, store) = {
+                let manager = state.manager.read().await;
+                let revocation =
+                    manager.revoke_ecdsa_proxy(&request.id, &address, Some(jwt)).await?;
+                (revocation, manager.proxy_store().cloned())
+            };
+            let revocation = store_proxy(
+                store,
+                request.id.clone(),
+                revocation,
+                ProxyStore::store_ecdsa_revocation,
+            )
+            .await?;
+            let revocation =
+                state.manager.write().await.add_ecdsa_revocation(&request.id, revocation)?;
+            ProxyOperation::new(&revocation.message, revocation, revocation.signature)
+        }
+    };
+
+    Ok(res)
+}
+
+/// Implements get_delegation from the Signer API. Delegations are public, so
+/// any module can get the one of a proxy generated by another module
+async fn handle_get_delegation(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<GetDelegationRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+    let module_id = state.authenticate_module(&auth, req_id)?;
+
+    debug!(event = "get_delegation", %module_id, proxy = ?request.proxy, %req_id, "New request");
+
+    let manager = state.manager.read().await;
+    let res = match request.proxy {
+        ProxyId::Bls(pubkey) => Json(manager.delegation_info(&pubkey)?).into_response(),
+        ProxyId::Ecdsa(address) => Json(manager.ecdsa_delegation_info(&address)?).into_response(),
+    };
+
+    Ok(res)
+}
+
+/// Implements verify_signature from the Signer API. Invalid signatures are
+/// reported in the response, not as errors
+async fn handle_verify_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<VerifySignatureRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+    let module_id = state.authenticate_module(&auth, req_id)?;
+
+    debug!(
+        event = "verify_signature",
+        %module_id,
+        pubkey = %request.pubkey,
+        %req_id,
+        "New request"
+    );
+
+    let res = check_bls_signature(
+        state.chain,
+        &request.pubkey,
+        request.object_root,
+        &request.domain,
+        &request.signature,
+    );
+
+    Ok((StatusCode::OK, Json(verification(res))).into_response())
+}
+
+/// Implements verify_ecdsa_signature from the Signer API
+async fn handle_verify_ecdsa_signature(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<VerifyEcdsaSignatureRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+    let module_id = state.authenticate_module(&auth, req_id)?;
+
+    debug!(
+        event = "verify_ecdsa_signature",
+        %module_id,
+        address = %request.address,
+        %req_id,
+        "New request"
+    );
+
+    let res = check_ecdsa_signature(
+        state.chain,
+        &request.address,
+        request.object_root,
+        &request.domain,
+        &request.signature,
+    );
+
+    Ok((StatusCode::OK, Json(verification(res))).into_response())
+}
+
+/// Body of verify_delegation, for BLS or ECDSA proxies
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VerifyDelegationRequest {
+    Bls(VerifyDelegationRequestBls),
+    Ecdsa(VerifyDelegationRequestEcdsa),
+}
+
+/// Implements verify_delegation from the Signer API. Besides the signature and
+/// expiry, the proxy must not be revoked in this signer. Revocations made by
+/// other signers are unknown here, and have to be checked separately
+async fn handle_verify_delegation(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    State(state): State<SigningState>,
+    Json(request): Json<VerifyDelegationRequest>,
+) -> Result<impl IntoResponse, SignerModuleError> {
+    let req_id = Uuid::new_v4();
+    let module_id = state.authenticate_module(&auth, req_id)?;
+
+    let manager = state.manager.read().await;
+    let epoch = manager.current_epoch();
+    let res = match &request {
+        VerifyDelegationRequest::Bls(request) => {
+            let proxy = request.delegation.message.proxy;
+            debug!(event = "verify_delegation", %module_id, %proxy, %req_id, "New request");
+
+            let revoked = manager.is_revoked(&ProxyId::Bls(proxy));
+            check_delegation(state.chain, epoch, &request.delegation, revoked).and_then(|_| {
+                let Some(message) = &request.message else { return Ok(()) };
+                check_bls_signature(
+                    state.chain,
+                    &proxy,
+                    message.object_root,
+                    &message.domain,
+                    &message.signature,
+                )
+            })
+        }
+        VerifyDelegationRequest::Ecdsa(request) => {
+            let proxy = request.delegation.message.proxy;
+            debug!(event = "verify_delegation", %module_id, %proxy, %req_id, "New request");
+
+            let revoked = manager.is_revoked(&ProxyId::Ecdsa(proxy));
+            check_delegation(state.chain, epoch, &request.delegation, revoked).and_then(|_| {
+                let Some(message) = &request.message else { return Ok(()) };
+                check_ecdsa_signature(
+                    state.chain,
+                    &proxy,
+                    message.object_root,
+                    &message.domain,
+                    &message.signature,
+                )
+            })
+        }
+    };
+
+    Ok((StatusCode::OK, Json(verification(res))).into_response())
+}
+
+fn verification(res: eyre::Result<()>) -> VerificationResponse {
+    match res {
+        Ok(()) => VerificationResponse::valid(),
+        Err(err) => VerificationResponse::invalid(err),
+    }
+}
+
+fn check_bls_signature(
+    chain: Chain,
+    pubkey: &BlsPublicKey,
+    object_root: [u8; 32],
+    domain: &SignatureDomain,
+    signature: &BlsSignature,
+) -> eyre::Result<()> {
+    let signing_root = domain.signing_root(chain, object_root);
+    verify_signature(pubkey, &signing_root, signature)
+        .map_err(|err| eyre!("invalid signature: {err}"))
+}
+
+fn check_ecdsa_signature(
+    chain: Chain,
+    address: &Address,
+    object_root: [u8; 32],
+    domain: &SignatureDomain,
+    signature: &EcdsaSignature,
+) -> eyre::Result<()> {
+    let signing_root = domain.signing_root(chain, object_root);
+    let recovered = signature
+        .recover_address_from_prehash(&B256::from(signing_root))
+        .map_err(|err| eyre!("invalid signature: {err}"))?;
+    ensure!(recovered == *address, "signature from {recovered}, expected {address}");
+
+    Ok(())
+}
+
+fn check_delegation<T: ProxyKey>(
+    chain: Chain,
+    epoch: u64,
+    delegation: &SignedProxyDelegation<T>,
+    revoked: bool,
+) -> eyre::Result<()> {
+    delegation.validate(chain).map_err(|err| eyre!("invalid delegation signature: {err}"))?;
+    ensure!(!delegation.message.is_expired(epoch), "delegation expired");
+    ensure!(!revoked, "proxy was revoked");
+
+    Ok(())
+}
+
+async fn serve_tcp(
+    address: SocketAddr,
+    tls: Option<Arc<ServerConfig>>,
+    app: Router,
+) -> eyre::Result<()> {
+    let listener = TcpListener::bind(address).await.wrap_err("failed tcp binding")?;
+
+    if let Err(err) = serve(listener, tls, app).await {
+        error!(?err, "Signing server exited")
+    }
+    Ok(())
+}
+
+/// Serves the Signer API on a Unix socket, which only the users allowed by the
+/// socket permissions can connect to. A socket left by a previous run is
+/// replaced
+async fn serve_unix(socket: &SignerSocketConfig, app: Router) -> eyre::Result<()> {
+    fs::create_dir_all(&socket.dir_path)?;
+
+    // the socket is bound in a directory only the signer can access, and moved in
+    // place once its permissions are set, so that no one can connect in between
+    let bind_dir = socket.dir_path.join(format!(".{SIGNER_SOCKET_FILE}.{}", std::process::id()));
+    if bind_dir.exists() {
+        fs::remove_dir_all(&bind_dir)?;
+    }
+    DirBuilder::new().mode(0o700).create(&bind_dir)?;
+
+    let bind_path = bind_dir.join(SIGNER_SOCKET_FILE);
+    let listener = UnixListener::bind(&bind_path).wrap_err("failed unix socket binding")?;
+    fs::set_permissions(&bind_path, Permissions::from_mode(socket.mode))?;
+
+    let path = socket.socket_path();
+    fs::rename(&bind_path, &path)?;
+    fs::remove_dir(&bind_dir)?;
+    info!(?path, mode = format!("{:o}", socket.mode), "Listening on unix socket");
+
+    let mut backoff = AcceptBackoff::default();
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff.reset();
+                stream
+            }
+            Err(err) => {
+                error!(?err, "Failed to accept unix socket connection");
+                backoff.wait().await;
+                continue;
+            }
+        };
+
+        tokio::spawn(serve_connection(stream, app.clone()));
+    }
+}
+
+/// Reloads the JWT secrets from the file on SIGHUP, so that they can be rotated
+/// without restarting the signer
+async fn reload_jwts(path: PathBuf, jwts: Arc<StdRwLock<HashMap<String, Vec<String>>>>) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            error!(?err, "Failed to listen for SIGHUP, JWT secrets won't be reloaded");
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        match load_jwts_file(&path) {
+            Ok(new_jwts) => {
+                info!(modules =? new_jwts.keys(), "Reloaded JWT secrets");
+                *jwts.write().expect("poisoned jwts") = new_jwts;
+            }
+            Err(err) => error!(?err, "Failed to reload JWT secrets, keeping the current ones"),
+        }
+    }
+}
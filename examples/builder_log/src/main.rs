@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use commit_boost::prelude::*;
+use eyre::Result;
 use tracing::{error, info};
 
 #[derive(Debug, Clone)]
@@ -13,11 +14,12 @@ impl OnBuilderApiEvent for LogProcessor {
 }
 
 #[tokio::main]
-async fn main() {
-    initialize_tracing_log();
-
+async fn main() -> Result<()> {
     match load_builder_module_config::<()>() {
         Ok(config) => {
+            // keep the guard alive to flush file logs on exit
+            let _guard = initialize_tracing_log(&config.id)?;
+
             info!(module_id = config.id, "Starting module");
 
             let client = BuilderEventClient::new(config.server_port, LogProcessor);
@@ -26,8 +28,7 @@ async fn main() {
                 error!(?err, "Service failed");
             }
         }
-        Err(err) => {
-            error!(?err, "Failed to load module config");
-        }
+        Err(err) => return Err(err),
     }
+    Ok(())
 }
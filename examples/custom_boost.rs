@@ -58,7 +58,7 @@ async fn handle_stats(State(state): State<BuilderState<StatusCounter>>) -> Respo
 async fn main() {
 
     color_eyre::install()?;
-    initialize_tracing_log();
+    let _guard = initialize_tracing_log("custom_pbs")?;
 
     let (chain, config) = load_pbs_config();
 
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use alloy::rpc::types::beacon::{BlsPublicKey, BlsSignature};
-use commit_boost::prelude::*;
+use commit_boost::prelude::{commit::schema::examples::Datagram, *};
 use eyre::{OptionExt, Result};
 use lazy_static::lazy_static;
 use prometheus::{IntCounter, Registry};
@@ -18,11 +18,6 @@ lazy_static! {
         IntCounter::new("signature_received", "successful signatures requests received").unwrap();
 }
 
-#[derive(TreeHash)]
-struct Datagram {
-    data: u64,
-}
-
 struct DaCommitService {
     config: StartCommitModuleConfig<ExtraConfig>,
 }
@@ -46,19 +41,26 @@ impl DaCommitService {
         let pubkey = pubkeys.consensus.first().ok_or_eyre("no key available")?;
         info!("Registered validator {pubkey}");
 
+        // modules should sign with a proxy key, delegated by the consensus key
+        let request = GenerateProxyRequest::new(&self.config.id, *pubkey);
+        let proxy_delegation = self.config.signer_client.generate_proxy_key(&request).await?;
+        let proxy = proxy_delegation.message.proxy;
+        info!("Obtained proxy {proxy} for validator");
+
         let mut data = 0;
 
         loop {
-            self.send_request(data, *pubkey).await?;
+            self.send_request(data, proxy).await?;
             sleep(Duration::from_secs(self.config.extra.sleep_secs)).await;
             data += 1;
         }
     }
 
-    pub async fn send_request(&self, data: u64, pubkey: BlsPublicKey) -> Result<()> {
+    pub async fn send_request(&self, data: u64, proxy: BlsPublicKey) -> Result<()> {
+        // the signer decodes the datagram itself, rather than signing a blind root
         let datagram = Datagram { data };
-        let request = SignRequest::builder(&self.config.id, pubkey).with_msg(&datagram);
-        let signature = self.config.signer_client.request_signature(&request).await?;
+        let request = SignMessageRequest::new(&self.config.id, proxy, &datagram).is_proxy();
+        let signature = self.config.signer_client.request_message_signature(&request).await?;
 
         info!("Proposer commitment: {}", pretty_print_sig(signature));
 
@@ -71,7 +73,6 @@ impl DaCommitService {
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    initialize_tracing_log();
 
     // Remember to register all your metrics before starting the process
     MY_CUSTOM_REGISTRY.register(Box::new(SIG_RECEIVED_COUNTER.clone()))?;
@@ -80,6 +81,9 @@ async fn main() -> Result<()> {
 
     match load_commit_module_config::<ExtraConfig>() {
         Ok(config) => {
+            // keep the guard alive to flush file logs on exit
+            let _guard = initialize_tracing_log(&config.id)?;
+
             info!(
                 module_id = config.id,
                 sleep_secs = config.extra.sleep_secs,
@@ -92,9 +96,7 @@ async fn main() -> Result<()> {
                 error!(?err, "Service failed");
             }
         }
-        Err(err) => {
-            error!(?err, "Failed to load module config");
-        }
+        Err(err) => return Err(err),
     }
     Ok(())
 }
@@ -1,3 +1,6 @@
+pub mod mock_collector;
+pub mod mock_dirk;
 pub mod mock_relay;
 pub mod mock_validator;
+pub mod mock_web3signer;
 pub mod utils;
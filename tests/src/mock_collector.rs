@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Bytes, extract::State, http::StatusCode, response::IntoResponse, routing::post, Router,
+};
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::ExportTraceServiceRequest, trace::v1::Span,
+};
+use prost::Message;
+
+/// Stand-in for an OTLP/HTTP collector, keeps all the spans it receives
+#[derive(Default)]
+pub struct MockCollectorState {
+    spans: Mutex<Vec<Span>>,
+}
+
+impl MockCollectorState {
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans.lock().unwrap().clone()
+    }
+}
+
+pub fn mock_collector_router(state: Arc<MockCollectorState>) -> Router {
+    Router::new().route("/v1/traces", post(handle_export_traces)).with_state(state)
+}
+
+async fn handle_export_traces(
+    State(state): State<Arc<MockCollectorState>>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Ok(request) = ExportTraceServiceRequest::decode(body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let spans = request
+        .resource_spans
+        .into_iter()
+        .flat_map(|resource| resource.scope_spans)
+        .flat_map(|scope| scope.spans);
+    state.spans.lock().unwrap().extend(spans);
+
+    StatusCode::OK
+}
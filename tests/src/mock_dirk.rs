@@ -0,0 +1,150 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use alloy::rpc::types::beacon::BlsPublicKey;
+use blst::min_pk::SecretKey;
+use cb_common::{
+    signature::{compute_signing_root, sign_message},
+    signer::dirk_proto::{
+        lister_server::{Lister, ListerServer},
+        sign_request,
+        signer_server::{Signer, SignerServer},
+        Account, DistributedAccount, Endpoint, ListAccountsRequest, ListAccountsResponse,
+        ResponseState, SignRequest, SignResponse,
+    },
+    utils::blst_pubkey_to_alloy,
+};
+use tonic::{
+    transport::{server::Router, Server},
+    Request, Response, Status,
+};
+
+/// Share of a distributed account held by a Dirk instance
+pub struct MockDistributedAccount {
+    pub name: String,
+    pub share: SecretKey,
+    pub composite_pubkey: BlsPublicKey,
+    pub threshold: u32,
+    /// Ids and ports of all the participants, reachable on localhost
+    pub participants: Vec<(u64, u16)>,
+    /// Signs with a key other than the listed share
+    pub faulty: bool,
+}
+
+/// Stand-in for a Dirk instance, signs the received roots with its accounts
+pub struct MockDirkState {
+    accounts: Vec<(String, SecretKey)>,
+    distributed: Option<MockDistributedAccount>,
+    received_sign: AtomicU64,
+}
+
+impl MockDirkState {
+    pub fn new(
+        accounts: Vec<(String, SecretKey)>,
+        distributed: Option<MockDistributedAccount>,
+    ) -> Self {
+        Self { accounts, distributed, received_sign: Default::default() }
+    }
+
+    pub fn received_sign(&self) -> u64 {
+        self.received_sign.load(Ordering::Relaxed)
+    }
+
+    fn find_key(&self, name: &str) -> Option<&SecretKey> {
+        if let Some((_, secret_key)) = self.accounts.iter().find(|(account, _)| account == name) {
+            return Some(secret_key);
+        }
+
+        self.distributed.as_ref().filter(|d| d.name == name).map(|d| &d.share)
+    }
+}
+
+pub fn mock_dirk_router(state: Arc<MockDirkState>) -> Router {
+    Server::builder()
+        .add_service(ListerServer::from_arc(state.clone()))
+        .add_service(SignerServer::from_arc(state))
+}
+
+#[tonic::async_trait]
+impl Lister for MockDirkState {
+    async fn list_accounts(
+        &self,
+        _request: Request<ListAccountsRequest>,
+    ) -> Result<Response<ListAccountsResponse>, Status> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(name, sk)| Account {
+                name: name.clone(),
+                public_key: blst_pubkey_to_alloy(&sk.sk_to_pk()).to_vec(),
+                uuid: vec![],
+            })
+            .collect();
+
+        let distributed_accounts = self
+            .distributed
+            .iter()
+            .map(|d| DistributedAccount {
+                name: d.name.clone(),
+                public_key: blst_pubkey_to_alloy(&d.share.sk_to_pk()).to_vec(),
+                participants: d
+                    .participants
+                    .iter()
+                    .map(|(id, port)| Endpoint {
+                        id: *id,
+                        name: "127.0.0.1".to_owned(),
+                        port: *port as u32,
+                    })
+                    .collect(),
+                signing_threshold: d.threshold,
+                composite_public_key: d.composite_pubkey.to_vec(),
+                uuid: vec![],
+            })
+            .collect();
+
+        Ok(Response::new(ListAccountsResponse {
+            state: ResponseState::Succeeded.into(),
+            accounts,
+            distributed_accounts,
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl Signer for MockDirkState {
+    async fn sign(&self, request: Request<SignRequest>) -> Result<Response<SignResponse>, Status> {
+        self.received_sign.fetch_add(1, Ordering::Relaxed);
+
+        let request = request.into_inner();
+        let (Some(sign_request::Id::Account(name)), Ok(object_root), Ok(domain)) = (
+            request.id,
+            <[u8; 32]>::try_from(request.data.as_slice()),
+            <[u8; 32]>::try_from(request.domain.as_slice()),
+        ) else {
+            return Err(Status::invalid_argument("invalid sign request"));
+        };
+
+        let faulty_key;
+        let Some(mut secret_key) = self.find_key(&name) else {
+            return Ok(Response::new(SignResponse {
+                state: ResponseState::Denied.into(),
+                signature: vec![],
+            }));
+        };
+
+        if self.distributed.as_ref().is_some_and(|d| d.faulty && d.name == name) {
+            faulty_key = SecretKey::key_gen(&[1; 32], &[]).unwrap();
+            secret_key = &faulty_key;
+        }
+
+        let signing_root = compute_signing_root(object_root, domain);
+        let signature = sign_message(secret_key, &signing_root);
+
+        Ok(Response::new(SignResponse {
+            state: ResponseState::Succeeded.into(),
+            signature: signature.to_vec(),
+        }))
+    }
+}
@@ -83,8 +83,11 @@ async fn handle_get_header(
     response.data.message.header.block_hash.0[0] = 1;
     response.data.message.set_value(U256::from(10));
     response.data.message.pubkey = state.signer.pubkey();
-    response.data.signature =
-        state.signer.sign(state.chain, &response.data.message.tree_hash_root().0).await;
+    response.data.signature = state
+        .signer
+        .sign(state.chain, &response.data.message.tree_hash_root().0)
+        .await
+        .expect("failed signing header");
 
     (StatusCode::OK, axum::Json(response)).into_response()
 }
@@ -0,0 +1,77 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use alloy::{primitives::hex::FromHex, rpc::types::beacon::BlsPublicKey};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use blst::min_pk::SecretKey;
+use cb_common::{
+    signature::{compute_signing_root, compute_validator_registration_root, sign_message},
+    signer::{Web3SignerRequest, Web3SignerResponse},
+    types::Chain,
+    utils::blst_pubkey_to_alloy,
+};
+
+/// Stand-in for a Web3Signer instance. Like Web3Signer, it computes the
+/// signing root of the typed objects itself, and rejects other types
+pub struct MockWeb3SignerState {
+    chain: Chain,
+    keys: Vec<SecretKey>,
+    received_sign: AtomicU64,
+}
+
+impl MockWeb3SignerState {
+    pub fn new(chain: Chain, keys: Vec<SecretKey>) -> Self {
+        Self { chain, keys, received_sign: Default::default() }
+    }
+
+    pub fn pubkeys(&self) -> Vec<BlsPublicKey> {
+        self.keys.iter().map(|sk| blst_pubkey_to_alloy(&sk.sk_to_pk())).collect()
+    }
+
+    pub fn received_sign(&self) -> u64 {
+        self.received_sign.load(Ordering::Relaxed)
+    }
+}
+
+pub fn mock_web3signer_router(state: Arc<MockWeb3SignerState>) -> Router {
+    Router::new()
+        .route("/api/v1/eth2/publicKeys", get(handle_public_keys))
+        .route("/api/v1/eth2/sign/:pubkey", post(handle_sign))
+        .with_state(state)
+}
+
+async fn handle_public_keys(State(state): State<Arc<MockWeb3SignerState>>) -> impl IntoResponse {
+    Json(state.pubkeys())
+}
+
+async fn handle_sign(
+    State(state): State<Arc<MockWeb3SignerState>>,
+    Path(pubkey): Path<String>,
+    Json(request): Json<Web3SignerRequest>,
+) -> Response {
+    state.received_sign.fetch_add(1, Ordering::Relaxed);
+
+    let Ok(pubkey) = BlsPublicKey::from_hex(pubkey) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Some(secret_key) =
+        state.keys.iter().find(|sk| blst_pubkey_to_alloy(&sk.sk_to_pk()) == pubkey)
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let Web3SignerRequest::ValidatorRegistration { validator_registration, .. } = request;
+    let object_root = compute_validator_registration_root(&validator_registration);
+    let signing_root = compute_signing_root(object_root, state.chain.builder_domain());
+
+    let signature = sign_message(secret_key, &signing_root);
+    Json(Web3SignerResponse { signature }).into_response()
+}
@@ -1,11 +1,23 @@
-use std::sync::Once;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Once,
+};
 
 use alloy::rpc::types::beacon::BlsPublicKey;
 use cb_common::{
     config::RelayConfig,
     pbs::{RelayClient, RelayEntry},
 };
-use eyre::Result;
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, KeyType, ObjectClass},
+    session::UserType,
+    types::AuthPin,
+};
+use eyre::{ensure, eyre, Result};
+use rcgen::{BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair};
 
 pub fn get_local_address(port: u16) -> String {
     format!("http://0.0.0.0:{port}")
@@ -23,3 +35,116 @@ pub fn generate_mock_relay(port: u16, pubkey: BlsPublicKey) -> Result<RelayClien
     let config = RelayConfig { entry, ..RelayConfig::default() };
     RelayClient::new(config)
 }
+
+/// PEM files of a test CA, and of a server and a client certificate signed by
+/// it
+pub struct TestCerts {
+    pub ca_cert: PathBuf,
+    pub server_cert: PathBuf,
+    pub server_key: PathBuf,
+    pub client_cert: PathBuf,
+    pub client_key: PathBuf,
+}
+
+/// Generates the certificates in `dir`, the server one is valid for
+/// `localhost` and `127.0.0.1`
+pub fn generate_test_certs(dir: &Path) -> Result<TestCerts> {
+    fs::create_dir_all(dir)?;
+
+    let ca_key = KeyPair::generate()?;
+    let mut ca_params = CertificateParams::new(Vec::<String>::new())?;
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key)?;
+
+    let server_key = KeyPair::generate()?;
+    let mut server_params =
+        CertificateParams::new(vec!["localhost".to_owned(), "127.0.0.1".to_owned()])?;
+    server_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    let server = server_params.signed_by(&server_key, &ca, &ca_key)?;
+
+    let client_key = KeyPair::generate()?;
+    let mut client_params = CertificateParams::new(vec!["client".to_owned()])?;
+    client_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    let client = client_params.signed_by(&client_key, &ca, &ca_key)?;
+
+    let certs = TestCerts {
+        ca_cert: dir.join("ca.crt"),
+        server_cert: dir.join("server.crt"),
+        server_key: dir.join("server.key"),
+        client_cert: dir.join("client.crt"),
+        client_key: dir.join("client.key"),
+    };
+    fs::write(&certs.ca_cert, ca.pem())?;
+    fs::write(&certs.server_cert, server.pem())?;
+    fs::write(&certs.server_key, server_key.serialize_pem())?;
+    fs::write(&certs.client_cert, client.pem())?;
+    fs::write(&certs.client_key, client_key.serialize_pem())?;
+
+    Ok(certs)
+}
+
+/// SoftHSM module, set `SOFTHSM2_MODULE` if installed elsewhere
+const SOFTHSM_MODULE: &str = "/usr/lib/softhsm/libsofthsm2.so";
+
+/// Initializes a SoftHSM token in `dir`, with a non-extractable AES key to wrap
+/// BLS keys with. Returns the path of the PKCS#11 module
+pub fn init_softhsm_token(
+    dir: &Path,
+    token_label: &str,
+    pin: &str,
+    wrapping_key_label: &str,
+) -> Result<PathBuf> {
+    let module_path =
+        PathBuf::from(std::env::var("SOFTHSM2_MODULE").unwrap_or(SOFTHSM_MODULE.to_owned()));
+    ensure!(
+        module_path.is_file(),
+        "SoftHSM not found at {}, install it or set SOFTHSM2_MODULE",
+        module_path.display()
+    );
+
+    let tokens_dir = dir.join("tokens");
+    fs::create_dir_all(&tokens_dir)?;
+    let conf_path = dir.join("softhsm2.conf");
+    fs::write(
+        &conf_path,
+        format!("directories.tokendir = {}\nobjectstore.backend = file\n", tokens_dir.display()),
+    )?;
+    std::env::set_var("SOFTHSM2_CONF", &conf_path);
+
+    let pkcs11 = Pkcs11::new(&module_path)?;
+    pkcs11.initialize(CInitializeArgs::OsThreads)?;
+
+    let slot = *pkcs11.get_slots_with_token()?.first().ok_or_else(|| eyre!("no SoftHSM slot"))?;
+    let so_pin = AuthPin::new("so-pin".to_owned());
+    pkcs11.init_token(slot, &so_pin, token_label)?;
+
+    // SoftHSM moves the initialized token to a new slot
+    let slot = pkcs11
+        .get_slots_with_token()?
+        .into_iter()
+        .find(|slot| {
+            pkcs11.get_token_info(*slot).map(|info| info.label() == token_label).unwrap_or(false)
+        })
+        .ok_or_else(|| eyre!("initialized token not found"))?;
+
+    let session = pkcs11.open_rw_session(slot)?;
+    session.login(UserType::So, Some(&so_pin))?;
+    session.init_pin(&AuthPin::new(pin.to_owned()))?;
+    session.logout()?;
+
+    session.login(UserType::User, Some(&AuthPin::new(pin.to_owned())))?;
+    session.generate_key(&Mechanism::AesKeyGen, &[
+        Attribute::Class(ObjectClass::SECRET_KEY),
+        Attribute::KeyType(KeyType::AES),
+        Attribute::ValueLen(32.into()),
+        Attribute::Label(wrapping_key_label.as_bytes().to_vec()),
+        Attribute::Token(true),
+        Attribute::Private(true),
+        Attribute::Sensitive(true),
+        Attribute::Extractable(false),
+        Attribute::Encrypt(true),
+        Attribute::Decrypt(true),
+    ])?;
+
+    Ok(module_path)
+}
@@ -0,0 +1,103 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use alloy::primitives::U256;
+use cb_common::{
+    config::{OtlpConfig, PbsConfig, PbsModuleConfig},
+    signer::Signer,
+    types::Chain,
+    utils::{default_host, otlp_layer},
+};
+use cb_pbs::{DefaultBuilderApi, PbsService, PbsState};
+use cb_tests::{
+    mock_collector::{mock_collector_router, MockCollectorState},
+    mock_relay::{mock_relay_app_router, MockRelayState},
+    mock_validator::MockValidator,
+    utils::{generate_mock_relay, get_local_address},
+};
+use eyre::Result;
+use tokio::net::TcpListener;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+async fn serve(app: axum::Router, port: u16) -> Result<()> {
+    let socket = SocketAddr::new("0.0.0.0".parse()?, port);
+    let listener = TcpListener::bind(socket).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+fn get_pbs_static_config(port: u16) -> PbsConfig {
+    PbsConfig {
+        host: default_host(),
+        port,
+        tls: None,
+        relay_check: true,
+        timeout_get_header_ms: u64::MAX,
+        timeout_get_payload_ms: u64::MAX,
+        timeout_register_validator_ms: u64::MAX,
+        skip_sigverify: false,
+        min_bid_wei: U256::ZERO,
+        late_in_slot_time_ms: u64::MAX,
+    }
+}
+
+// multi thread runtime is needed to flush the batch exporter from a blocking
+// task
+#[tokio::test(flavor = "multi_thread")]
+async fn test_slot_trace() -> Result<()> {
+    let signer = Signer::new_random()?;
+
+    let chain = Chain::Holesky;
+    let port = 3500;
+
+    let collector = Arc::new(MockCollectorState::default());
+    tokio::spawn(serve(mock_collector_router(collector.clone()), port + 2));
+
+    let otlp_config = OtlpConfig { endpoint: get_local_address(port + 2).parse()? };
+    tracing_subscriber::registry().with(otlp_layer(&otlp_config, "cb_pbs_test")?).init();
+
+    let relays = vec![generate_mock_relay(port + 1, signer.pubkey())?];
+    let mock_state = Arc::new(MockRelayState::new(chain, signer, 0));
+    tokio::spawn(serve(mock_relay_app_router(mock_state), port + 1));
+
+    let config = PbsModuleConfig {
+        chain,
+        pbs_config: Arc::new(get_pbs_static_config(port)),
+        signer_client: None,
+        event_publiher: None,
+        extra: (),
+        relays,
+    };
+    let state = PbsState::new(config);
+    tokio::spawn(PbsService::run::<(), DefaultBuilderApi>(state.clone()));
+
+    // leave some time to start servers
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mock_validator = MockValidator::new(port)?;
+    mock_validator.do_get_header().await?;
+    mock_validator.do_submit_block().await?;
+
+    let (_, slot_uuid) = state.get_slot_and_uuid();
+    let trace_id = slot_uuid.as_bytes().to_vec();
+
+    // flushes pending spans to the collector
+    tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider).await?;
+
+    let spans = collector.spans();
+    for name in ["get_header", "submit_blinded_block"] {
+        assert!(
+            spans.iter().any(|span| span.name == name && span.trace_id == trace_id),
+            "missing {name} span for slot"
+        );
+    }
+
+    let relay_spans: Vec<_> = spans.iter().filter(|span| span.name == "handler").collect();
+    assert_eq!(relay_spans.len(), 2);
+    for span in relay_spans {
+        assert_eq!(span.trace_id, trace_id);
+        assert!(span.attributes.iter().any(|kv| kv.key == "relay_id"));
+        assert!(span.attributes.iter().any(|kv| kv.key == "status_code"));
+    }
+
+    Ok(())
+}
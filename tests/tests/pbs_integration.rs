@@ -1,17 +1,24 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration, u64};
+use std::{
+    fs,
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+    u64,
+};
 
 use alloy::primitives::U256;
 use cb_common::{
-    config::{PbsConfig, PbsModuleConfig},
-    pbs::RelayClient,
+    config::{PbsConfig, PbsModuleConfig, TlsConfig},
+    pbs::{RelayClient, BULDER_API_PATH, GET_STATUS_PATH},
     signer::Signer,
     types::Chain,
+    utils::default_host,
 };
 use cb_pbs::{DefaultBuilderApi, PbsService, PbsState};
 use cb_tests::{
     mock_relay::{mock_relay_app_router, MockRelayState},
     mock_validator::MockValidator,
-    utils::{generate_mock_relay, setup_test_env},
+    utils::{generate_mock_relay, generate_test_certs, setup_test_env},
 };
 use eyre::Result;
 use tokio::net::TcpListener;
@@ -30,7 +37,9 @@ async fn start_mock_relay_service(state: Arc<MockRelayState>, port: u16) -> Resu
 
 fn get_pbs_static_config(port: u16) -> PbsConfig {
     PbsConfig {
+        host: default_host(),
         port,
+        tls: None,
         relay_check: true,
         timeout_get_header_ms: u64::MAX,
         timeout_get_payload_ms: u64::MAX,
@@ -171,3 +180,47 @@ async fn test_submit_block() -> Result<()> {
     assert_eq!(mock_state.received_submit_block(), 1);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_tls() -> Result<()> {
+    setup_test_env();
+    let signer = Signer::new_random()?;
+
+    let chain = Chain::Holesky;
+    let port = 3950;
+    let dir = std::env::temp_dir().join(format!("cb_pbs_tls_{}", std::process::id()));
+    let certs = generate_test_certs(&dir)?;
+
+    let relays = vec![generate_mock_relay(port + 1, signer.pubkey())?];
+    let mock_state = Arc::new(MockRelayState::new(chain, signer, 0));
+    tokio::spawn(start_mock_relay_service(mock_state.clone(), port + 1));
+
+    let mut pbs_config = get_pbs_static_config(port);
+    pbs_config.host = Ipv4Addr::LOCALHOST.into();
+    pbs_config.tls = Some(TlsConfig {
+        cert_path: certs.server_cert,
+        key_path: certs.server_key,
+        ca_cert_path: None,
+        client_ca_cert_path: None,
+    });
+    let config = to_pbs_config(chain, pbs_config, relays);
+    let state = PbsState::new(config);
+    tokio::spawn(PbsService::run::<(), DefaultBuilderApi>(state));
+
+    // leave some time to start servers
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let path = format!("{BULDER_API_PATH}{GET_STATUS_PATH}");
+    let ca_cert = reqwest::Certificate::from_pem(&fs::read(&certs.ca_cert)?)?;
+    let client =
+        reqwest::Client::builder().use_rustls_tls().add_root_certificate(ca_cert).build()?;
+    let res = client.get(format!("https://127.0.0.1:{port}{path}")).send().await?;
+    assert!(res.status().is_success());
+    assert_eq!(mock_state.received_get_status(), 1);
+
+    // cleartext requests are not served
+    assert!(reqwest::get(format!("http://127.0.0.1:{port}{path}")).await.is_err());
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::fs;
+
+use cb_common::{
+    config::PKCS11_PIN_ENV,
+    loader::SignerLoader,
+    signature::{compute_signing_root, verify_signature},
+    signer::{Pkcs11Keys, Pkcs11Token, Signer},
+    types::Chain,
+};
+use cb_tests::utils::{init_softhsm_token, setup_test_env};
+use eyre::Result;
+
+const TOKEN_LABEL: &str = "commit-boost";
+const PIN: &str = "1234";
+const WRAPPING_KEY_LABEL: &str = "cb-wrap";
+
+/// Needs SoftHSM, installed in CI
+#[tokio::test]
+async fn test_pkcs11_wrapped_keys() -> Result<()> {
+    setup_test_env();
+
+    let dir = std::env::temp_dir().join(format!("cb_pkcs11_{}", std::process::id()));
+    let module_path = init_softhsm_token(&dir, TOKEN_LABEL, PIN, WRAPPING_KEY_LABEL)?;
+
+    let keys_path = dir.join("wrapped");
+    fs::create_dir_all(&keys_path)?;
+
+    let token = Pkcs11Token::open(&module_path, TOKEN_LABEL, PIN)?;
+    let signer = Signer::new_random()?;
+    let wrapped = token.wrap_key(WRAPPING_KEY_LABEL, &signer)?;
+    assert_eq!(wrapped.pubkey, signer.pubkey());
+    fs::write(keys_path.join("key.json"), serde_json::to_string(&wrapped)?)?;
+
+    // a key wrapped for another pubkey fails authentication and is skipped
+    let mut tampered = token.wrap_key(WRAPPING_KEY_LABEL, &Signer::new_random()?)?;
+    tampered.pubkey = signer.pubkey();
+    fs::write(keys_path.join("tampered.json"), serde_json::to_string(&tampered)?)?;
+
+    std::env::set_var(PKCS11_PIN_ENV, PIN);
+    let loader = SignerLoader::Pkcs11 {
+        module_path: module_path.clone(),
+        token_label: TOKEN_LABEL.to_owned(),
+        keys: Pkcs11Keys::Wrapped {
+            wrapping_key_label: WRAPPING_KEY_LABEL.to_owned(),
+            keys_path: keys_path.clone(),
+        },
+    };
+    let signers = loader.load_keys().await?;
+    assert_eq!(signers.len(), 1);
+    assert_eq!(signers[0].pubkey(), signer.pubkey());
+
+    let object_root = [1; 32];
+    let signature = signers[0].sign(Chain::Holesky, &object_root).await?;
+    let signing_root = compute_signing_root(object_root, Chain::Holesky.builder_domain());
+    assert!(verify_signature(&signer.pubkey(), &signing_root, &signature).is_ok());
+
+    // wrong PIN and unknown wrapping key
+    assert!(Pkcs11Token::open(&module_path, TOKEN_LABEL, "wrong").is_err());
+    assert!(token.unwrap_keys("unknown", &keys_path).is_err());
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
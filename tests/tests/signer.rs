@@ -0,0 +1,1297 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    net::Ipv4Addr,
+    os::unix::fs::PermissionsExt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use alloy::{primitives::Address, rpc::types::beacon::relay::ValidatorRegistrationMessage};
+use cb_common::{
+    commit::{
+        client::{RetryConfig, SignerClient, SignerClientTls},
+        constants::{GET_PUBKEYS_PATH, HEALTH_PATH, PARTIAL_SIGNATURE_PATH, READY_PATH},
+        error::SignerClientError,
+        request::{
+            GenerateProxyRequest, ProxyId, ProxyStatus, SignEcdsaMessageRequest, SignEcdsaRequest,
+            SignMessageRequest, SignRequest, SignValidatorRegistrationRequest, SignatureDomain,
+            VerifyDelegationRequest, VerifyEcdsaSignatureRequest, VerifySignatureRequest,
+        },
+        schema::{self, examples::PreconfCommitment, MessageSchema, SchemaMessageRoot},
+    },
+    config::{
+        KeymanagerConfig, PeerTlsConfig, SignerHardeningConfig, SignerSocketConfig, SigningPolicy,
+        StartSignerConfig, ThresholdAccount, ThresholdConfig, ThresholdPeerConfig, TlsConfig,
+        SIGNER_KEYS_ENV,
+    },
+    jwt::create_jwt,
+    loader::SignerLoader,
+    signature::{
+        compute_signing_root, compute_validator_registration_root, random_secret,
+        verify_ecdsa_module_message, verify_signature, verify_signed_builder_message,
+        verify_signed_module_message,
+    },
+    signer::{
+        read_audit_key, read_audit_log, split_secret, threshold_peer_id, AuditLog, ModuleRequest,
+        ModuleRequestKind, PartialSignatureRequest,
+    },
+    types::Chain,
+    utils::{blst_pubkey_to_alloy, current_slot, default_host, SLOTS_PER_EPOCH},
+};
+use cb_signer::{
+    keymanager::{
+        DeleteKeystoresRequest, ImportKeystoresRequest, KeystoreStatusKind, KeystoresResponse,
+        ListKeystoresResponse, KEYSTORES_PATH,
+    },
+    metrics::SIGNER_METRICS_REGISTRY,
+    service::{ReadinessResponse, SigningService},
+};
+use cb_tests::{
+    mock_dirk::{mock_dirk_router, MockDirkState, MockDistributedAccount},
+    mock_web3signer::{mock_web3signer_router, MockWeb3SignerState},
+    utils::{generate_test_certs, get_local_address, setup_test_env},
+};
+use eth2_keystore::{
+    json_keystore::{Kdf, Pbkdf2, Prf},
+    keypair_from_secret, KeystoreBuilder,
+};
+use eyre::Result;
+use reqwest::StatusCode;
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+const MODULE_ID: &str = "TEST_MODULE";
+const MODULE_JWT: &str = "test_jwt";
+const OTHER_MODULE_ID: &str = "OTHER_MODULE";
+const OTHER_MODULE_JWT: &str = "other_jwt";
+
+#[derive(TreeHash)]
+struct Datagram {
+    data: u64,
+}
+
+async fn start_signer(chain: Chain, port: u16) -> Result<()> {
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    start_signer_with_loader(chain, port, loader).await
+}
+
+async fn start_signer_with_loader(chain: Chain, port: u16, loader: SignerLoader) -> Result<()> {
+    run_signer(signer_config(chain, port, loader)).await
+}
+
+fn signer_config(chain: Chain, port: u16, loader: SignerLoader) -> StartSignerConfig {
+    StartSignerConfig {
+        chain,
+        loader,
+        store: None,
+        audit: None,
+        keymanager: None,
+        keymanager_token: None,
+        threshold: None,
+        threshold_secrets: HashMap::new(),
+        socket: None,
+        server_host: default_host(),
+        server_port: port,
+        tls: None,
+        // keep the test process dumpable
+        hardening: SignerHardeningConfig { lock_memory: false, disable_core_dumps: false },
+        jwts: HashMap::from([
+            (MODULE_ID.to_owned(), vec![MODULE_JWT.to_owned()]),
+            (OTHER_MODULE_ID.to_owned(), vec![OTHER_MODULE_JWT.to_owned()]),
+        ]),
+        jwts_path: None,
+        builder_domain_modules: HashSet::from([OTHER_MODULE_ID.to_owned()]),
+        policies: HashMap::new(),
+        key_groups: HashMap::new(),
+        beacon_url: None,
+    }
+}
+
+async fn run_signer(config: StartSignerConfig) -> Result<()> {
+    tokio::spawn(SigningService::run(config));
+
+    // leave some time to start servers
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_signature() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3600;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let pubkeys = client.get_pubkeys().await?;
+    assert!(pubkeys.proxy.is_empty());
+    let consensus = pubkeys.consensus[0];
+
+    let delegation =
+        client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus)).await?;
+    assert_eq!(delegation.message.delegator, consensus);
+    assert!(delegation.validate(chain).is_ok());
+
+    let proxy = delegation.message.proxy;
+    assert_eq!(client.get_pubkeys().await?.proxy, vec![proxy]);
+    assert!(other_client.get_pubkeys().await?.proxy.is_empty());
+
+    let datagram = Datagram { data: 1 };
+    let request = SignRequest::builder(MODULE_ID, proxy).is_proxy().with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_module_message(chain, MODULE_ID, &proxy, &datagram, &signature).is_ok());
+
+    // proxies can't be used by other modules
+    let request = SignRequest::builder(OTHER_MODULE_ID, proxy).is_proxy().with_msg(&datagram);
+    let res = other_client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ecdsa_proxy_signature() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3800;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+
+    let delegation = client
+        .generate_ecdsa_proxy_key(&GenerateProxyRequest::new_ecdsa(MODULE_ID, consensus))
+        .await?;
+    assert_eq!(delegation.message.delegator, consensus);
+    assert!(delegation.validate(chain).is_ok());
+
+    let address = delegation.message.proxy;
+    let pubkeys = client.get_pubkeys().await?;
+    assert_eq!(pubkeys.proxy_ecdsa, vec![address]);
+    assert!(pubkeys.proxy.is_empty());
+    assert!(other_client.get_pubkeys().await?.proxy_ecdsa.is_empty());
+
+    let datagram = Datagram { data: 1 };
+    let request = SignEcdsaRequest::builder(MODULE_ID, address).with_msg(&datagram);
+    let signature = client.request_ecdsa_signature(&request).await?;
+    assert!(verify_ecdsa_module_message(chain, MODULE_ID, &address, &datagram, &signature).is_ok());
+
+    // proxies can't be used by other modules
+    let request = SignEcdsaRequest::builder(OTHER_MODULE_ID, address).with_msg(&datagram);
+    let res = other_client.request_ecdsa_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_revocation() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3870;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let epoch = current_slot(chain) / SLOTS_PER_EPOCH;
+
+    // expiries must be in the future
+    let request = GenerateProxyRequest::new(MODULE_ID, consensus).with_expiry(epoch);
+    let res = client.generate_proxy_key(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 400, .. })));
+
+    let request = GenerateProxyRequest::new(MODULE_ID, consensus).with_expiry(epoch + 10);
+    let delegation = client.generate_proxy_key(&request).await?;
+    assert_eq!(delegation.message.expiry_epoch, Some(epoch + 10));
+    assert!(delegation.validate(chain).is_ok());
+    let proxy = delegation.message.proxy;
+
+    let delegation = client
+        .generate_ecdsa_proxy_key(&GenerateProxyRequest::new_ecdsa(MODULE_ID, consensus))
+        .await?;
+    let address = delegation.message.proxy;
+
+    // proxies of other modules can't be revoked
+    let res = other_client.revoke_proxy_key(proxy).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    let revocation = client.revoke_proxy_key(proxy).await?;
+    assert_eq!(revocation.message.delegator, consensus);
+    assert_eq!(revocation.message.proxy, proxy);
+    assert!(revocation.message.revoked_epoch >= epoch);
+    assert!(revocation.validate(chain).is_ok());
+
+    // revoking again returns the same revocation
+    let again = client.revoke_proxy_key(proxy).await?;
+    assert_eq!(again.signature, revocation.signature);
+
+    let datagram = Datagram { data: 1 };
+    let request = SignRequest::builder(MODULE_ID, proxy).is_proxy().with_msg(&datagram);
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let pubkeys = client.get_pubkeys().await?;
+    assert!(pubkeys.proxy.is_empty());
+    assert_eq!(pubkeys.proxy_ecdsa, vec![address]);
+    let status: HashMap<_, _> =
+        pubkeys.proxy_status.iter().map(|info| (info.proxy, info.status)).collect();
+    assert_eq!(status[&ProxyId::Bls(proxy)], ProxyStatus::Revoked);
+    assert_eq!(status[&ProxyId::Ecdsa(address)], ProxyStatus::Active);
+
+    let revocation = client.revoke_ecdsa_proxy_key(address).await?;
+    assert!(revocation.validate(chain).is_ok());
+    let request = SignEcdsaRequest::builder(MODULE_ID, address).with_msg(&datagram);
+    let res = client.request_ecdsa_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+    assert!(client.get_pubkeys().await?.proxy_ecdsa.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verification() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3920;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let delegation =
+        client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus)).await?;
+    let proxy = delegation.message.proxy;
+
+    let datagram = Datagram { data: 1 };
+    let object_root = datagram.tree_hash_root().0;
+    let signature = client.request_proxy_signature(proxy, &datagram).await?;
+
+    // signatures are verified by any module, with the domain of the signer
+    let request = VerifySignatureRequest {
+        pubkey: proxy,
+        object_root,
+        domain: SignatureDomain::Module(MODULE_ID.to_owned()),
+        signature,
+    };
+    assert!(other_client.verify_signature(&request).await?.valid);
+
+    let request = VerifySignatureRequest {
+        domain: SignatureDomain::Module(OTHER_MODULE_ID.to_owned()),
+        ..request
+    };
+    let res = other_client.verify_signature(&request).await?;
+    assert!(!res.valid);
+    assert!(res.reason.is_some());
+
+    // delegations of other modules can be looked up
+    let res = other_client.get_delegation(proxy).await?;
+    assert_eq!(res.delegation.signature, delegation.signature);
+    assert_eq!(res.status, ProxyStatus::Active);
+    assert!(res.revocation.is_none());
+
+    let res = other_client.get_delegation(consensus).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    let request = VerifyDelegationRequest::new(delegation).with_message(
+        object_root,
+        SignatureDomain::Module(MODULE_ID.to_owned()),
+        signature,
+    );
+    assert!(other_client.verify_delegation(&request).await?.valid);
+
+    // the proxy signature is checked against the delegated proxy
+    let consensus_signature = client.request_consensus_signature(consensus, &datagram).await?;
+    let request = VerifyDelegationRequest::new(delegation).with_message(
+        object_root,
+        SignatureDomain::Module(MODULE_ID.to_owned()),
+        consensus_signature,
+    );
+    assert!(!other_client.verify_delegation(&request).await?.valid);
+
+    let mut tampered = delegation;
+    tampered.message.expiry_epoch = Some(1);
+    assert!(!other_client.verify_delegation(&VerifyDelegationRequest::new(tampered)).await?.valid);
+
+    let delegation = client
+        .generate_ecdsa_proxy_key(&GenerateProxyRequest::new_ecdsa(MODULE_ID, consensus))
+        .await?;
+    let address = delegation.message.proxy;
+    let signature = client.request_ecdsa_proxy_signature(address, &datagram).await?;
+
+    let request = VerifyEcdsaSignatureRequest {
+        address,
+        object_root,
+        domain: SignatureDomain::Module(MODULE_ID.to_owned()),
+        signature,
+    };
+    assert!(other_client.verify_ecdsa_signature(&request).await?.valid);
+    let request = VerifyEcdsaSignatureRequest { domain: SignatureDomain::Builder, ..request };
+    assert!(!other_client.verify_ecdsa_signature(&request).await?.valid);
+
+    assert_eq!(other_client.get_ecdsa_delegation(address).await?.status, ProxyStatus::Active);
+    let request = VerifyDelegationRequest::new(delegation).with_message(
+        object_root,
+        SignatureDomain::Module(MODULE_ID.to_owned()),
+        signature,
+    );
+    assert!(other_client.verify_ecdsa_delegation(&request).await?.valid);
+
+    // revoked proxies are invalid
+    let revocation = client.revoke_ecdsa_proxy_key(address).await?;
+    let res = other_client.verify_ecdsa_delegation(&request).await?;
+    assert!(!res.valid);
+    assert_eq!(res.reason.as_deref(), Some("proxy was revoked"));
+
+    let res = other_client.get_ecdsa_delegation(address).await?;
+    assert_eq!(res.status, ProxyStatus::Revoked);
+    assert_eq!(res.revocation.map(|revocation| revocation.signature), Some(revocation.signature));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signer_client() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3880;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?
+        .with_chain(chain)
+        .with_pubkeys_ttl(Duration::from_secs(60));
+
+    let pubkeys = client.cached_pubkeys().await?;
+    assert!(pubkeys.proxy.is_empty());
+    let consensus = pubkeys.consensus[0];
+
+    let datagram = Datagram { data: 1 };
+    let signature = client.request_consensus_signature(consensus, &datagram).await?;
+    assert!(
+        verify_signed_module_message(chain, MODULE_ID, &consensus, &datagram, &signature).is_ok()
+    );
+
+    // generating a proxy refreshes the cached pubkeys
+    let proxy = client
+        .generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus))
+        .await?
+        .message
+        .proxy;
+    assert_eq!(client.cached_pubkeys().await?.proxy, vec![proxy]);
+    client.request_proxy_signature(proxy, &datagram).await?;
+
+    let address = client
+        .generate_ecdsa_proxy_key(&GenerateProxyRequest::new_ecdsa(MODULE_ID, consensus))
+        .await?
+        .message
+        .proxy;
+    assert_eq!(client.cached_pubkeys().await?.proxy_ecdsa, vec![address]);
+    client.request_ecdsa_proxy_signature(address, &datagram).await?;
+
+    // signatures are checked against the domain of the client chain
+    let wrong_chain = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?
+        .with_chain(Chain::Mainnet);
+    let res = wrong_chain.request_consensus_signature(consensus, &datagram).await;
+    assert!(matches!(res, Err(SignerClientError::InvalidSignature(_))));
+    let res = wrong_chain.request_ecdsa_proxy_signature(address, &datagram).await;
+    assert!(matches!(res, Err(SignerClientError::InvalidSignature(_))));
+
+    // connection errors are retried with backoff
+    let retry = RetryConfig {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(50),
+    };
+    let unreachable =
+        SignerClient::new("0.0.0.0:3889".to_owned(), MODULE_ID, MODULE_JWT)?.with_retries(retry);
+    let start = Instant::now();
+    let err = unreachable.get_pubkeys().await.unwrap_err();
+    assert!(err.is_transient());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // signature requests reaching the signer are not sent again
+    let hits = Arc::new(AtomicUsize::new(0));
+    let unavailable = axum::Router::new().fallback({
+        let hits = hits.clone();
+        move || async move {
+            hits.fetch_add(1, Ordering::Relaxed);
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    });
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3888").await?;
+    tokio::spawn(async move { axum::serve(listener, unavailable).await });
+
+    let unavailable =
+        SignerClient::new("0.0.0.0:3888".to_owned(), MODULE_ID, MODULE_JWT)?.with_retries(retry);
+    let err = unavailable.request_consensus_signature(consensus, &datagram).await.unwrap_err();
+    assert!(err.is_transient());
+    assert_eq!(hits.load(Ordering::Relaxed), 1);
+
+    unavailable.get_pubkeys().await.unwrap_err();
+    assert_eq!(hits.load(Ordering::Relaxed), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_signature() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3620;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let proxy = client
+        .generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus))
+        .await?
+        .message
+        .proxy;
+
+    let datagram = Datagram { data: 1 };
+    let requests = vec![
+        SignRequest::builder(MODULE_ID, consensus).with_msg(&datagram),
+        SignRequest::builder(MODULE_ID, proxy).is_proxy().with_msg(&datagram),
+        // not a proxy of the module
+        SignRequest::builder(MODULE_ID, consensus).is_proxy().with_msg(&datagram),
+        SignRequest::builder(MODULE_ID, consensus).with_builder_domain().with_msg(&datagram),
+    ];
+    let results = client.request_signatures(&requests).await?;
+    assert_eq!(results.len(), 4);
+
+    let signature = results[0].as_ref().unwrap();
+    assert!(verify_signed_module_message(chain, MODULE_ID, &consensus, &datagram, signature)
+        .is_ok());
+    let signature = results[1].as_ref().unwrap();
+    assert!(verify_signed_module_message(chain, MODULE_ID, &proxy, &datagram, signature).is_ok());
+    assert!(matches!(results[2], Err(SignerClientError::FailedRequest { status: 404, .. })));
+    assert!(matches!(results[3], Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    // all requests must be of the authenticated module
+    let requests = vec![
+        SignRequest::builder(MODULE_ID, consensus).with_msg(&datagram),
+        SignRequest::builder(OTHER_MODULE_ID, consensus).with_msg(&datagram),
+    ];
+    let res = client.request_signatures(&requests).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 401, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_builder_domain_signature() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3650;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let privileged_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let datagram = Datagram { data: 1 };
+
+    // module signatures can't be used as builder messages
+    let request = SignRequest::builder(MODULE_ID, consensus).with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_builder_message(chain, &consensus, &datagram, &signature).is_err());
+
+    let request =
+        SignRequest::builder(MODULE_ID, consensus).with_builder_domain().with_msg(&datagram);
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let request =
+        SignRequest::builder(OTHER_MODULE_ID, consensus).with_builder_domain().with_msg(&datagram);
+    let signature = privileged_client.request_signature(&request).await?;
+    assert!(verify_signed_builder_message(chain, &consensus, &datagram, &signature).is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signing_policy() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3660;
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let keys: Vec<_> = loader.clone().load_keys().await?.iter().map(|s| s.pubkey()).collect();
+    let (allowed, denied) = (keys[0], keys[1]);
+
+    let mut config = signer_config(chain, port, loader);
+    config.key_groups.insert("group".to_owned(), vec![allowed]);
+    config.policies.insert(MODULE_ID.to_owned(), SigningPolicy {
+        key_groups: vec!["group".to_owned()],
+        proxy_only: true,
+        max_signatures_per_epoch: Some(3),
+        ..Default::default()
+    });
+    run_signer(config).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    // other modules are unrestricted
+    assert_eq!(client.get_pubkeys().await?.consensus, vec![allowed]);
+    assert_eq!(other_client.get_pubkeys().await?.consensus.len(), 2);
+
+    let datagram = Datagram { data: 1 };
+
+    let request = SignRequest::builder(MODULE_ID, allowed).with_msg(&datagram);
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let res = client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, denied)).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let proxy = client
+        .generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, allowed))
+        .await?
+        .message
+        .proxy;
+
+    // the delegation counts towards the limit, denied requests don't
+    let request = SignRequest::builder(MODULE_ID, proxy).is_proxy().with_msg(&datagram);
+    client.request_signature(&request).await?;
+    client.request_signature(&request).await?;
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 429, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_structured_messages() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3930;
+    let audit_dir = std::env::temp_dir().join(format!("cb_message_audit_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&audit_dir);
+    fs::create_dir_all(&audit_dir)?;
+    let audit = AuditLog { dir_path: audit_dir.join("log"), key_path: audit_dir.join("audit.key") };
+    fs::write(&audit.key_path, [1; 32])?;
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let mut config = signer_config(chain, port, loader);
+    config.audit = Some(audit.clone());
+    config.policies.insert(MODULE_ID.to_owned(), SigningPolicy {
+        message_schemas: vec!["PreconfCommitment".to_owned()],
+        max_slot_lookahead: Some(32),
+        ..Default::default()
+    });
+    run_signer(config).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let other_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let slot = current_slot(chain);
+    let commitment = PreconfCommitment { slot: slot + 1, tx_hash: [1; 32] };
+
+    // the root of the message is signed together with its schema
+    let request = SignMessageRequest::new(MODULE_ID, consensus, &commitment);
+    let signature = client.request_message_signature(&request).await?;
+    let root = SchemaMessageRoot::new(&commitment);
+    assert!(verify_signed_module_message(chain, MODULE_ID, &consensus, &root, &signature).is_ok());
+    assert!(verify_signed_module_message(chain, MODULE_ID, &consensus, &commitment, &signature)
+        .is_err());
+
+    // raw roots, other schemas and far slots are denied
+    let request = SignRequest::builder(MODULE_ID, consensus).with_msg(&commitment);
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let datagram = schema::examples::Datagram { data: 1 };
+    let request = SignMessageRequest::new(MODULE_ID, consensus, &datagram);
+    let res = client.request_message_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    let far = PreconfCommitment { slot: slot + 100, ..commitment.clone() };
+    let request = SignMessageRequest::new(MODULE_ID, consensus, &far);
+    let res = client.request_message_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    // messages are decoded before being sent
+    let mut request = SignMessageRequest::new(MODULE_ID, consensus, &commitment);
+    request.message.schema = "Datagram".to_owned();
+    let res = client.request_message_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::InvalidMessage(_))));
+
+    // modules without a policy can sign any schema
+    let request = SignMessageRequest::new(OTHER_MODULE_ID, consensus, &datagram);
+    other_client.request_message_signature(&request).await?;
+
+    let address = client
+        .generate_ecdsa_proxy_key(&GenerateProxyRequest::new_ecdsa(MODULE_ID, consensus))
+        .await?
+        .message
+        .proxy;
+    let request = SignEcdsaMessageRequest::new(MODULE_ID, address, &commitment);
+    let signature = client.request_ecdsa_message_signature(&request).await?;
+    assert!(verify_ecdsa_module_message(chain, MODULE_ID, &address, &root, &signature).is_ok());
+
+    // requests failing authentication are audited too
+    let wrong_client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, OTHER_MODULE_JWT)?;
+    let request = SignMessageRequest::new(MODULE_ID, consensus, &commitment);
+    let res = wrong_client.request_message_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 401, .. })));
+
+    // the summary of the message is audited
+    let entries = read_audit_log(&audit.file_path(), &read_audit_key(&audit.key_path)?)?;
+    let first = &entries[0].record;
+    assert_eq!(first.object_root, Some(root.tree_hash_root().0.into()));
+    assert_eq!(first.message.as_deref(), Some(commitment.summary().as_str()));
+    assert!(first.signature.is_some());
+
+    let last = &entries.last().unwrap().record;
+    assert_eq!(last.module_id, MODULE_ID);
+    assert!(last.signature.is_none() && last.error.is_some());
+
+    fs::remove_dir_all(&audit_dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_proxy_unauthorized() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3700;
+    start_signer(chain, port).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+    let consensus = client.get_pubkeys().await?.consensus[0];
+
+    let res = client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus)).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 401, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_jwt_auth() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3750;
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let mut config = signer_config(chain, port, loader);
+    // secret being rotated
+    config.jwts.get_mut(MODULE_ID).unwrap().push("new_jwt".to_owned());
+    run_signer(config).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let rotated_client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, "new_jwt")?;
+    assert!(client.get_pubkeys().await.is_ok());
+    assert!(rotated_client.get_pubkeys().await.is_ok());
+
+    let wrong_secret = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, OTHER_MODULE_JWT)?;
+    let res = wrong_secret.get_pubkeys().await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 401, .. })));
+
+    let unknown = SignerClient::new(format!("0.0.0.0:{port}"), "UNKNOWN", MODULE_JWT)?;
+    let res = unknown.get_pubkeys().await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    // the raw secret is not a valid token
+    let res = reqwest::Client::new()
+        .get(format!("http://0.0.0.0:{port}{GET_PUBKEYS_PATH}"))
+        .bearer_auth(MODULE_JWT)
+        .send()
+        .await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unix_socket() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3890;
+
+    let dir = std::env::temp_dir().join(format!("cb_socket_{}", std::process::id()));
+    let socket = SignerSocketConfig { dir_path: dir.clone(), mode: 0o600 };
+    let socket_path = socket.socket_path();
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let mut config = signer_config(chain, port, loader);
+    config.socket = Some(socket);
+    run_signer(config).await?;
+
+    assert_eq!(fs::metadata(&socket_path)?.permissions().mode() & 0o777, 0o600);
+
+    let address = format!("unix://{}", socket_path.display());
+    let client = SignerClient::new(address.clone(), MODULE_ID, MODULE_JWT)?.with_chain(chain);
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let datagram = Datagram { data: 1 };
+    client.request_consensus_signature(consensus, &datagram).await?;
+
+    let proxy = client
+        .generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, consensus))
+        .await?
+        .message
+        .proxy;
+    client.request_proxy_signature(proxy, &datagram).await?;
+
+    // requests are still authenticated
+    let wrong_secret = SignerClient::new(address, MODULE_ID, OTHER_MODULE_JWT)?;
+    let res = wrong_secret.get_pubkeys().await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 401, .. })));
+
+    // the api is not served over tcp
+    let tcp_client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?
+        .with_retries(RetryConfig::disabled());
+    assert!(tcp_client.get_pubkeys().await.is_err());
+
+    fs::remove_dir_all(dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3910;
+
+    let dir = std::env::temp_dir().join(format!("cb_signer_tls_{}", std::process::id()));
+    let certs = generate_test_certs(&dir)?;
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let mut config = signer_config(chain, port, loader);
+    config.server_host = Ipv4Addr::LOCALHOST.into();
+    config.tls = Some(TlsConfig {
+        cert_path: certs.server_cert.clone(),
+        key_path: certs.server_key.clone(),
+        ca_cert_path: None,
+        client_ca_cert_path: Some(certs.ca_cert.clone()),
+    });
+    run_signer(config).await?;
+
+    let address = format!("https://127.0.0.1:{port}");
+    let tls = SignerClientTls {
+        ca_cert_path: Some(certs.ca_cert.clone()),
+        cert_path: Some(certs.client_cert.clone()),
+        key_path: Some(certs.client_key.clone()),
+    };
+    let client = SignerClient::new(address.clone(), MODULE_ID, MODULE_JWT)?
+        .with_chain(chain)
+        .with_tls(&tls)?;
+
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    client.request_consensus_signature(consensus, &Datagram { data: 1 }).await?;
+
+    // clients need a certificate signed by the client CA
+    let no_identity = SignerClientTls { ca_cert_path: Some(certs.ca_cert), ..Default::default() };
+    let anonymous_client = SignerClient::new(address, MODULE_ID, MODULE_JWT)?
+        .with_retries(RetryConfig::disabled())
+        .with_tls(&no_identity)?;
+    assert!(anonymous_client.get_pubkeys().await.is_err());
+
+    // cleartext requests are not served
+    let http_client = SignerClient::new(format!("127.0.0.1:{port}"), MODULE_ID, MODULE_JWT)?
+        .with_retries(RetryConfig::disabled());
+    assert!(http_client.get_pubkeys().await.is_err());
+    assert!(http_client.with_tls(&tls).is_err());
+
+    fs::remove_dir_all(dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keymanager() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3770;
+    let keymanager_port = port + 1;
+    let token = "keymanager_token";
+
+    let dir = std::env::temp_dir().join(format!("cb_keymanager_{}", std::process::id()));
+    let keymanager = KeymanagerConfig {
+        host: Ipv4Addr::LOCALHOST.into(),
+        port: keymanager_port,
+        keys_path: dir.join("keys"),
+        secrets_path: dir.join("secrets"),
+    };
+
+    std::env::set_var(SIGNER_KEYS_ENV, "../keys.example.json");
+    let loader = SignerLoader::File { key_path: "../keys.example.json".to_owned() };
+    let mut config = signer_config(chain, port, loader);
+    config.keymanager = Some(keymanager);
+    config.keymanager_token = Some(token.to_owned());
+    run_signer(config).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let config_pubkey = client.get_pubkeys().await?.consensus[0];
+
+    let http = reqwest::Client::new();
+    let url = format!("http://0.0.0.0:{keymanager_port}{KEYSTORES_PATH}");
+
+    let res = http.get(&url).bearer_auth("wrong_token").send().await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // import a new key, and the same key again
+    let secret = random_secret()?;
+    let pubkey = blst_pubkey_to_alloy(&secret.sk_to_pk());
+    let keypair = keypair_from_secret(&secret.to_bytes()).unwrap();
+    // cheap kdf, to keep the test fast
+    let kdf =
+        Kdf::Pbkdf2(Pbkdf2 { dklen: 32, c: 2, prf: Prf::HmacSha256, salt: vec![1; 32].into() });
+    let keystore = KeystoreBuilder::new(&keypair, b"password", String::new())
+        .unwrap()
+        .kdf(kdf)
+        .build()
+        .unwrap()
+        .to_json_string()
+        .unwrap();
+
+    let request = ImportKeystoresRequest {
+        keystores: vec![keystore.clone(), keystore.clone(), keystore],
+        passwords: vec!["password".to_owned(), "password".to_owned(), "wrong".to_owned()],
+        slashing_protection: None,
+    };
+    let res: KeystoresResponse =
+        http.post(&url).bearer_auth(token).json(&request).send().await?.json().await?;
+    let statuses: Vec<_> = res.data.iter().map(|status| status.status).collect();
+    assert_eq!(statuses, vec![
+        KeystoreStatusKind::Imported,
+        KeystoreStatusKind::Duplicate,
+        KeystoreStatusKind::Error
+    ]);
+
+    let res: ListKeystoresResponse =
+        http.get(&url).bearer_auth(token).send().await?.json().await?;
+    let imported = res.data.iter().find(|entry| entry.validating_pubkey == pubkey).unwrap();
+    assert!(!imported.readonly);
+    let from_config = res.data.iter().find(|entry| entry.validating_pubkey == config_pubkey);
+    assert!(from_config.unwrap().readonly);
+
+    // the imported key can sign and delegate
+    let datagram = Datagram { data: 1 };
+    let request = SignRequest::builder(MODULE_ID, pubkey).with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_module_message(chain, MODULE_ID, &pubkey, &datagram, &signature).is_ok());
+
+    let delegation =
+        client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, pubkey)).await?;
+    assert_eq!(client.get_pubkeys().await?.proxy, vec![delegation.message.proxy]);
+
+    // removing the key revokes its proxies, keys from the config are read only
+    let request = DeleteKeystoresRequest { pubkeys: vec![pubkey, pubkey, config_pubkey] };
+    let res: KeystoresResponse =
+        http.delete(&url).bearer_auth(token).json(&request).send().await?.json().await?;
+    let statuses: Vec<_> = res.data.iter().map(|status| status.status).collect();
+    assert_eq!(statuses, vec![
+        KeystoreStatusKind::Deleted,
+        KeystoreStatusKind::NotFound,
+        KeystoreStatusKind::Error
+    ]);
+
+    let pubkeys = client.get_pubkeys().await?;
+    assert!(!pubkeys.consensus.contains(&pubkey));
+    assert!(pubkeys.consensus.contains(&config_pubkey));
+    assert!(pubkeys.proxy.is_empty());
+
+    let res = client.get_delegation(delegation.message.proxy).await?;
+    assert_eq!(res.status, ProxyStatus::Revoked);
+    let revocation = res.revocation.unwrap();
+    assert_eq!(revocation.message.delegator, pubkey);
+    assert!(revocation.validate(chain).is_ok());
+
+    let request = SignRequest::builder(MODULE_ID, pubkey).with_msg(&datagram);
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 404, .. })));
+
+    std::fs::remove_dir_all(dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_and_metrics() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3790;
+    start_signer(chain, port).await?;
+
+    let http = reqwest::Client::new();
+    let res = http.get(format!("http://0.0.0.0:{port}{HEALTH_PATH}")).send().await?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = http.get(format!("http://0.0.0.0:{port}{READY_PATH}")).send().await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    let ready: ReadinessResponse = res.json().await?;
+    assert!(ready.ready);
+    assert!(ready.consensus > 0);
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let consensus = client.get_pubkeys().await?.consensus[0];
+    let request = SignRequest::builder(MODULE_ID, consensus).with_msg(&Datagram { data: 1 });
+    client.request_signature(&request).await?;
+    let wrong_secret = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, OTHER_MODULE_JWT)?;
+    assert!(wrong_secret.request_signature(&request).await.is_err());
+
+    let metrics = SIGNER_METRICS_REGISTRY.gather();
+    let names: Vec<_> = metrics.iter().map(|family| family.get_name()).collect();
+    assert!(names.contains(&"cb_signer_signature_requests_total"));
+    assert!(names.contains(&"cb_signer_signing_latency"));
+    assert!(names.contains(&"cb_signer_auth_failures_total"));
+    assert!(names.contains(&"cb_signer_loaded_keys"));
+
+    // a signer without keys is alive but not ready
+    let port = port + 1;
+    let dirk_state = Arc::new(MockDirkState::new(vec![], None));
+    tokio::spawn(mock_dirk_router(dirk_state).serve(format!("0.0.0.0:{}", port + 1).parse()?));
+
+    let loader = SignerLoader::Dirk {
+        url: get_local_address(port + 1).parse()?,
+        accounts: vec!["wallet".to_owned()],
+        tls: None,
+    };
+    start_signer_with_loader(chain, port, loader).await?;
+
+    let res = http.get(format!("http://0.0.0.0:{port}{HEALTH_PATH}")).send().await?;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = http.get(format!("http://0.0.0.0:{port}{READY_PATH}")).send().await?;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!res.json::<ReadinessResponse>().await?.ready);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_threshold_signer() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3850;
+    // each pair of nodes shares its own secret
+    let pair_secret = |a: u64, b: u64| format!("secret_{}_{}", a.min(b), a.max(b));
+
+    // modules and peers connect with mTLS
+    let dir = std::env::temp_dir().join(format!("cb_signer_threshold_{}", std::process::id()));
+    let certs = generate_test_certs(&dir)?;
+
+    // 2 of 3 cluster, the second node signs with a share of another key
+    let composite_secret = random_secret()?;
+    let composite_pubkey = blst_pubkey_to_alloy(&composite_secret.sk_to_pk());
+    let mut shares = split_secret(&composite_secret, 2, &[1, 2, 3])?;
+    let share_pubkeys: Vec<_> =
+        shares.iter().map(|share| blst_pubkey_to_alloy(&share.sk_to_pk())).collect();
+    shares[1] = random_secret()?;
+    let peers: Vec<_> = (1..=3)
+        .map(|i| ThresholdPeerConfig {
+            share_index: i,
+            url: format!("https://127.0.0.1:{}", port + 4 + i as u16).parse().unwrap(),
+        })
+        .collect();
+
+    for (i, share) in shares.into_iter().enumerate() {
+        let share_index = i as u64 + 1;
+        let share_pubkey = blst_pubkey_to_alloy(&share.sk_to_pk());
+
+        // shares are held by Dirk, to load a different key in each node
+        let dirk_port = port + 10 + i as u16;
+        let dirk_state =
+            Arc::new(MockDirkState::new(vec![("wallet/share".to_owned(), share)], None));
+        tokio::spawn(mock_dirk_router(dirk_state).serve(format!("0.0.0.0:{dirk_port}").parse()?));
+
+        let loader = SignerLoader::Dirk {
+            url: get_local_address(dirk_port).parse()?,
+            accounts: vec!["wallet".to_owned()],
+            tls: None,
+        };
+        let node_peers: Vec<_> =
+            peers.iter().filter(|peer| peer.share_index != share_index).cloned().collect();
+        let mut config = signer_config(chain, port + i as u16, loader);
+        config.server_host = Ipv4Addr::LOCALHOST.into();
+        config.tls = Some(TlsConfig {
+            cert_path: certs.server_cert.clone(),
+            key_path: certs.server_key.clone(),
+            ca_cert_path: None,
+            client_ca_cert_path: Some(certs.ca_cert.clone()),
+        });
+        config.threshold = Some(ThresholdConfig {
+            share_index,
+            threshold: 2,
+            port: port + 5 + i as u16,
+            accounts: vec![ThresholdAccount {
+                pubkey: composite_pubkey,
+                share_pubkey,
+                peer_share_pubkeys: node_peers
+                    .iter()
+                    .map(|peer| share_pubkeys[peer.share_index as usize - 1])
+                    .collect(),
+            }],
+            peers: node_peers,
+            tls: Some(PeerTlsConfig {
+                ca_cert_path: certs.ca_cert.clone(),
+                cert_path: certs.client_cert.clone(),
+                key_path: certs.client_key.clone(),
+            }),
+        });
+        config.threshold_secrets = (1..=3)
+            .filter(|&peer| peer != share_index)
+            .map(|peer| (peer, pair_secret(share_index, peer)))
+            .collect();
+        run_signer(config).await?;
+    }
+
+    let tls = SignerClientTls {
+        ca_cert_path: Some(certs.ca_cert.clone()),
+        cert_path: Some(certs.client_cert.clone()),
+        key_path: Some(certs.client_key.clone()),
+    };
+    let client = SignerClient::new(format!("https://127.0.0.1:{port}"), MODULE_ID, MODULE_JWT)?
+        .with_tls(&tls)?;
+    assert_eq!(client.get_pubkeys().await?.consensus, vec![composite_pubkey]);
+
+    // the invalid partial signature of the second node is skipped
+    let datagram = Datagram { data: 1 };
+    let request = SignRequest::builder(MODULE_ID, composite_pubkey).with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_module_message(
+        chain,
+        MODULE_ID,
+        &composite_pubkey,
+        &datagram,
+        &signature
+    )
+    .is_ok());
+
+    // proxies are delegated by the composite key
+    let delegation =
+        client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, composite_pubkey)).await?;
+    assert!(delegation.validate(chain).is_ok());
+
+    // peers forward the module request, which is checked again
+    let ca_cert = reqwest::Certificate::from_pem(&fs::read(&certs.ca_cert)?)?;
+    let mut identity = fs::read(&certs.client_cert)?;
+    identity.push(b'\n');
+    identity.extend(fs::read(&certs.client_key)?);
+    let http = reqwest::Client::builder()
+        .add_root_certificate(ca_cert.clone())
+        .identity(reqwest::Identity::from_pem(&identity)?)
+        .build()?;
+    let url = format!("https://127.0.0.1:{}{PARTIAL_SIGNATURE_PATH}", port + 7);
+    let (module_jwt, _) = create_jwt(MODULE_ID, MODULE_JWT)?;
+    let request = PartialSignatureRequest {
+        pubkey: composite_pubkey,
+        request: ModuleRequest {
+            module_id: MODULE_ID.to_owned(),
+            jwt: module_jwt,
+            kind: ModuleRequestKind::Signature {
+                builder_domain: false,
+                object_root: [1; 32].into(),
+                message: None,
+            },
+        },
+    };
+    let (jwt, _) = create_jwt(&threshold_peer_id(1), &pair_secret(1, 3))?;
+    let res = http.post(&url).bearer_auth(&jwt).json(&request).send().await?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // peers need a certificate signed by the cluster CA
+    let anonymous_http = reqwest::Client::builder().add_root_certificate(ca_cert).build()?;
+    assert!(anonymous_http.post(&url).bearer_auth(&jwt).json(&request).send().await.is_err());
+
+    // each peer signs with the secret it shares with the node
+    let (wrong_jwt, _) = create_jwt(&threshold_peer_id(2), &pair_secret(1, 3))?;
+    let res = http.post(&url).bearer_auth(wrong_jwt).json(&request).send().await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let (wrong_module_jwt, _) = create_jwt(MODULE_ID, OTHER_MODULE_JWT)?;
+    let mut wrong_request = request.clone();
+    wrong_request.request.jwt = wrong_module_jwt;
+    let res = http.post(&url).bearer_auth(&jwt).json(&wrong_request).send().await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let mut builder_request = request.clone();
+    builder_request.request.kind = ModuleRequestKind::Signature {
+        builder_domain: true,
+        object_root: [1; 32].into(),
+        message: None,
+    };
+    let res = http.post(&url).bearer_auth(&jwt).json(&builder_request).send().await?;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // the Signer API is not served on the peer port
+    let url = format!("https://127.0.0.1:{}{GET_PUBKEYS_PATH}", port + 7);
+    let res = http.get(&url).bearer_auth(&jwt).send().await?;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    fs::remove_dir_all(dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dirk_signer() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 4000;
+
+    // 2 of 3 distributed account, the first participant returns invalid
+    // partial signatures
+    let composite_secret = random_secret()?;
+    let composite_pubkey = blst_pubkey_to_alloy(&composite_secret.sk_to_pk());
+    let participants = vec![(1, port + 1), (2, port + 2), (3, port + 3)];
+    let mut shares = split_secret(&composite_secret, 2, &[1, 2, 3])?.into_iter();
+
+    let simple_secret = random_secret()?;
+    let simple_pubkey = blst_pubkey_to_alloy(&simple_secret.sk_to_pk());
+
+    let accounts = [vec![("wallet/simple".to_owned(), simple_secret)], vec![], vec![]];
+    let mut states = Vec::new();
+    for (i, accounts) in accounts.into_iter().enumerate() {
+        let state = Arc::new(MockDirkState::new(
+            accounts,
+            Some(MockDistributedAccount {
+                name: "wallet/distributed".to_owned(),
+                share: shares.next().unwrap(),
+                composite_pubkey,
+                threshold: 2,
+                participants: participants.clone(),
+                faulty: i == 0,
+            }),
+        ));
+        let address = format!("0.0.0.0:{}", port + 1 + i as u16).parse()?;
+        tokio::spawn(mock_dirk_router(state.clone()).serve(address));
+        states.push(state);
+    }
+
+    let loader = SignerLoader::Dirk {
+        url: get_local_address(port + 1).parse()?,
+        accounts: vec!["wallet".to_owned()],
+        tls: None,
+    };
+    start_signer_with_loader(chain, port, loader).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let mut consensus = client.get_pubkeys().await?.consensus;
+    consensus.sort();
+    let mut expected = vec![simple_pubkey, composite_pubkey];
+    expected.sort();
+    assert_eq!(consensus, expected);
+
+    let datagram = Datagram { data: 1 };
+
+    let request = SignRequest::builder(MODULE_ID, simple_pubkey).with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_module_message(chain, MODULE_ID, &simple_pubkey, &datagram, &signature)
+        .is_ok());
+    assert_eq!(states[0].received_sign(), 1);
+
+    let request = SignRequest::builder(MODULE_ID, composite_pubkey).with_msg(&datagram);
+    let signature = client.request_signature(&request).await?;
+    assert!(verify_signed_module_message(
+        chain,
+        MODULE_ID,
+        &composite_pubkey,
+        &datagram,
+        &signature
+    )
+    .is_ok());
+    assert_eq!(states[0].received_sign(), 2);
+    assert_eq!(states[1].received_sign(), 1);
+    assert_eq!(states[2].received_sign(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_web3signer() -> Result<()> {
+    setup_test_env();
+
+    let chain = Chain::Holesky;
+    let port = 3960;
+
+    let remote_state = Arc::new(MockWeb3SignerState::new(chain, vec![random_secret()?]));
+    let remote_pubkey = remote_state.pubkeys()[0];
+    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port + 1)).await?;
+    let router = mock_web3signer_router(remote_state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let loader = SignerLoader::Remote { url: get_local_address(port + 1).parse()? };
+    start_signer_with_loader(chain, port, loader).await?;
+
+    let client = SignerClient::new(format!("0.0.0.0:{port}"), MODULE_ID, MODULE_JWT)?;
+    let privileged_client =
+        SignerClient::new(format!("0.0.0.0:{port}"), OTHER_MODULE_ID, OTHER_MODULE_JWT)?;
+    assert_eq!(client.get_pubkeys().await?.consensus, vec![remote_pubkey]);
+
+    let registration = ValidatorRegistrationMessage {
+        fee_recipient: Address::repeat_byte(1),
+        gas_limit: 30_000_000,
+        timestamp: 1_700_000_000,
+        pubkey: remote_pubkey,
+    };
+    let request = SignValidatorRegistrationRequest::new(OTHER_MODULE_ID, registration.clone());
+    let signature = privileged_client.request_validator_registration(&request).await?;
+    let signing_root = compute_signing_root(
+        compute_validator_registration_root(&registration),
+        chain.builder_domain(),
+    );
+    assert!(verify_signature(&remote_pubkey, &signing_root, &signature).is_ok());
+    assert_eq!(remote_state.received_sign(), 1);
+
+    // registrations are signed with the builder domain
+    let request = SignValidatorRegistrationRequest::new(MODULE_ID, registration);
+    let res = client.request_validator_registration(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 403, .. })));
+
+    // Web3Signer can't sign arbitrary objects, nor proxy delegations
+    let request = SignRequest::builder(MODULE_ID, remote_pubkey).with_msg(&Datagram { data: 1 });
+    let res = client.request_signature(&request).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 500, .. })));
+    let res = client.generate_proxy_key(&GenerateProxyRequest::new(MODULE_ID, remote_pubkey)).await;
+    assert!(matches!(res, Err(SignerClientError::FailedRequest { status: 500, .. })));
+    assert_eq!(remote_state.received_sign(), 1);
+
+    Ok(())
+}